@@ -1,7 +1,11 @@
-from typing import Any, Dict, List, Tuple, Union, NamedTuple, overload, Optional
+import os
+from typing import Any, BinaryIO, Dict, Iterator, List, Tuple, Union, NamedTuple, overload, Optional, TYPE_CHECKING
 import numpy as np
 from enum import IntEnum
 
+if TYPE_CHECKING:
+    import pandas as pd
+
 class DType(IntEnum):
     """Enumeration for data types."""
 
@@ -13,6 +17,27 @@ class DType(IntEnum):
     DateTime: int
     Any: int
 
+class ExcelError(IntEnum):
+    """Enumeration for the error values of cells, e.g. `#N/A` or `#DIV/0!`.
+
+    Error cells are read as `ExcelError`s with `DType.Any`, and could be written as values.
+    They are written as formulas of the error, e.g. `=#N/A`, whose cached result is stored as
+    text, read them back with `evaluate=True`.
+    """
+
+    Div0: int
+    NA: int
+    Name: int
+    Null: int
+    Num: int
+    Ref: int
+    Value: int
+    GettingData: int
+
+    def __str__(self) -> str:
+        """The error as it is shown in Excel, e.g. "#N/A"."""
+        ...
+
 class DShape:
     """Class to describe the shape of data."""
     class Scalar(NamedTuple): ...
@@ -27,20 +52,33 @@ class DShape:
         n_rows: int
         n_cols: int
 
+    class MatrixToEnd(NamedTuple):
+        """Matrix to the end of the used range of the sheet"""
+
+    class RowToEmpty(NamedTuple):
+        """Row until the first empty cell"""
+
+    class ColumnToEmpty(NamedTuple):
+        """Column until the first empty cell"""
+
 class RangeInfo:
     """Class to describe the range of data."""
 
     pos: Tuple[int, int]
     data_shape: DShape
-    dtype: DType
+    dtype: Union[DType, List[DType]]
+    """One data type for the whole range, or a list of one per column"""
     strict: bool
+    formulas: bool
+    """Whether to read the formulas instead of the values"""
     def __init__(
         self,
         pos: Tuple[int, int],
         data_shape: DShape,
         *,
-        dtype: DType = DType.Any,
+        dtype: Union[DType, List[DType]] = DType.Any,
         strict: bool = True,
+        formulas: bool = False,
     ) -> "RangeInfo":
         """Generate a RangeInfo object.
 
@@ -51,11 +89,16 @@ class RangeInfo:
             Negative refer counted from the end of the sheet.
         data_shape : DShape
             The shape of the data in the range.
-        dtype : DType, default DType.Any
-            The data type of the range.
+        dtype : DType | List[DType], default DType.Any
+            The data type of the range, or a list of one data type per column to read the
+            range as a dict of columns by their 0-based index in the range.
         strict : bool, default True
             Whether to enforce strict type checking. If True, raise error when
             the `dtype` does not match, else use default value.
+        formulas : bool, default False
+            Whether to read the formulas of the range instead of the values, as strings starting
+            with "=" or None for cells without a formula. `dtype` and `strict` are ignored, and
+            the worksheet should be got with `formulas=True`.
         """
         ...
     @property
@@ -64,15 +107,193 @@ class RangeInfo:
         ...
     @property
     def end(self) -> Tuple[int, int]:
-        """The 0-based ending position of the range as (row, col)"""
+        """The 0-based ending position of the range as (row, col), unknown for open-ended shapes"""
         ...
     @property
     def shape(self) -> Tuple[int, int]:
-        """The shape of the range as (n_rows, n_cols)"""
+        """The shape of the range as (n_rows, n_cols), unknown for open-ended shapes"""
+        ...
+    @staticmethod
+    def from_addr(
+        addr: str, *, dtype: Union[DType, List[DType]] = DType.Any, strict: bool = True
+    ) -> "RangeInfo":
+        """Generate a RangeInfo object from a cell or range address, the shape is inferred from
+        the size of the range.
+
+        Parameters
+        ----------
+        addr : str
+            The cell or range address, e.g. "B2" for `DShape.Scalar`, "B2:D2" for `DShape.Row`,
+            "B2:B10" for `DShape.Column` and "B2:D10" for `DShape.Matrix`. Whole columns or
+            rows (e.g., "A:C") span to the limits of the worksheet.
+        dtype : DType | List[DType], default DType.Any
+            The data type of the range, or a list of one data type per column.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+        """
+        ...
+    @staticmethod
+    def from_name(
+        name: str, *, dtype: Union[DType, List[DType]] = DType.Any, strict: bool = True
+    ) -> "RangeInfo":
+        """Generate a RangeInfo object referring to a defined name (named range) of the workbook.
+        The position and shape are resolved when it is read.
+
+        Parameters
+        ----------
+        name : str
+            The defined name, case-insensitive.
+        dtype : DType | List[DType], default DType.Any
+            The data type of the range, or a list of one data type per column.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+        """
+        ...
+    @property
+    def name(self) -> Optional[str]:
+        """The defined name to read instead of `pos` and `data_shape`"""
+        ...
+    def to_addr(self) -> str:
+        """Convert the range to an address string, e.g. "B2:D10"."""
+        ...
+    def offset(self, n_rows: int, n_cols: int) -> "RangeInfo":
+        """Move the range by `(n_rows, n_cols)`, the shape is kept. A position counted from the
+        end of the sheet stays counted from the end, and moving the range out of the worksheet
+        raises `ValueError`.
+
+        Parameters
+        ----------
+        n_rows : int
+            The number of rows to move down, negative to move up.
+        n_cols : int
+            The number of columns to move right, negative to move left.
+        """
+        ...
+    def resize(self, n_rows: int, n_cols: int) -> "RangeInfo":
+        """Change the size of the range, the starting position is kept and the shape is inferred
+        from the new size.
+
+        Parameters
+        ----------
+        n_rows : int
+            The new number of rows.
+        n_cols : int
+            The new number of columns.
+        """
+        ...
+    def intersect(self, other: "RangeInfo") -> Optional["RangeInfo"]:
+        """Get the overlapping part of two ranges, `dtype` and `strict` are taken from this range.
+
+        Returns
+        -------
+        Optional[RangeInfo]
+            The intersection, None if the ranges do not overlap.
+        """
+        ...
+    def union_bounds(self, other: "RangeInfo") -> "RangeInfo":
+        """Get the smallest range containing both ranges, `dtype` and `strict` are taken from
+        this range.
+        """
+        ...
+    def contains(self, cell_addr: Union[Tuple[int, int], str]) -> bool:
+        """Whether a cell is inside the range.
+
+        Parameters
+        ----------
+        cell_addr : Union[Tuple[int, int], str]
+            The cell address, either as a tuple of (row, col) or a string (e.g., "A1").
+        """
+        ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class Format:
+    """Class to describe the format of cells."""
+
+    num_format: Optional[str]
+    def __init__(
+        self,
+        *,
+        bold: bool = False,
+        italic: bool = False,
+        underline: bool = False,
+        font_name: Optional[str] = None,
+        font_size: Optional[float] = None,
+        font_color: Optional[str] = None,
+        bg_color: Optional[str] = None,
+        border: Optional[str] = None,
+        border_color: Optional[str] = None,
+        align: Optional[str] = None,
+        valign: Optional[str] = None,
+        num_format: Optional[str] = None,
+        text_wrap: bool = False,
+    ) -> "Format":
+        """Generate a Format object.
+
+        Parameters
+        ----------
+        bold : bool, default False
+            Whether to use bold font.
+        italic : bool, default False
+            Whether to use italic font.
+        underline : bool, default False
+            Whether to underline the text.
+        font_name : Optional[str], default None
+            The font name, e.g. "Calibri".
+        font_size : Optional[float], default None
+            The font size in points.
+        font_color : Optional[str], default None
+            The font color, as a "#RRGGBB" string or a color name (e.g., "red").
+        bg_color : Optional[str], default None
+            The background fill color, as a "#RRGGBB" string or a color name.
+        border : Optional[str], default None
+            The border style of all four sides, e.g. "thin", "medium", "thick", "dashed",
+            "dotted", "double".
+        border_color : Optional[str], default None
+            The border color, as a "#RRGGBB" string or a color name.
+        align : Optional[str], default None
+            The horizontal alignment, one of "general", "left", "center", "right", "fill",
+            "justify", "center_across", "distributed".
+        valign : Optional[str], default None
+            The vertical alignment, one of "top", "center", "bottom", "justify", "distributed".
+        num_format : Optional[str], default None
+            The number format, e.g. "0.00%" or "yyyy-mm-dd".
+        text_wrap : bool, default False
+            Whether to wrap the text in the cell.
+        """
         ...
 
 class ReadOnlyWorksheet:
     """Read-only worksheet class"""
+
+    title: str
+    n_rows: int
+    """Number of rows counted from the first row of the sheet, not of the used range"""
+    n_cols: int
+    """Number of columns counted from the first column of the sheet, not of the used range"""
+    errors_as_missing: bool
+    """Whether to read error cells as NaN for `DType.Float` and None for `DType.Any`"""
+    @property
+    def start(self) -> Optional[Tuple[int, int]]:
+        """The 0-based position of the first cell of the used range as (row, col), None if the
+        sheet is empty"""
+        ...
+    @property
+    def end(self) -> Optional[Tuple[int, int]]:
+        """The 0-based position of the last cell of the used range as (row, col), None if the
+        sheet is empty"""
+        ...
+    @property
+    def used_range(self) -> Optional[RangeInfo]:
+        """The used range of the sheet as a `RangeInfo` of `DShape.Matrix`, None if the sheet is
+        empty"""
+        ...
+    @property
+    def has_formulas(self) -> bool:
+        """Whether the formulas of the sheet are loaded"""
+        ...
     def read_value(self, range_info: RangeInfo) -> Any:
         """Read a single value from the worksheet based on the specified range.
 
@@ -145,51 +366,286 @@ class ReadOnlyWorksheet:
             The value read from the specified cell as the specified `dtype`.
         """
         ...
+    def formula_value(self, cell_addr: Union[Tuple[int, int], str]) -> Optional[str]:
+        """Read the formula of a specific cell in the worksheet, the worksheet should be got with
+        `formulas=True`.
+
+        Parameters
+        ----------
+        cell_addr : Union[Tuple[int, int], str]
+            The cell address, either as a tuple of (row, col) or a string (e.g., "A1").
+
+        Returns
+        -------
+        Optional[str]
+            The formula starting with "=", e.g. "=SUM(A1:A10)", None if the cell has no formula.
+        """
+        ...
+    def to_array(self, *, dtype: DType = DType.Any, strict: bool = True) -> Union[np.ndarray, List[List[Any]]]:
+        """Read the whole worksheet from A1 to the end of the used range.
+
+        Parameters
+        ----------
+        dtype : DType, default DType.Any
+            The expected data type of the values.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+
+        Returns
+        -------
+        np.ndarray | List[List[Any]]
+            The values as a 2d-array.
+        """
+        ...
+    def iter_rows(
+        self,
+        *,
+        chunk_size: int = 1000,
+        dtype: DType = DType.Any,
+        columns: Optional[List[int]] = None,
+        strict: bool = True,
+    ) -> RowIterator:
+        """Iterate over the rows of the worksheet in chunks. To stream a sheet from the file
+        without parsing it as a whole, use `ReadOnlyWorkbook.iter_rows`.
+
+        Parameters
+        ----------
+        chunk_size : int, default 1000
+            The number of rows of each chunk, the last chunk may have fewer rows.
+        dtype : DType, default DType.Any
+            The expected data type of the values.
+        columns : Optional[List[int]], default None
+            The 0-based indices of the columns to read, in the order they are returned. If None,
+            read the columns from A to the end of the used range.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+
+        Returns
+        -------
+        Iterator[np.ndarray | List[List[Any]]]
+            The chunks of rows as 2d-arrays, from the first row of the sheet to the end of the
+            used range.
+        """
+        ...
+    def to_pandas(
+        self,
+        range: Optional[Union[str, RangeInfo]] = None,
+        *,
+        header: bool = True,
+        dtypes: Optional[Union[DType, List[DType], Dict[str, DType]]] = None,
+        index_col: Optional[Union[int, str]] = None,
+        strict: bool = True,
+    ) -> "pd.DataFrame":
+        """Read a range as a pandas DataFrame, whose columns are built as typed arrays.
+
+        Parameters
+        ----------
+        range : Optional[Union[str, RangeInfo]], default None
+            The range to read, either as an address (e.g., "A1:D10") or a `RangeInfo`, whose
+            `dtype` is ignored. If None, read the used range of the worksheet.
+        header : bool, default True
+            Whether the first row of the range holds the headers of the columns. If False, the
+            columns are labeled by their 0-based index in the range.
+        dtypes : Optional[DType | List[DType] | Dict[str, DType]], default None
+            The data types of the columns, either one for all columns, a list in the order of
+            columns, or a dict by header (by index as str without header). The data types of
+            other columns are inferred from their values: numbers are read as `DType.Int` if
+            they are all integers without missing values, else as `DType.Float`, and text or
+            mixed values as `DType.Any`.
+        index_col : Optional[Union[int, str]], default None
+            The column to use as the index of the DataFrame, by its index in the range or its
+            header.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+
+        Returns
+        -------
+        pd.DataFrame
+            `DType.Int`, `DType.Float` and `DType.Bool` columns are int64, float64 and bool,
+            `DType.Date` and `DType.DateTime` columns are datetime64, other columns are object.
+            Empty cells are read as NaN, NaT or None, even in strict mode: `DType.Int` columns
+            with empty cells are float64, and `DType.Bool` and `DType.Str` columns are object.
+        """
+        ...
+
+class RowIterator:
+    """Iterator over chunks of rows of a worksheet"""
+
+    def __iter__(self) -> Iterator[Union[np.ndarray, List[List[Any]]]]: ...
+    def __next__(self) -> Union[np.ndarray, List[List[Any]]]: ...
 
 class ReadOnlyWorkbook:
     """Read-only workbook class"""
-    def __init__(self, path: str) -> "ReadOnlyWorkbook":
+
+    evaluate: bool
+    """Whether formulas are evaluated instead of reading their cached values."""
+    errors_as_missing: bool
+    """Whether to read error cells as NaN for `DType.Float` and None for `DType.Any`."""
+    cache: bool
+    """Whether to keep parsed sheets to reuse when they are got again."""
+    def __init__(
+        self,
+        path: str,
+        *,
+        evaluate: bool = False,
+        errors_as_missing: bool = False,
+        cache: bool = False,
+    ) -> "ReadOnlyWorkbook":
         """Generate a `ReadOnlyWorkbook` object.
 
         Parameters
         ----------
         path : str
-            The path to the xlsx file.
+            The path to the workbook file. Xlsx, xlsm, xlsb, xls and ods files are supported, the
+            format is detected from the content.
+        evaluate : bool, default False
+            Whether to evaluate formulas instead of reading their cached values, for files
+            written without cached values. Formulas calling unsupported functions, or referring
+            to defined names which are not defined or not supported, keep their cached values.
+        errors_as_missing : bool, default False
+            Whether to read error cells, e.g. `#N/A`, as NaN for `DType.Float` and None for
+            `DType.Any` instead of `ExcelError`s, and without raising for `DType.Float` when
+            `strict` is True.
+        cache : bool, default False
+            Whether to keep parsed sheets to reuse when they are got again, see `evict` and
+            `clear_cache`. Cached sheets stay in memory until they are evicted, so only enable it
+            when sheets are got more than once.
         """
         ...
-    def get_by_name(self, name: str) -> ReadOnlyWorksheet:
+    @staticmethod
+    def from_bytes(
+        data: Union[bytes, bytearray, memoryview, BinaryIO],
+        *,
+        evaluate: bool = False,
+        errors_as_missing: bool = False,
+        cache: bool = False,
+    ) -> "ReadOnlyWorkbook":
+        """Generate a `ReadOnlyWorkbook` object from the content of a workbook file in memory.
+
+        Parameters
+        ----------
+        data : bytes | bytearray | memoryview | BinaryIO
+            The content of the workbook file in any supported format, or a readable binary
+            file-like object, e.g. an `io.BytesIO` or a file opened with "rb". Other objects,
+            e.g. a file opened in text mode, raise `TypeError`.
+        evaluate : bool, default False
+            Whether to evaluate formulas instead of reading their cached values, for files
+            written without cached values. Formulas calling unsupported functions, or referring
+            to defined names which are not defined or not supported, keep their cached values.
+        errors_as_missing : bool, default False
+            Whether to read error cells, e.g. `#N/A`, as NaN for `DType.Float` and None for
+            `DType.Any` instead of `ExcelError`s, and without raising for `DType.Float` when
+            `strict` is True.
+        cache : bool, default False
+            Whether to keep parsed sheets to reuse when they are got again, see `evict` and
+            `clear_cache`. Cached sheets stay in memory until they are evicted, so only enable it
+            when sheets are got more than once.
+
+        Returns
+        -------
+        ReadOnlyWorkbook
+        """
+        ...
+    def table_names(self, sheet_name: Optional[str] = None) -> List[str]:
+        """Get the names of tables (ListObjects) in the workbook, which only xlsx files have.
+
+        Parameters
+        ----------
+        sheet_name : Optional[str], default None
+            Only get the tables in this sheet if specified.
+
+        Returns
+        -------
+        List[str]
+        """
+        ...
+    def read_table(
+        self,
+        name: str,
+        dtypes: Union[DType, List[DType], Dict[str, DType]] = DType.Any,
+        *,
+        strict: bool = True,
+    ) -> Dict[str, Union[np.ndarray, List[Any]]]:
+        """Read a table (ListObject) by its name.
+
+        Parameters
+        ----------
+        name : str
+            The name of the table.
+        dtypes : DType | List[DType] | Dict[str, DType], default DType.Any
+            The data types of the columns, either one for all columns, a list in the order of
+            columns, or a dict by header where missing columns are read as `DType.Any`.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+
+        Returns
+        -------
+        Dict[str, Union[np.ndarray, List[Any]]]
+            A dictionary mapping headers to the columns of data, excluding the header and
+            total rows.
+        """
+        ...
+    @property
+    def defined_names(self) -> Dict[str, str]:
+        """The defined names (named ranges) of the workbook.
+
+        Returns
+        -------
+        Dict[str, str]
+            A dictionary mapping names to what they refer to, e.g. {"rate": "Inputs!$B$2"}.
+            Names referring to a range of a sheet could be read with `RangeInfo.from_name`.
+        """
+        ...
+    @property
+    def path(self) -> Optional[str]:
+        """The path to the workbook file, None if the workbook is read from memory."""
+        ...
+    def get_by_name(self, name: str, *, formulas: bool = False) -> ReadOnlyWorksheet:
         """Get the sheet by sheet name.
 
         Parameters
         ----------
         name : str
             The name of the sheet.
+        formulas : bool, default False
+            Whether to also load the formulas of the sheet, which could then be read with
+            `formula_value` or `RangeInfo`s with `formulas=True`.
 
         Returns
         -------
         ReadOnlyWorksheet
         """
         ...
-    def get_by_idx(self, idx: int) -> ReadOnlyWorksheet:
+    def get_by_idx(self, idx: int, *, formulas: bool = False) -> ReadOnlyWorksheet:
         """Get the sheet by index.
 
         Parameters
         ----------
         idx : int
             The 0-based index of the sheet.
+        formulas : bool, default False
+            Whether to also load the formulas of the sheet, which could then be read with
+            `formula_value` or `RangeInfo`s with `formulas=True`.
 
         Returns
         -------
         ReadOnlyWorksheet
         """
         ...
-    def get(self, idx_or_name: Union[int, str]) -> ReadOnlyWorksheet:
+    def get(self, idx_or_name: Union[int, str], *, formulas: bool = False) -> ReadOnlyWorksheet:
         """Get the sheet by index or name.
 
         Parameters
         ----------
         idx_or_name : Union[int, str]
             The 0-based index or name of the sheet.
+        formulas : bool, default False
+            Whether to also load the formulas of the sheet, which could then be read with
+            `formula_value` or `RangeInfo`s with `formulas=True`.
 
         Returns
         -------
@@ -204,12 +660,28 @@ class ReadOnlyWorkbook:
     def worksheets(self) -> List[ReadOnlyWorksheet]:
         """The sheets."""
         ...
+    def evict(self, sheet: Union[int, str]):
+        """Drop a sheet from the cache, so it is parsed again when it is got next time.
+
+        Parameters
+        ----------
+        sheet : int | str
+            The 0-based index or the name of the sheet.
+        """
+        ...
+    def clear_cache(self):
+        """Drop all sheets from the cache."""
+        ...
     @overload
     def read_worksheets(
         self,
         worksheets_to_read: Dict[Union[str, int], List[RangeInfo]],
     ) -> Dict[Union[str, int], List[Any]]:
-        """Read values from multiple worksheets based on specified ranges.
+        """Read values from multiple worksheets based on specified ranges, the worksheets are parsed
+        in parallel.
+
+        Sheets of xlsx and xlsb files which are not cached are only parsed up to the last row
+        read, when the ends of all ranges read from them are known beforehand.
 
         Parameters
         ----------
@@ -250,7 +722,11 @@ class ReadOnlyWorkbook:
         self,
         worksheets_to_read: Dict[Union[str, int], Dict[str, RangeInfo]],
     ) -> Dict[Union[str, int], Dict[str, Any]]:
-        """Read values from multiple worksheets based on named ranges.
+        """Read values from multiple worksheets based on named ranges, the worksheets are parsed in
+        parallel.
+
+        Sheets of xlsx and xlsb files which are not cached are only parsed up to the last row
+        read, when the ends of all ranges read from them are known beforehand.
 
         This method reads data from one or more worksheets in the workbook, using the provided
         named ranges for each worksheet. Each named range is defined by a string key and a
@@ -290,6 +766,69 @@ class ReadOnlyWorkbook:
         }
         """
         ...
+    def read_all_sheets(
+        self, *, dtype: DType = DType.Any, strict: bool = True
+    ) -> Dict[str, Union[np.ndarray, List[List[Any]]]]:
+        """Read all sheets from A1 to the end of their used ranges, the sheets are parsed in
+        parallel.
+
+        Parameters
+        ----------
+        dtype : DType, default DType.Any
+            The expected data type of the values.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+
+        Returns
+        -------
+        Dict[str, np.ndarray | List[List[Any]]]
+            A dictionary mapping sheet names to their values as 2d-arrays.
+        """
+        ...
+    def iter_rows(
+        self,
+        sheet: Union[str, int],
+        *,
+        chunk_size: int = 1000,
+        dtype: DType = DType.Any,
+        columns: Optional[List[int]] = None,
+        strict: bool = True,
+    ) -> RowIterator:
+        """Iterate over the rows of a sheet in chunks, streaming them from the file so that the
+        whole sheet is never held in memory.
+
+        Parameters
+        ----------
+        sheet : Union[str, int]
+            The name or index of the sheet.
+        chunk_size : int, default 1000
+            The number of rows of each chunk, the last chunk may have fewer rows.
+        dtype : DType, default DType.Any
+            The expected data type of the values.
+        columns : Optional[List[int]], default None
+            The 0-based indices of the columns to read, in the order they are returned. If None,
+            read the columns from A to the end of the sheet. A streamed sheet without its
+            dimensions recorded in the file is as wide as its first chunk, and a later row
+            with a value past it raises an error.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+
+        Returns
+        -------
+        Iterator[np.ndarray | List[List[Any]]]
+            The chunks of rows as 2d-arrays, from the first row of the sheet to its last row
+            with a value.
+
+        Notes
+        -----
+        Sheets of xlsx and xlsb files are streamed by another thread, which parses the next
+        chunk while the current one is processed. Cached sheets, sheets of other formats and
+        sheets of workbooks opened with `evaluate=True` are parsed as a whole first. Streamed
+        sheets are not cached. Chart sheets have no rows and raise an error.
+        """
+        ...
 
 class WriteOnlyWorksheet:
     """Write-only worksheet class"""
@@ -302,12 +841,18 @@ class WriteOnlyWorksheet:
             The title of the worksheet.
         """
         ...
+    @property
+    def constant_memory(self) -> bool:
+        """Whether the worksheet is in constant memory mode."""
+        ...
     def write_cell(
         self,
         cell_addr: Union[Tuple[int, int], str],
         value: Any,
         *,
         dtype: Optional[DType] = None,
+        format: Optional[Format] = None,
+        num_format: Optional[str] = None,
     ):
         """Write a value to a specific cell in the worksheet.
 
@@ -319,6 +864,11 @@ class WriteOnlyWorksheet:
             The value to write to the cell.
         dtype : DType, default DType.Any
             The data type to enforce for the value. If None, try for each type automatically.
+        format : Optional[Format], default None
+            The format to apply to the cell.
+        num_format : Optional[str], default None
+            The number format to apply to the cell, e.g. "0.00%" or "dd.mm.yyyy". Override the
+            number format of `format` and the default date/datetime format of the workbook.
         """
         ...
     def write_row(
@@ -327,6 +877,8 @@ class WriteOnlyWorksheet:
         value: Union[np.ndarray, List[Any]],
         *,
         dtype: Optional[DType] = None,
+        format: Optional[Format] = None,
+        num_format: Optional[str] = None,
     ):
         """Write a row of values starting from a specific cell.
 
@@ -340,6 +892,12 @@ class WriteOnlyWorksheet:
         dtype : Optional[DType], default None
             The data type to enforce for the values. If None, will try for every possible types.\n
             DType.Any allow each item have different type, but will also increase the time cost.
+        format : Optional[Format], default None
+            The format to apply to every written cell.
+        num_format : Optional[str], default None
+            The number format to apply to every written cell, e.g. "0.00%" or "dd.mm.yyyy".
+            Override the number format of `format` and the default date/datetime format of the
+            workbook.
         """
         ...
     def write_column(
@@ -348,6 +906,8 @@ class WriteOnlyWorksheet:
         value: Union[np.ndarray, List[Any]],
         *,
         dtype: Optional[DType] = None,
+        format: Optional[Format] = None,
+        num_format: Optional[str] = None,
     ):
         """Write a column of values starting from a specific cell.
 
@@ -361,6 +921,12 @@ class WriteOnlyWorksheet:
         dtype : Optional[DType], default None
             The data type to enforce for the values. If None, will try for every possible types.\n
             DType.Any allow each item have different type, but will also increase the time cost.
+        format : Optional[Format], default None
+            The format to apply to every written cell.
+        num_format : Optional[str], default None
+            The number format to apply to every written cell, e.g. "0.00%" or "dd.mm.yyyy".
+            Override the number format of `format` and the default date/datetime format of the
+            workbook.
         """
         ...
     def write_matrix(
@@ -369,6 +935,8 @@ class WriteOnlyWorksheet:
         value: Union[np.ndarray, List[List[Any]]],
         *,
         dtype: Optional[DType] = None,
+        format: Optional[Format] = None,
+        num_format: Optional[str] = None,
     ):
         """Write a matrix of values starting from a specific cell.
 
@@ -382,19 +950,191 @@ class WriteOnlyWorksheet:
         dtype : Optional[DType], default None
             The data type to enforce for the values. If None, will try for every possible types.\n
             DType.Any allow each item have different type, but will also increase the time cost.
+        format : Optional[Format], default None
+            The format to apply to every written cell.
+        num_format : Optional[str], default None
+            The number format to apply to every written cell, e.g. "0.00%" or "dd.mm.yyyy".
+            Override the number format of `format` and the default date/datetime format of the
+            workbook.
+        """
+        ...
+
+    def write_table(
+        self,
+        cell_addr: Union[Tuple[int, int], str],
+        columns: Dict[str, Union[np.ndarray, List[Any]]],
+        *,
+        name: Optional[str] = None,
+        style: Optional[str] = None,
+        total_row: Union[bool, Dict[str, str]] = False,
+        dtypes: Optional[Union[DType, List[DType], Dict[str, DType]]] = None,
+    ):
+        """Write columns of data as an Excel table (ListObject), with a header row of the column
+        names and optional total row.
+
+        Parameters
+        ----------
+        cell_addr : Union[Tuple[int, int], str]
+            The top-left cell of the table, either as a tuple of (row, col) or a string
+            (e.g., "A1").
+        columns : Dict[str, Union[np.ndarray, List[Any]]]
+            A dictionary mapping headers to columns of values with the same length.\n
+            MUST be `np.ndarray` with correct dtype if the dtype of a column is one of
+            [`DType.Bool`, `DType.Int`, `DType.Float`]
+        name : Optional[str], default None
+            The name of the table, e.g. "Sales". Excel names it "Table1", "Table2", ... if None.
+        style : Optional[str], default None
+            The table style, e.g. "TableStyleMedium9", "Light1" or "None". Excel uses
+            "TableStyleMedium9" if None.
+        total_row : bool | Dict[str, str], default False
+            Whether to add a total row, or a dictionary mapping headers to their total, which
+            is one of the functions "sum", "average", "count", "count_numbers", "max", "min",
+            "std_dev", "var", or a label like "Total".
+        dtypes : Optional[DType | List[DType] | Dict[str, DType]], default None
+            The data types of the columns, either one for all columns, a list in the order of
+            columns, or a dict by header. If None, will try for every possible types.
+        """
+        ...
+    def write_dataframe(
+        self,
+        cell_addr: Union[Tuple[int, int], str],
+        df: Any,
+        *,
+        header: bool = True,
+        index: bool = False,
+        formats: Optional[Union[Format, Dict[str, Format]]] = None,
+    ):
+        """Write a pandas or polars DataFrame, with a header row of the column names. Each column
+        is written by the type of its values, and missing values (None, NaN, NaT) are written as
+        blank cells.
+
+        Parameters
+        ----------
+        cell_addr : Union[Tuple[int, int], str]
+            The top-left cell of the DataFrame, either as a tuple of (row, col) or a string
+            (e.g., "A1").
+        df : Union[pandas.DataFrame, polars.DataFrame]
+            The DataFrame to write. Boolean, integer, float and datetime columns are written from
+            their numpy arrays, datetime columns of polars `Date` type are written as dates.
+            Datetimes with a time zone are written as their local times, and unsigned integers
+            past the range of int64 as floats.
+        header : bool, default True
+            Whether to write the column names in the first row.
+        index : bool, default False
+            Whether to write the index of a pandas DataFrame as its first columns.
+        formats : Optional[Format | Dict[str, Format]], default None
+            The formats of the values, either one for all columns or a dict by column name.
+        """
+        ...
+    def write_formula(
+        self,
+        cell_addr: Union[Tuple[int, int], str],
+        formula: str,
+        *,
+        value: Optional[Any] = None,
+        format: Optional[Format] = None,
+    ):
+        """Write a formula to a specific cell in the worksheet.
+
+        Parameters
+        ----------
+        cell_addr : Union[Tuple[int, int], str]
+            The cell address, either as a 0-based tuple of (row, col) or a string (e.g., "A1").
+        formula : str
+            The formula to write, e.g. "=SUM(A1:A10)". The leading "=" is optional.
+        value : Optional[Any], default None
+            The cached result of the formula, shown by readers that do not recalculate.
+            If None, Excel will recalculate it when the file is opened.
+        format : Optional[Format], default None
+            The format to apply to the cell.
+        """
+        ...
+    def write_array_formula(
+        self,
+        range: Union[Tuple[Tuple[int, int], Tuple[int, int]], str],
+        formula: str,
+        *,
+        value: Optional[Any] = None,
+        format: Optional[Format] = None,
+    ):
+        """Write an array formula (CSE formula) to a range of the worksheet.
+
+        Parameters
+        ----------
+        range : Union[Tuple[Tuple[int, int], Tuple[int, int]], str]
+            The range the formula applies to, either as a 0-based tuple of (first, last) cells or
+            a string (e.g., "A1:C3").
+        formula : str
+            The array formula to write, e.g. "{=LINEST(A1:A10, B1:B10)}". The braces and leading
+            "=" are optional.
+        value : Optional[Any], default None
+            The cached result of the first cell of the range.
+        format : Optional[Format], default None
+            The format to apply to every cell of the range.
+        """
+        ...
+    def write_dynamic_array_formula(
+        self,
+        range: Union[Tuple[Tuple[int, int], Tuple[int, int]], str],
+        formula: str,
+        *,
+        value: Optional[Any] = None,
+        format: Optional[Format] = None,
+    ):
+        """Write a dynamic array formula to a range of the worksheet.
+
+        Parameters
+        ----------
+        range : Union[Tuple[Tuple[int, int], Tuple[int, int]], str]
+            The range the formula result spills into, either as a 0-based tuple of
+            (first, last) cells or a string (e.g., "A1:A10").
+        formula : str
+            The dynamic array formula to write, e.g. "=SORT(B1:B10)". The leading "=" is optional.
+        value : Optional[Any], default None
+            The cached result of the first cell of the range.
+        format : Optional[Format], default None
+            The format to apply to every cell of the range.
         """
         ...
 
 class WriteOnlyWorkbook:
     """Write-only workbook class"""
-    def __init__(self) -> "WriteOnlyWorkbook": ...
-    def create_sheet(self, name: str) -> WriteOnlyWorksheet:
+
+    date_format: str
+    datetime_format: str
+    def __init__(
+        self,
+        *,
+        date_format: str = "yyyy/mm/dd",
+        datetime_format: str = "yyyy/mm/dd hh:mm:ss",
+    ) -> "WriteOnlyWorkbook":
+        """Generate a `WriteOnlyWorkbook` object.
+
+        Parameters
+        ----------
+        date_format : str, default "yyyy/mm/dd"
+            The default number format of date cells.
+        datetime_format : str, default "yyyy/mm/dd hh:mm:ss"
+            The default number format of datetime cells.
+        """
+        ...
+    def create_sheet(self, name: str, *, constant_memory: bool = False) -> WriteOnlyWorksheet:
         """Create a new worksheet with the specified name.
 
         Parameters
         ----------
         name : str
             The name of the new worksheet.
+        constant_memory : bool, default False
+            Whether to create the worksheet in constant memory mode. Instead of being buffered
+            until `save`, data are written into the workbook immediately and each row is flushed
+            to a temporary file as soon as a later row is written. Thus:\n
+            - Writes must be in row order: writing to a row before the last written row raises
+            `ValueError`. Writing several times to the last written row is allowed.\n
+            - `write_column` and `write_matrix` move the last written row to their last row.\n
+            - The date formats of the workbook are fixed when the worksheet is created.\n
+            - The workbook could only be saved once, and the worksheet could not be used
+            with `write_many`.
 
         Returns
         -------
@@ -444,13 +1184,36 @@ class WriteOnlyWorkbook:
             The worksheet at the specified index or with the specified name.
         """
         ...
-    def save(self, path: str):
-        """Save the workbook to the specified file path.
+    def define_name(self, name: str, reference: str):
+        """Define a name (named range) of the workbook.
 
         Parameters
         ----------
-        path : str
-            The file path where the workbook will be saved.
+        name : str
+            The name, e.g. "Rate". Prefix it with a sheet name to define a name local to that
+            sheet, e.g. "Sheet1!Rate".
+        reference : str
+            What the name refers to, e.g. "=Sheet1!$B$2", "=Sheet1!$A$1:$C$10" or a constant
+            like "=0.05".
+        """
+        ...
+    def save(self, path: Union[str, os.PathLike, BinaryIO]):
+        """Save the workbook to the specified file path or file-like object.
+
+        Parameters
+        ----------
+        path : str | os.PathLike | BinaryIO
+            The file path where the workbook will be saved, or a writable binary file-like
+            object, e.g. an `io.BytesIO` or a file opened with "wb".
+        """
+        ...
+    def save_to_bytes(self) -> bytes:
+        """Save the workbook into memory.
+
+        Returns
+        -------
+        bytes
+            The content of the xlsx file.
         """
         ...
     def sheetnames(self) -> List[str]:
@@ -466,6 +1229,10 @@ class WriteOnlyWorkbook:
 @overload
 def read_many(
     workbooks_to_read: Dict[str, Dict[Union[int, str], List[RangeInfo]]],
+    *,
+    contents: Optional[Dict[str, Union[bytes, bytearray, memoryview, BinaryIO]]] = None,
+    evaluate: bool = False,
+    errors_as_missing: bool = False,
 ) -> Dict[str, Dict[Union[int, str], List[Any]]]:
     """Read values from multiple workbooks based on specified ranges.
 
@@ -478,6 +1245,13 @@ def read_many(
     workbooks_to_read : Dict[str, Dict[Union[int, str], List[RangeInfo]]]
         A dictionary mapping workbook file paths to nested dictionaries. Each nested dictionary
         maps worksheet identifiers (either by name or index) to a list of `RangeInfo` objects.
+    contents : Optional[Dict[str, Union[bytes, bytearray, memoryview, BinaryIO]]], default None
+        The content of workbooks in memory. Workbooks whose key is found here are read from
+        the content instead of from the file path.
+    evaluate : bool, default False
+        Whether to evaluate formulas instead of reading their cached values.
+    errors_as_missing : bool, default False
+        Whether to read error cells as NaN for `DType.Float` and None for `DType.Any`.
 
     Returns
     -------
@@ -520,6 +1294,10 @@ def read_many(
 @overload
 def read_many(
     workbooks_to_read: Dict[str, Dict[Union[int, str], Dict[str, RangeInfo]]],
+    *,
+    contents: Optional[Dict[str, Union[bytes, bytearray, memoryview, BinaryIO]]] = None,
+    evaluate: bool = False,
+    errors_as_missing: bool = False,
 ) -> Dict[str, Dict[Union[int, str], Dict[str, Any]]]:
     """Read values from multiple workbooks based on named ranges.
 
@@ -533,6 +1311,13 @@ def read_many(
         A dictionary mapping workbook file paths to nested dictionaries. Each nested dictionary
         maps worksheet identifiers (either by name or index) to a dictionary of named ranges (string keys
         mapped to `RangeInfo` objects).
+    contents : Optional[Dict[str, Union[bytes, bytearray, memoryview, BinaryIO]]], default None
+        The content of workbooks in memory. Workbooks whose key is found here are read from
+        the content instead of from the file path.
+    evaluate : bool, default False
+        Whether to evaluate formulas instead of reading their cached values.
+    errors_as_missing : bool, default False
+        Whether to read error cells as NaN for `DType.Float` and None for `DType.Any`.
 
     Returns
     -------
@@ -572,8 +1357,60 @@ def read_many(
     """
     ...
 
+def read_many_pandas(
+    frames_to_read: Dict[str, Dict[Union[int, str], Optional[Union[str, RangeInfo]]]],
+    *,
+    header: bool = True,
+    dtypes: Optional[Union[DType, List[DType], Dict[str, DType]]] = None,
+    index_col: Optional[Union[int, str]] = None,
+    strict: bool = True,
+    contents: Optional[Dict[str, Union[bytes, bytearray, memoryview, BinaryIO]]] = None,
+    evaluate: bool = False,
+    errors_as_missing: bool = False,
+) -> Dict[str, Dict[Union[int, str], "pd.DataFrame"]]:
+    """Read ranges of multiple workbooks as pandas DataFrames, the workbooks are read in parallel.
+
+    Parameters
+    ----------
+    frames_to_read : Dict[str, Dict[Union[int, str], Optional[Union[str, RangeInfo]]]]
+        A dictionary mapping workbook file paths to dictionaries, which map worksheet
+        identifiers (either by name or index) to the range to read, None for the used range.
+    header : bool, default True
+        Whether the first row of each range holds the headers of the columns.
+    dtypes : Optional[DType | List[DType] | Dict[str, DType]], default None
+        The data types of the columns of every range, see `ReadOnlyWorksheet.to_pandas`.
+    index_col : Optional[Union[int, str]], default None
+        The column to use as the index of every DataFrame.
+    strict : bool, default True
+        Whether to enforce strict type checking. If True, raise error when
+        the `dtype` does not match, else use default value.
+    contents : Optional[Dict[str, Union[bytes, bytearray, memoryview, BinaryIO]]], default None
+        The contents of workbooks by their key in `frames_to_read`, read instead of the file.
+    evaluate : bool, default False
+        Whether to evaluate formulas instead of reading their cached values.
+    errors_as_missing : bool, default False
+        Whether to read error cells as missing values.
+
+    Returns
+    -------
+    Dict[str, Dict[Union[int, str], pd.DataFrame]]
+        The DataFrames, mirroring the structure of `frames_to_read`, with columns typed as in
+        `ReadOnlyWorksheet.to_pandas`.
+
+    Examples
+    --------
+    >>> from fastxlsx import read_many_pandas
+    >>> frames = read_many_pandas({"workbook1.xlsx": {"Sheet1": "A1:C10", 1: None}})
+    >>> frames["workbook1.xlsx"]["Sheet1"].columns
+    Index(['Name', 'Price', 'Date'], dtype='object')
+    """
+    ...
+
 def write_many(
     workbooks_to_write: Dict[str, List[WriteOnlyWorksheet]],
+    *,
+    date_format: str = "yyyy/mm/dd",
+    datetime_format: str = "yyyy/mm/dd hh:mm:ss",
 ) -> None:
     """Write multiple workbooks to disk.
 
@@ -585,6 +1422,10 @@ def write_many(
     workbooks_to_write : Dict[str, List[WriteOnlyWorksheet]]
         A dictionary mapping workbook file paths to lists of `WriteOnlyWorksheet` objects.
         Each `WriteOnlyWorksheet` object represents a worksheet containing data to be written.
+    date_format : str, default "yyyy/mm/dd"
+        The default number format of date cells.
+    datetime_format : str, default "yyyy/mm/dd hh:mm:ss"
+        The default number format of datetime cells.
 
     Examples
     --------
@@ -603,6 +1444,33 @@ def write_many(
     """
     ...
 
+def write_many_to_bytes(
+    workbooks_to_write: Dict[str, List[WriteOnlyWorksheet]],
+    *,
+    date_format: str = "yyyy/mm/dd",
+    datetime_format: str = "yyyy/mm/dd hh:mm:ss",
+) -> Dict[str, bytes]:
+    """Write multiple workbooks into memory.
+
+    The same as `write_many`, but the workbooks are returned instead of being written to disk.
+
+    Parameters
+    ----------
+    workbooks_to_write : Dict[str, List[WriteOnlyWorksheet]]
+        A dictionary mapping workbook keys (e.g. file names) to lists of `WriteOnlyWorksheet`
+        objects.
+    date_format : str, default "yyyy/mm/dd"
+        The default number format of date cells.
+    datetime_format : str, default "yyyy/mm/dd hh:mm:ss"
+        The default number format of datetime cells.
+
+    Returns
+    -------
+    Dict[str, bytes]
+        A dictionary mapping the keys to the content of the xlsx files.
+    """
+    ...
+
 def idx_to_addr(row: int, col: int) -> str:
     """Convert a 0-based (row, col) index to a cell address string (e.g., "A1").
 
@@ -626,7 +1494,9 @@ def addr_to_idx(addr: str) -> Tuple[int, int]:
     Parameters
     ----------
     addr : str
-        The cell address string (e.g., "A1").
+        The cell address string. Lowercase (e.g., "a1"), absolute (e.g., "$A$1"),
+        R1C1 (e.g., "R1C1") and sheet-qualified (e.g., "'My Sheet'!A1") addresses are
+        also accepted, the sheet name is ignored.
 
     Returns
     -------
@@ -634,3 +1504,40 @@ def addr_to_idx(addr: str) -> Tuple[int, int]:
         A tuple of (row, col) indices.
     """
     ...
+
+def range_to_idx(addr: str) -> Tuple[Tuple[int, int], Tuple[int, int]]:
+    """Convert a range address string (e.g., "A1:C10") to 0-based indices of its first and last
+    cells.
+
+    Parameters
+    ----------
+    addr : str
+        The range address string. Single cells (e.g., "B2"), whole columns (e.g., "A:C"),
+        whole rows (e.g., "1:3"), absolute (e.g., "$A$1:$C$10"), R1C1 (e.g., "R1C1:R10C3") and
+        sheet-qualified (e.g., "'My Sheet'!B2:D9") addresses are also accepted, the sheet name
+        is ignored.
+
+    Returns
+    -------
+    Tuple[Tuple[int, int], Tuple[int, int]]
+        The (row, col) indices of the top-left and bottom-right cells.
+    """
+    ...
+
+def idx_to_range(first: Tuple[int, int], last: Tuple[int, int]) -> str:
+    """Convert 0-based indices of the first and last cells to a range address string
+    (e.g., "A1:C10").
+
+    Parameters
+    ----------
+    first : Tuple[int, int]
+        The 0-based (row, col) index of the top-left cell.
+    last : Tuple[int, int]
+        The 0-based (row, col) index of the bottom-right cell.
+
+    Returns
+    -------
+    str
+        The range address string, or a cell address string if `first` equals `last`.
+    """
+    ...
@@ -0,0 +1,270 @@
+use crate::types::{Array1Container, CalamineData, IdxOrName, ValueContainer, WrappedValue};
+use crate::utils::adjust_idx;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use ndarray::Array1;
+use numpy::datetime::{units, Datetime};
+use numpy::PyArray1;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// The value numpy stores for NaT in datetime64 arrays
+const NAT: i64 = i64::MIN;
+
+/// A column of a DataFrame read from a worksheet
+pub enum FrameColumn {
+    Values(WrappedValue),
+    /// Microseconds since the epoch, None for missing values
+    DateTimes(Vec<Option<i64>>),
+}
+impl<'py> IntoPyObject<'py> for FrameColumn {
+    type Target = PyAny;
+    type Output = Bound<'py, Self::Target>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, PyErr> {
+        match self {
+            FrameColumn::Values(v) => v.into_pyobject(py),
+            FrameColumn::DateTimes(v) => {
+                let v = v
+                    .into_iter()
+                    .map(|t| Datetime::<units::Microseconds>::from(t.unwrap_or(NAT)))
+                    .collect::<Vec<_>>();
+                Ok(PyArray1::from_vec(py, v).into_any())
+            }
+        }
+    }
+}
+
+/// The columns of a DataFrame read from a worksheet
+pub struct FrameData {
+    /// None if the range has no header row, the columns are then labeled by their index
+    pub headers: Option<Vec<String>>,
+    pub columns: Vec<FrameColumn>,
+}
+impl FrameData {
+    /// Build a pandas DataFrame, with the column `index_col` as its index
+    pub fn to_pandas(self, py: Python<'_>, index_col: Option<&IdxOrName>) -> PyResult<PyObject> {
+        let pandas = py.import("pandas")?;
+        let n_cols = self.columns.len();
+        // Columns are labeled by their index first, as headers may be repeated
+        let columns = PyDict::new(py);
+        for (j, column) in self.columns.into_iter().enumerate() {
+            columns.set_item(j, column)?;
+        }
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("copy", false)?;
+        let df = pandas.call_method("DataFrame", (columns,), Some(&kwargs))?;
+        let index_col = match index_col {
+            Some(IdxOrName::Idx(idx)) => match adjust_idx(*idx, n_cols) {
+                j if j < n_cols => Some(j),
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "No column at index {idx} of {n_cols} columns"
+                    )))
+                }
+            },
+            Some(IdxOrName::Name(name)) => {
+                let position = self
+                    .headers
+                    .as_ref()
+                    .and_then(|headers| headers.iter().position(|h| h == name));
+                match position {
+                    Some(j) => Some(j),
+                    None => {
+                        return Err(PyValueError::new_err(format!("Unknown column: \"{name}\"")))
+                    }
+                }
+            }
+            None => None,
+        };
+        if let Some(headers) = self.headers {
+            df.setattr("columns", headers)?;
+        }
+        let df = match index_col {
+            Some(j) => {
+                let label = df.getattr("columns")?.get_item(j)?;
+                df.call_method1("set_index", (label,))?
+            }
+            None => df,
+        };
+        Ok(df.unbind())
+    }
+}
+
+/// The headers and columns of a pandas or polars DataFrame to write, with the index of a pandas
+/// DataFrame as its first columns if `index` is true.
+pub fn frame_to_write(
+    df: &Bound<'_, PyAny>,
+    index: bool,
+) -> PyResult<(Vec<String>, Vec<WrappedValue>)> {
+    let df = match index {
+        true if df.hasattr("reset_index")? => df.call_method0("reset_index")?,
+        true => {
+            return Err(PyValueError::new_err(
+                "Only pandas DataFrames have an index to write",
+            ))
+        }
+        false => df.clone(),
+    };
+    // polars DataFrames list their columns, pandas DataFrames iterate (label, column) pairs
+    let series = if df.hasattr("get_columns")? {
+        df.call_method0("get_columns")?
+            .try_iter()?
+            .collect::<PyResult<Vec<_>>>()?
+    } else {
+        df.call_method0("items")?
+            .try_iter()?
+            .map(|item| item?.get_item(1))
+            .collect::<PyResult<Vec<_>>>()?
+    };
+    let headers = series
+        .iter()
+        .map(|s| s.getattr("name")?.str()?.extract::<String>())
+        .collect::<PyResult<Vec<_>>>()?;
+    let columns = series
+        .iter()
+        .zip(&headers)
+        .map(|(s, header)| column_to_write(s, header))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok((headers, columns))
+}
+
+/// The datetimes of a pandas or polars Series as their local times if they have a time zone,
+/// which Excel has no notion of. None for other Series.
+fn local_datetimes<'py>(series: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyAny>>> {
+    // pandas `DatetimeTZDtype` has a `tz`, polars `Datetime` has a `time_zone`
+    let dtype = series.getattr("dtype")?;
+    let has_tz = ["tz", "time_zone"]
+        .iter()
+        .any(|attr| dtype.getattr(*attr).is_ok_and(|tz| !tz.is_none()));
+    if !has_tz {
+        return Ok(None);
+    }
+    let dt = series.getattr("dt")?;
+    let none = series.py().None();
+    match dt.hasattr("replace_time_zone")? {
+        true => dt.call_method1("replace_time_zone", (none,)).map(Some),
+        false => dt.call_method1("tz_localize", (none,)).map(Some),
+    }
+}
+
+/// Convert a pandas or polars Series by the kind of its numpy dtype, missing values are written
+/// as blank cells.
+fn column_to_write(series: &Bound<'_, PyAny>, header: &str) -> PyResult<WrappedValue> {
+    let local = local_datetimes(series)?;
+    let series = local.as_ref().unwrap_or(series);
+    let array = series.call_method0("to_numpy")?;
+    let dtype = array.getattr("dtype")?;
+    let kind = dtype.getattr("kind")?.extract::<char>()?;
+    match kind {
+        'b' => Ok(WrappedValue::Bool(ValueContainer::Array1(array.extract()?))),
+        'i' | 'u' => {
+            // Excel stores numbers as floats, so unsigned integers past the range of int64 are
+            // written as floats instead of wrapping around
+            let past_i64 = kind == 'u'
+                && array.len()? > 0
+                && array.call_method0("max")?.extract::<u64>()? > i64::MAX as u64;
+            match past_i64 {
+                true => Ok(WrappedValue::Float(ValueContainer::Array1(
+                    array.call_method1("astype", ("float64",))?.extract()?,
+                ))),
+                false => Ok(WrappedValue::Int(ValueContainer::Array1(
+                    array.call_method1("astype", ("int64",))?.extract()?,
+                ))),
+            }
+        }
+        'f' => {
+            let values: Array1Container<f64> =
+                array.call_method1("astype", ("float64",))?.extract()?;
+            if values.value.iter().any(|v| v.is_nan()) {
+                Ok(WrappedValue::Any(ValueContainer::Array1(values.mapv(
+                    |v| match v.is_nan() {
+                        true => CalamineData::Empty,
+                        false => CalamineData::Float(v),
+                    },
+                ))))
+            } else {
+                Ok(WrappedValue::Float(ValueContainer::Array1(values)))
+            }
+        }
+        'M' => {
+            let is_date = dtype.getattr("str")?.extract::<String>()?.ends_with("[D]");
+            let micros: Array1Container<i64> = array
+                .call_method1("astype", ("datetime64[us]",))?
+                .call_method1("view", ("int64",))?
+                .extract()?;
+            // NaT is stored as the smallest i64, which is out of the range of chrono
+            let values = micros.mapv(|t| DateTime::from_timestamp_micros(t).map(|t| t.naive_utc()));
+            Ok(datetimes_to_write(values, is_date))
+        }
+        'U' => Ok(WrappedValue::Str(ValueContainer::Array1(
+            array.call_method0("tolist")?.extract()?,
+        ))),
+        'O' => {
+            let missing = match series.hasattr("is_null")? {
+                true => series.call_method0("is_null")?,
+                false => series.call_method0("isna")?,
+            };
+            let missing: Array1Container<bool> = missing.call_method0("to_numpy")?.extract()?;
+            if !missing.value.iter().any(|&m| m) {
+                if let Ok(values) = array.extract::<Array1Container<String>>() {
+                    return Ok(WrappedValue::Str(ValueContainer::Array1(values)));
+                }
+            }
+            let values = array
+                .try_iter()?
+                .zip(missing.value.iter())
+                .enumerate()
+                .map(|(i, (value, &is_missing))| match is_missing {
+                    true => Ok(CalamineData::Empty),
+                    false => {
+                        let value = value?;
+                        value.extract::<CalamineData>().map_err(|_| {
+                            let has_tz = value.getattr("tzinfo").is_ok_and(|tz| !tz.is_none());
+                            match has_tz {
+                                true => PyTypeError::new_err(format!(
+                                    "Time zone aware datetime at row {i} of column \"{header}\", \
+                                     convert it to a naive datetime to write it"
+                                )),
+                                false => PyTypeError::new_err(format!(
+                                    "Unsupported value at row {i} of column \"{header}\""
+                                )),
+                            }
+                        })
+                    }
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(WrappedValue::Any(ValueContainer::Array1(
+                Array1Container::new(Array1::from_vec(values)),
+            )))
+        }
+        _ => Err(PyTypeError::new_err(format!(
+            "Unsupported dtype {} of column \"{header}\"",
+            dtype.str()?
+        ))),
+    }
+}
+
+/// Write datetimes as dates or datetimes, missing values as blank cells
+fn datetimes_to_write(
+    values: Array1Container<Option<NaiveDateTime>>,
+    is_date: bool,
+) -> WrappedValue {
+    if values.value.iter().any(Option::is_none) {
+        let values = values.mapv(|t| match t {
+            Some(t) if is_date => CalamineData::Date(t.date()),
+            Some(t) => CalamineData::DateTime(t),
+            None => CalamineData::Empty,
+        });
+        return WrappedValue::Any(ValueContainer::Array1(values));
+    }
+    match is_date {
+        true => WrappedValue::Date(ValueContainer::Array1(
+            values.mapv(|t| t.map_or(NaiveDate::MIN, |t| t.date())),
+        )),
+        false => WrappedValue::DateTime(ValueContainer::Array1(
+            values.mapv(|t| t.unwrap_or(NaiveDateTime::MIN)),
+        )),
+    }
+}
@@ -0,0 +1,566 @@
+use super::parser::Expr;
+use super::{Array, EvalResult, Evaluator, Operand, Value, EMPTY, MAX_NAME_DEPTH};
+use calamine::CellErrorType;
+use chrono::{Datelike, Local, Months, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::mem::discriminant;
+
+/// The functions which could be evaluated, formulas calling others keep their cached values
+const FUNCTIONS: [&str; 28] = [
+    "ABS",
+    "AND",
+    "AVERAGE",
+    "COUNT",
+    "COUNTA",
+    "DATE",
+    "DAY",
+    "DAYS",
+    "EDATE",
+    "EOMONTH",
+    "HLOOKUP",
+    "IF",
+    "IFERROR",
+    "INDEX",
+    "MATCH",
+    "MAX",
+    "MIN",
+    "MONTH",
+    "NOT",
+    "NOW",
+    "OR",
+    "ROUND",
+    "SUM",
+    "SUMPRODUCT",
+    "TODAY",
+    "VLOOKUP",
+    "WEEKDAY",
+    "YEAR",
+];
+
+/// Whether all functions called in an expression could be evaluated, following the formulas
+/// of defined names, `depth` is the depth of defined names. Formulas referring to names which
+/// are not defined or not supported, e.g. dynamic ranges with OFFSET, keep their cached values.
+pub fn is_supported(expr: &Expr, names: &HashMap<String, Option<Expr>>, depth: usize) -> bool {
+    let supported = |e: &Expr| is_supported(e, names, depth);
+    match expr {
+        Expr::Call(name, args) => FUNCTIONS.contains(&name.as_str()) && args.iter().all(supported),
+        Expr::Name(name) => match names.get(&name.to_lowercase()) {
+            Some(Some(e)) if depth < MAX_NAME_DEPTH => is_supported(e, names, depth + 1),
+            _ => false,
+        },
+        Expr::Array(rows) => rows.iter().flatten().all(supported),
+        Expr::Neg(e) | Expr::Percent(e) => supported(e),
+        Expr::Binary(_, a, b) => supported(a) && supported(b),
+        _ => true,
+    }
+}
+
+pub fn call(ev: &Evaluator, name: &str, args: &[Expr], sheet: usize, depth: usize) -> Operand {
+    let eval = |expr: &Expr| ev.eval(expr, sheet, depth);
+    // Only the chosen branch is evaluated
+    match (name, args) {
+        ("IF", [condition, rest @ ..]) if (1..=2).contains(&rest.len()) => {
+            match eval(condition).into_value().as_bool() {
+                Ok(true) => rest.first().map_or(Value::Bool(true).into(), eval),
+                Ok(false) => rest.get(1).map_or(Value::Bool(false).into(), eval),
+                Err(e) => Value::Error(e).into(),
+            }
+        }
+        ("IFERROR", [value, fallback]) => {
+            let is_error = |v: &Value| matches!(v, Value::Error(_));
+            match eval(value) {
+                Operand::Value(v) if is_error(&v) => eval(fallback),
+                Operand::Array(a) if a.values().any(is_error) => {
+                    let fallback = eval(fallback).into_value();
+                    Operand::Array(a.map(|v| {
+                        if is_error(v) {
+                            fallback.clone()
+                        } else {
+                            v.clone()
+                        }
+                    }))
+                }
+                operand => operand,
+            }
+        }
+        ("IF" | "IFERROR", _) => Value::Error(CellErrorType::Value).into(),
+        _ => {
+            let args = args.iter().map(eval).collect();
+            call_eager(name, args).unwrap_or_else(|e| Value::Error(e).into())
+        }
+    }
+}
+
+fn call_eager(name: &str, mut args: Vec<Operand>) -> EvalResult<Operand> {
+    let value = match name {
+        "SUM" => Value::number(numbers(&args)?.iter().sum()),
+        "AVERAGE" => {
+            let numbers = numbers(&args)?;
+            if numbers.is_empty() {
+                return Err(CellErrorType::Div0);
+            }
+            Value::number(numbers.iter().sum::<f64>() / numbers.len() as f64)
+        }
+        "MIN" => Value::Number(numbers(&args)?.into_iter().reduce(f64::min).unwrap_or(0.0)),
+        "MAX" => Value::Number(numbers(&args)?.into_iter().reduce(f64::max).unwrap_or(0.0)),
+        "COUNT" => {
+            let is_number = |v: &Value| matches!(v, Value::Number(_));
+            Value::Number(count(&args, |v| v.as_number().is_ok(), is_number) as f64)
+        }
+        "COUNTA" => {
+            let is_filled = |v: &Value| !matches!(v, Value::Empty);
+            Value::Number(count(&args, is_filled, is_filled) as f64)
+        }
+        "SUMPRODUCT" => sum_product(args)?,
+        "AND" => Value::Bool(logicals(&args)?.into_iter().all(|b| b)),
+        "OR" => Value::Bool(logicals(&args)?.into_iter().any(|b| b)),
+        "NOT" => {
+            check_count(&args, 1, 1)?;
+            Value::Bool(!value_at(&args, 0).as_bool()?)
+        }
+        "ROUND" => {
+            check_count(&args, 2, 2)?;
+            let factor = 10_f64.powf(value_at(&args, 1).as_number()?.trunc());
+            Value::number((value_at(&args, 0).as_number()? * factor).round() / factor)
+        }
+        "ABS" => {
+            check_count(&args, 1, 1)?;
+            Value::Number(value_at(&args, 0).as_number()?.abs())
+        }
+        "VLOOKUP" => lookup(args, false)?,
+        "HLOOKUP" => lookup(args, true)?,
+        "INDEX" => return index(args),
+        "MATCH" => {
+            check_count(&args, 2, 3)?;
+            let mode = optional(&args, 2, 1.0, Value::as_number)?;
+            let array = take_array(&mut args, 1);
+            let values = if array.n_rows == 1 {
+                array
+                    .rows
+                    .first()
+                    .map(|row| row.iter().collect())
+                    .unwrap_or_default()
+            } else if array.n_cols == 1 {
+                column(&array, 0)
+            } else {
+                return Err(CellErrorType::NA);
+            };
+            let mode = match mode {
+                m if m > 0.0 => MatchMode::Ascending,
+                m if m < 0.0 => MatchMode::Descending,
+                _ => MatchMode::Exact,
+            };
+            Value::Number((lookup_position(&value_at(&args, 0), values, mode)? + 1) as f64)
+        }
+        _ => date_function(name, &args)?,
+    };
+    Ok(Operand::Value(value))
+}
+
+fn check_count(args: &[Operand], min: usize, max: usize) -> EvalResult<()> {
+    if (min..=max).contains(&args.len()) {
+        Ok(())
+    } else {
+        Err(CellErrorType::Value)
+    }
+}
+
+/// The single value of an argument, empty if it is not given
+fn value_at(args: &[Operand], i: usize) -> Value {
+    match args.get(i) {
+        Some(Operand::Value(v)) => v.clone(),
+        Some(Operand::Array(a)) => a.get(0, 0).clone(),
+        None => Value::Empty,
+    }
+}
+
+fn optional<T>(
+    args: &[Operand],
+    i: usize,
+    default: T,
+    convert: impl Fn(&Value) -> EvalResult<T>,
+) -> EvalResult<T> {
+    match args.get(i) {
+        Some(_) => convert(&value_at(args, i)),
+        None => Ok(default),
+    }
+}
+
+fn take_array(args: &mut [Operand], i: usize) -> Array {
+    std::mem::replace(&mut args[i], Operand::Value(Value::Empty)).into_array()
+}
+
+/// The values of a column which are not clamped
+fn column(array: &Array, j: usize) -> Vec<&Value> {
+    array
+        .rows
+        .iter()
+        .map(|row| row.get(j).unwrap_or(&EMPTY))
+        .collect()
+}
+
+/// The numbers of the arguments, numbers, logicals and numeric text given directly are
+/// counted, while only numbers are counted in ranges and arrays like Excel.
+fn numbers(args: &[Operand]) -> EvalResult<Vec<f64>> {
+    let mut numbers = Vec::new();
+    for arg in args {
+        match arg {
+            Operand::Value(Value::Empty) => {}
+            Operand::Value(v) => numbers.push(v.as_number()?),
+            Operand::Array(a) => {
+                for v in a.values() {
+                    match v {
+                        Value::Number(n) => numbers.push(*n),
+                        Value::Error(e) => return Err(e.clone()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    Ok(numbers)
+}
+
+/// Count the values given directly and the values in ranges and arrays by different rules
+fn count(
+    args: &[Operand],
+    counted: impl Fn(&Value) -> bool,
+    counted_in_array: impl Fn(&Value) -> bool,
+) -> usize {
+    args.iter()
+        .map(|arg| match arg {
+            Operand::Value(v) => usize::from(counted(v)),
+            Operand::Array(a) => a.values().filter(|v| counted_in_array(v)).count(),
+        })
+        .sum()
+}
+
+fn logicals(args: &[Operand]) -> EvalResult<Vec<bool>> {
+    let mut logicals = Vec::new();
+    for arg in args {
+        match arg {
+            Operand::Value(Value::Empty) => {}
+            Operand::Value(v) => logicals.push(v.as_bool()?),
+            Operand::Array(a) => {
+                for v in a.values() {
+                    match v {
+                        Value::Bool(b) => logicals.push(*b),
+                        Value::Number(n) => logicals.push(*n != 0.0),
+                        Value::Error(e) => return Err(e.clone()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    if logicals.is_empty() {
+        return Err(CellErrorType::Value);
+    }
+    Ok(logicals)
+}
+
+fn sum_product(args: Vec<Operand>) -> EvalResult<Value> {
+    let arrays = args
+        .into_iter()
+        .map(Operand::into_array)
+        .collect::<Vec<_>>();
+    let shape = arrays
+        .first()
+        .map(|a| (a.n_rows, a.n_cols))
+        .ok_or(CellErrorType::Value)?;
+    if arrays.iter().any(|a| (a.n_rows, a.n_cols) != shape) {
+        return Err(CellErrorType::Value);
+    }
+    // Clamped values are empty, so their products are 0
+    let n_rows = arrays.iter().map(|a| a.rows.len()).min().unwrap_or(0);
+    let mut total = 0.0;
+    for i in 0..n_rows {
+        for j in 0..shape.1 {
+            let mut product = 1.0;
+            for a in &arrays {
+                match a.get(i, j) {
+                    Value::Number(n) => product *= n,
+                    Value::Error(e) => return Err(e.clone()),
+                    _ => product = 0.0,
+                }
+            }
+            total += product;
+        }
+    }
+    Ok(Value::number(total))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MatchMode {
+    Exact,
+    /// The largest value not greater than the lookup value, the values are sorted ascending
+    Ascending,
+    /// The smallest value not less than the lookup value, the values are sorted descending
+    Descending,
+}
+
+/// Match text with the wildcards `*` and `?`, which could be escaped by `~`.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    enum Wild {
+        Any,
+        One,
+        Char(char),
+    }
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars().flat_map(char::to_lowercase);
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '*' => Wild::Any,
+            '?' => Wild::One,
+            '~' => Wild::Char(chars.next().unwrap_or('~')),
+            c => Wild::Char(c),
+        });
+    }
+    let text = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some(Wild::Any) => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(Wild::One) => (p, t) = (p + 1, t + 1),
+            Some(Wild::Char(c)) if *c == text[t] => (p, t) = (p + 1, t + 1),
+            // Let the last `*` match one more character
+            _ => match backtrack {
+                Some((star, from)) => {
+                    backtrack = Some((star, from + 1));
+                    (p, t) = (star + 1, from + 1);
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[p..].iter().all(|w| matches!(w, Wild::Any))
+}
+
+/// The 0-based position of the lookup value, values of other types are skipped
+fn lookup_position(lookup: &Value, values: Vec<&Value>, mode: MatchMode) -> EvalResult<usize> {
+    if let Value::Error(e) = lookup {
+        return Err(e.clone());
+    }
+    let mut found = None;
+    for (i, v) in values.into_iter().enumerate() {
+        if discriminant(v) != discriminant(lookup) {
+            continue;
+        }
+        let ordering = v.compare(lookup)?;
+        match (mode, ordering, lookup) {
+            (MatchMode::Exact, _, Value::Str(pattern)) => {
+                if let Value::Str(text) = v {
+                    if wildcard_match(pattern, text) {
+                        return Ok(i);
+                    }
+                }
+            }
+            (MatchMode::Exact, Ordering::Equal, _) => return Ok(i),
+            (MatchMode::Exact, _, _) => {}
+            (_, Ordering::Equal, _)
+            | (MatchMode::Ascending, Ordering::Less, _)
+            | (MatchMode::Descending, Ordering::Greater, _) => found = Some(i),
+            _ => break,
+        }
+    }
+    found.ok_or(CellErrorType::NA)
+}
+
+/// VLOOKUP, or HLOOKUP if `horizontal`
+fn lookup(mut args: Vec<Operand>, horizontal: bool) -> EvalResult<Value> {
+    check_count(&args, 3, 4)?;
+    let approximate = optional(&args, 3, true, Value::as_bool)?;
+    let index = value_at(&args, 2).as_number()?.trunc();
+    let mut table = take_array(&mut args, 1);
+    if horizontal {
+        // Transpose the values which are not clamped
+        let n_cols = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        table = Array {
+            n_rows: table.n_cols,
+            n_cols: table.n_rows,
+            rows: (0..n_cols)
+                .map(|j| column(&table, j).into_iter().cloned().collect())
+                .collect(),
+        };
+    }
+    if index < 1.0 {
+        return Err(CellErrorType::Value);
+    }
+    if index > table.n_cols as f64 {
+        return Err(CellErrorType::Ref);
+    }
+    let mode = if approximate {
+        MatchMode::Ascending
+    } else {
+        MatchMode::Exact
+    };
+    let i = lookup_position(&value_at(&args, 0), column(&table, 0), mode)?;
+    Ok(table.get(i, index as usize - 1).clone())
+}
+
+fn index(mut args: Vec<Operand>) -> EvalResult<Operand> {
+    check_count(&args, 2, 3)?;
+    let row = value_at(&args, 1).as_number()?.trunc();
+    let col = optional(&args, 2, None, |v| v.as_number().map(|n| Some(n.trunc())))?;
+    let array = take_array(&mut args, 0);
+    let (row, col) = match col {
+        Some(col) => (row, col),
+        // A single row is indexed by columns
+        None if array.n_rows == 1 => (1.0, row),
+        None => (row, 0.0),
+    };
+    if row < 0.0 || col < 0.0 {
+        return Err(CellErrorType::Value);
+    }
+    let (row, col) = (row as usize, col as usize);
+    if row > array.n_rows || col > array.n_cols {
+        return Err(CellErrorType::Ref);
+    }
+    // 0 means the whole row or column
+    Ok(match (row, col) {
+        (0, 0) => Operand::Array(array),
+        (0, col) => Operand::Array(array.line(col - 1, false)),
+        (row, 0) => Operand::Array(array.line(row - 1, true)),
+        (row, col) => Operand::Value(array.get(row - 1, col - 1).clone()),
+    })
+}
+
+fn ymd(year: i64, month: u32, day: u32) -> EvalResult<NaiveDate> {
+    let year = i32::try_from(year).map_err(|_| CellErrorType::Num)?;
+    NaiveDate::from_ymd_opt(year, month, day).ok_or(CellErrorType::Num)
+}
+
+/// The epoch of serial numbers, Excel counts the fictitious 1900-02-29 as day 60
+fn epoch(before_march_1900: bool) -> EvalResult<NaiveDate> {
+    if before_march_1900 {
+        ymd(1899, 12, 31)
+    } else {
+        ymd(1899, 12, 30)
+    }
+}
+
+fn serial_to_date(serial: f64) -> EvalResult<NaiveDate> {
+    // Serial numbers of 1900-01-01 to 9999-12-31
+    if !(0.0..2_958_466.0).contains(&serial) {
+        return Err(CellErrorType::Num);
+    }
+    let days = serial.trunc() as i64;
+    epoch(days < 60)?
+        .checked_add_signed(TimeDelta::try_days(days).ok_or(CellErrorType::Num)?)
+        .ok_or(CellErrorType::Num)
+}
+
+fn date_to_serial(date: NaiveDate) -> EvalResult<f64> {
+    let days = (date - epoch(date < ymd(1900, 3, 1)?)?).num_days();
+    if !(0..2_958_466).contains(&days) {
+        return Err(CellErrorType::Num);
+    }
+    Ok(days as f64)
+}
+
+/// A date as a serial number, ISO 8601 text is also accepted
+fn to_serial(value: &Value) -> EvalResult<f64> {
+    if let Value::Str(s) = value {
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return date_to_serial(date);
+        }
+        if let Ok(datetime) = s.parse::<NaiveDateTime>() {
+            let seconds = datetime.time().num_seconds_from_midnight() as f64;
+            return Ok(date_to_serial(datetime.date())? + seconds / 86_400.0);
+        }
+    }
+    value.as_number()
+}
+
+fn add_months(date: NaiveDate, months: f64) -> EvalResult<NaiveDate> {
+    let n = Months::new(months.abs().trunc() as u32);
+    if months < 0.0 {
+        date.checked_sub_months(n)
+    } else {
+        date.checked_add_months(n)
+    }
+    .ok_or(CellErrorType::Num)
+}
+
+fn date_function(name: &str, args: &[Operand]) -> EvalResult<Value> {
+    let date_at = |i: usize| serial_to_date(to_serial(&value_at(args, i))?);
+    let serial = match name {
+        "DATE" => {
+            check_count(args, 3, 3)?;
+            let [year, month, day] = [0, 1, 2].map(|i| value_at(args, i).as_number());
+            let (year, month, day) = (year? as i64, month? as i64, day? as i64);
+            // Like Excel, years 0 to 1899 are counted from 1900
+            let year = if (0..1900).contains(&year) {
+                year + 1900
+            } else {
+                year
+            };
+            // Large numbers are saturated to i64 above, so the arithmetic is checked
+            let months = year
+                .checked_mul(12)
+                .zip(month.checked_sub(1))
+                .and_then(|(y, m)| y.checked_add(m))
+                .ok_or(CellErrorType::Num)?;
+            let first = ymd(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1)?;
+            let date = TimeDelta::try_days(day.saturating_sub(1))
+                .and_then(|days| first.checked_add_signed(days))
+                .ok_or(CellErrorType::Num)?;
+            date_to_serial(date)?
+        }
+        "YEAR" | "MONTH" | "DAY" => {
+            check_count(args, 1, 1)?;
+            let date = date_at(0)?;
+            f64::from(match name {
+                "YEAR" => date.year() as u32,
+                "MONTH" => date.month(),
+                _ => date.day(),
+            })
+        }
+        "TODAY" => {
+            check_count(args, 0, 0)?;
+            date_to_serial(Local::now().date_naive())?
+        }
+        "NOW" => {
+            check_count(args, 0, 0)?;
+            let now = Local::now().naive_local();
+            let seconds = now.time().num_seconds_from_midnight() as f64;
+            date_to_serial(now.date())? + seconds / 86_400.0
+        }
+        "EDATE" | "EOMONTH" => {
+            check_count(args, 2, 2)?;
+            let date = add_months(date_at(0)?, value_at(args, 1).as_number()?)?;
+            if name == "EOMONTH" {
+                let next = add_months(ymd(date.year().into(), date.month(), 1)?, 1.0)?;
+                date_to_serial(next.pred_opt().ok_or(CellErrorType::Num)?)?
+            } else {
+                date_to_serial(date)?
+            }
+        }
+        "WEEKDAY" => {
+            check_count(args, 1, 2)?;
+            let weekday = date_at(0)?.weekday();
+            match optional(args, 1, 1.0, Value::as_number)? as i64 {
+                1 => weekday.number_from_sunday(),
+                2 => weekday.number_from_monday(),
+                3 => weekday.num_days_from_monday(),
+                _ => return Err(CellErrorType::Num),
+            }
+            .into()
+        }
+        "DAYS" => {
+            check_count(args, 2, 2)?;
+            let end = to_serial(&value_at(args, 0))?.trunc();
+            end - to_serial(&value_at(args, 1))?.trunc()
+        }
+        _ => return Err(CellErrorType::Name),
+    };
+    Ok(Value::number(serial))
+}
@@ -0,0 +1,893 @@
+mod functions;
+mod parser;
+
+use crate::types::CellRange;
+use calamine::{Cell, CellErrorType, Data, Range, Reader, Sheets};
+use parser::{parse_formula, BinaryOp, Expr};
+use pyo3::exceptions::{PyFileExistsError, PyValueError};
+use pyo3::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Seek};
+
+/// Maximum depth of defined names referring to other names
+const MAX_NAME_DEPTH: usize = 16;
+/// Maximum number of values computed by an operator on arrays, larger results are `#NUM!`
+const MAX_ARRAY_VALUES: usize = 1 << 22;
+
+type EvalResult<T> = Result<T, CellErrorType>;
+/// A cell as (sheet index, row, col)
+type CellKey = (usize, u32, u32);
+
+/// A single value during evaluation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Str(String),
+    Bool(bool),
+    Error(CellErrorType),
+    Empty,
+}
+static EMPTY: Value = Value::Empty;
+impl Value {
+    fn from_data(data: &Data) -> Self {
+        match data {
+            Data::Int(v) => Value::Number(*v as f64),
+            Data::Float(v) => Value::Number(*v),
+            Data::String(v) | Data::DateTimeIso(v) | Data::DurationIso(v) => Value::Str(v.clone()),
+            Data::Bool(v) => Value::Bool(*v),
+            Data::DateTime(v) => Value::Number(v.as_f64()),
+            Data::Error(e) => Value::Error(e.clone()),
+            Data::Empty => Value::Empty,
+        }
+    }
+    /// The result of a formula, a reference to an empty cell results in 0 like Excel
+    fn into_data(self) -> Data {
+        match self {
+            Value::Number(v) => Data::Float(v),
+            Value::Str(v) => Data::String(v),
+            Value::Bool(v) => Data::Bool(v),
+            Value::Error(e) => Data::Error(e),
+            Value::Empty => Data::Float(0.0),
+        }
+    }
+    fn number(value: f64) -> Self {
+        if value.is_finite() {
+            Value::Number(value)
+        } else {
+            Value::Error(CellErrorType::Num)
+        }
+    }
+    pub fn as_number(&self) -> EvalResult<f64> {
+        match self {
+            Value::Number(v) => Ok(*v),
+            Value::Bool(v) => Ok(f64::from(u8::from(*v))),
+            Value::Empty => Ok(0.0),
+            Value::Str(v) => v.trim().parse().map_err(|_| CellErrorType::Value),
+            Value::Error(e) => Err(e.clone()),
+        }
+    }
+    pub fn as_bool(&self) -> EvalResult<bool> {
+        match self {
+            Value::Bool(v) => Ok(*v),
+            Value::Number(v) => Ok(*v != 0.0),
+            Value::Empty => Ok(false),
+            Value::Str(v) => match v.to_uppercase().as_str() {
+                "TRUE" => Ok(true),
+                "FALSE" => Ok(false),
+                _ => Err(CellErrorType::Value),
+            },
+            Value::Error(e) => Err(e.clone()),
+        }
+    }
+    pub fn as_text(&self) -> EvalResult<String> {
+        match self {
+            Value::Number(v) => Ok(v.to_string()),
+            Value::Str(v) => Ok(v.clone()),
+            Value::Bool(v) => Ok(if *v { "TRUE" } else { "FALSE" }.to_string()),
+            Value::Empty => Ok(String::new()),
+            Value::Error(e) => Err(e.clone()),
+        }
+    }
+    /// Compare like Excel: numbers < text < logicals, text is case-insensitive and an empty
+    /// cell is 0, "" or FALSE depending on the other side.
+    pub fn compare(&self, other: &Value) -> EvalResult<Ordering> {
+        fn rank(value: &Value) -> u8 {
+            match value {
+                Value::Number(_) => 0,
+                Value::Str(_) => 1,
+                _ => 2,
+            }
+        }
+        match (self, other) {
+            (Value::Error(e), _) | (_, Value::Error(e)) => Err(e.clone()),
+            (Value::Empty, Value::Empty) => Ok(Ordering::Equal),
+            (Value::Empty, v) => Self::empty_like(v).compare(v),
+            (v, Value::Empty) => v.compare(&Self::empty_like(v)),
+            (Value::Number(a), Value::Number(b)) => Ok(a.total_cmp(b)),
+            (Value::Str(a), Value::Str(b)) => Ok(a.to_lowercase().cmp(&b.to_lowercase())),
+            (Value::Bool(a), Value::Bool(b)) => Ok(a.cmp(b)),
+            (a, b) => Ok(rank(a).cmp(&rank(b))),
+        }
+    }
+    fn empty_like(value: &Value) -> Value {
+        match value {
+            Value::Str(_) => Value::Str(String::new()),
+            Value::Bool(_) => Value::Bool(false),
+            _ => Value::Number(0.0),
+        }
+    }
+}
+
+/// A 2d-array of values from a range or an array constant.
+#[derive(Clone, Debug)]
+pub struct Array {
+    pub n_rows: usize,
+    pub n_cols: usize,
+    /// Ranges are clamped to the used range of the sheet, missing values are empty
+    rows: Vec<Vec<Value>>,
+}
+impl Array {
+    fn from_rows(rows: Vec<Vec<Value>>) -> Self {
+        Self {
+            n_rows: rows.len(),
+            n_cols: rows.iter().map(Vec::len).max().unwrap_or(0),
+            rows,
+        }
+    }
+    pub fn get(&self, i: usize, j: usize) -> &Value {
+        self.rows
+            .get(i)
+            .and_then(|row| row.get(j))
+            .unwrap_or(&EMPTY)
+    }
+    /// The values which are not clamped, in row-major order
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.rows.iter().flatten()
+    }
+    /// The number of columns which are not clamped
+    fn stored_cols(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+    /// The value at (i, j), where a single row or column is repeated to fill the shape
+    fn broadcast(&self, i: usize, j: usize) -> Value {
+        let i = if self.n_rows == 1 { 0 } else { i };
+        let j = if self.n_cols == 1 { 0 } else { j };
+        if i < self.n_rows && j < self.n_cols {
+            self.get(i, j).clone()
+        } else {
+            Value::Error(CellErrorType::NA)
+        }
+    }
+    fn map(&self, f: impl Fn(&Value) -> Value) -> Self {
+        Self {
+            n_rows: self.n_rows,
+            n_cols: self.n_cols,
+            rows: self
+                .rows
+                .iter()
+                .map(|row| row.iter().map(&f).collect())
+                .collect(),
+        }
+    }
+    /// The i-th row or column, 0-based
+    fn line(&self, i: usize, is_row: bool) -> Self {
+        if is_row {
+            Self::from_rows(vec![(0..self.n_cols)
+                .map(|j| self.get(i, j).clone())
+                .collect()])
+        } else {
+            Self::from_rows(
+                (0..self.n_rows)
+                    .map(|k| vec![self.get(k, i).clone()])
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// The result of an expression, either a single value or an array.
+#[derive(Clone, Debug)]
+pub enum Operand {
+    Value(Value),
+    Array(Array),
+}
+impl Operand {
+    /// The value, or the top-left value of an array like the anchor of a spilled formula
+    pub fn into_value(self) -> Value {
+        match self {
+            Operand::Value(v) => v,
+            Operand::Array(a) => a.get(0, 0).clone(),
+        }
+    }
+    pub fn into_array(self) -> Array {
+        match self {
+            Operand::Value(v) => Array::from_rows(vec![vec![v]]),
+            Operand::Array(a) => a,
+        }
+    }
+    fn map(self, f: impl Fn(&Value) -> Value) -> Self {
+        match self {
+            Operand::Value(v) => Operand::Value(f(&v)),
+            Operand::Array(a) => Operand::Array(a.map(f)),
+        }
+    }
+}
+impl From<Value> for Operand {
+    fn from(value: Value) -> Self {
+        Operand::Value(value)
+    }
+}
+
+fn binary_value(op: BinaryOp, a: &Value, b: &Value) -> EvalResult<Value> {
+    let arithmetic = |f: fn(f64, f64) -> f64| Ok(Value::number(f(a.as_number()?, b.as_number()?)));
+    match op {
+        BinaryOp::Add => arithmetic(|x, y| x + y),
+        BinaryOp::Sub => arithmetic(|x, y| x - y),
+        BinaryOp::Mul => arithmetic(|x, y| x * y),
+        BinaryOp::Div => {
+            let (x, y) = (a.as_number()?, b.as_number()?);
+            if y == 0.0 {
+                Err(CellErrorType::Div0)
+            } else {
+                Ok(Value::number(x / y))
+            }
+        }
+        BinaryOp::Pow => arithmetic(f64::powf),
+        BinaryOp::Concat => Ok(Value::Str(a.as_text()? + &b.as_text()?)),
+        BinaryOp::Eq => Ok(Value::Bool(a.compare(b)?.is_eq())),
+        BinaryOp::Ne => Ok(Value::Bool(a.compare(b)?.is_ne())),
+        BinaryOp::Lt => Ok(Value::Bool(a.compare(b)?.is_lt())),
+        BinaryOp::Le => Ok(Value::Bool(a.compare(b)?.is_le())),
+        BinaryOp::Gt => Ok(Value::Bool(a.compare(b)?.is_gt())),
+        BinaryOp::Ge => Ok(Value::Bool(a.compare(b)?.is_ge())),
+    }
+}
+
+/// Apply an operator element-wise if any side is an array.
+fn binary_operand(op: BinaryOp, a: Operand, b: Operand) -> Operand {
+    let apply = |a: &Value, b: &Value| binary_value(op, a, b).unwrap_or_else(Value::Error);
+    match (a, b) {
+        (Operand::Value(a), Operand::Value(b)) => Operand::Value(apply(&a, &b)),
+        (a, b) => {
+            let (a, b) = (a.into_array(), b.into_array());
+            let (n_rows, n_cols) = (a.n_rows.max(b.n_rows), a.n_cols.max(b.n_cols));
+            // Like ranges, the result is clamped to the values which are not clamped on either
+            // side, so broadcasting whole rows and columns like `1:1*A:A` stays in the used range
+            let stored_rows = a.rows.len().max(b.rows.len()).min(n_rows);
+            let stored_cols = a.stored_cols().max(b.stored_cols()).min(n_cols);
+            if stored_rows.saturating_mul(stored_cols) > MAX_ARRAY_VALUES {
+                return Operand::Value(Value::Error(CellErrorType::Num));
+            }
+            let rows = (0..stored_rows)
+                .map(|i| {
+                    (0..stored_cols)
+                        .map(|j| apply(&a.broadcast(i, j), &b.broadcast(i, j)))
+                        .collect()
+                })
+                .collect();
+            Operand::Array(Array {
+                n_rows,
+                n_cols,
+                rows,
+            })
+        }
+    }
+}
+
+/// Where the evaluator loads the values and formulas of sheets from.
+pub trait SheetSource {
+    fn load_sheet(&mut self, name: &str) -> PyResult<(Range<Data>, Range<String>)>;
+}
+impl<RS: Read + Seek> SheetSource for Sheets<RS> {
+    fn load_sheet(&mut self, name: &str) -> PyResult<(Range<Data>, Range<String>)> {
+        let to_err = |e: calamine::Error| PyErr::new::<PyFileExistsError, _>(format!("{e}"));
+        let values = self.worksheet_range(name).map_err(to_err)?;
+        let formulas = self.worksheet_formula(name).map_err(to_err)?;
+        Ok((values, formulas))
+    }
+}
+
+struct SheetData {
+    values: Range<Data>,
+    /// The formulas to evaluate by absolute (row, col), unsupported ones are left out and
+    /// keep their cached values
+    formulas: BTreeMap<(u32, u32), Expr>,
+    /// The last (row, col) of values and formulas, ranges are clamped to it
+    end: Option<(u32, u32)>,
+    results: HashMap<(u32, u32), Value>,
+}
+
+/**
+    Evaluate the formulas of a worksheet, following references to cells of other sheets.
+
+    The formulas a sheet depends on form a graph, which is sorted so every formula is evaluated
+    after the formulas it refers to. Formulas in a circular reference result in `#VALUE!`.
+*/
+pub struct Evaluator<'a> {
+    source: &'a mut dyn SheetSource,
+    sheetnames: &'a [String],
+    /// The sheets tried to load by index, None if the sheet could not be loaded
+    sheets: HashMap<usize, Option<SheetData>>,
+    /// The parsed defined names by lowercase name, None if the formula is not supported
+    names: HashMap<String, Option<Expr>>,
+}
+impl<'a> Evaluator<'a> {
+    pub fn new(
+        source: &'a mut dyn SheetSource,
+        sheetnames: &'a [String],
+        defined_names: &[(String, String)],
+    ) -> Self {
+        let names = defined_names
+            .iter()
+            .map(|(name, formula)| (name.to_lowercase(), parse_formula(formula)))
+            .collect();
+        Self {
+            source,
+            sheetnames,
+            sheets: HashMap::new(),
+            names,
+        }
+    }
+    fn sheet_idx(&self, name: &str) -> Option<usize> {
+        let name = name.to_lowercase();
+        self.sheetnames
+            .iter()
+            .position(|s| s.to_lowercase() == name)
+    }
+    fn sheet(&self, idx: usize) -> Option<&SheetData> {
+        self.sheets.get(&idx).and_then(Option::as_ref)
+    }
+    fn load_rs(&mut self, idx: usize) -> PyResult<()> {
+        if self.sheets.contains_key(&idx) {
+            return Ok(());
+        }
+        let (values, formula_range) = match self.source.load_sheet(&self.sheetnames[idx]) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.sheets.insert(idx, None);
+                return Err(e);
+            }
+        };
+        let start = formula_range.start().unwrap_or_default();
+        let formulas = formula_range
+            .used_cells()
+            .filter_map(|(i, j, formula)| {
+                let expr = parse_formula(formula)
+                    .filter(|e| functions::is_supported(e, &self.names, 0))?;
+                Some(((start.0 + i as u32, start.1 + j as u32), expr))
+            })
+            .collect();
+        let end = [values.end(), formula_range.end()]
+            .into_iter()
+            .flatten()
+            .reduce(|a, b| (a.0.max(b.0), a.1.max(b.1)));
+        let sheet = SheetData {
+            values,
+            formulas,
+            end,
+            results: HashMap::new(),
+        };
+        self.sheets.insert(idx, Some(sheet));
+        Ok(())
+    }
+    /// Collect the references of an expression, including those of defined names
+    fn collect_refs(&self, expr: &Expr, depth: usize, refs: &mut Vec<CellRange>) {
+        match expr {
+            Expr::Ref(range) => refs.push(range.clone()),
+            Expr::Name(name) if depth < MAX_NAME_DEPTH => {
+                if let Some(Some(expr)) = self.names.get(&name.to_lowercase()) {
+                    self.collect_refs(expr, depth + 1, refs);
+                }
+            }
+            Expr::Array(rows) => rows
+                .iter()
+                .flatten()
+                .for_each(|e| self.collect_refs(e, depth, refs)),
+            Expr::Neg(e) | Expr::Percent(e) => self.collect_refs(e, depth, refs),
+            Expr::Binary(_, a, b) => {
+                self.collect_refs(a, depth, refs);
+                self.collect_refs(b, depth, refs);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|e| self.collect_refs(e, depth, refs)),
+            _ => {}
+        }
+    }
+    /// The formula cells a formula cell refers to
+    fn dependencies(&mut self, key: CellKey) -> Vec<CellKey> {
+        let mut refs = Vec::new();
+        if let Some(expr) = self
+            .sheet(key.0)
+            .and_then(|s| s.formulas.get(&(key.1, key.2)))
+        {
+            self.collect_refs(expr, 0, &mut refs);
+        }
+        let mut deps = Vec::new();
+        for range in refs {
+            let idx = match &range.sheet {
+                Some(name) => match self.sheet_idx(name) {
+                    Some(idx) => idx,
+                    None => continue,
+                },
+                None => key.0,
+            };
+            // Sheets which could not be loaded are read as `#REF!`
+            let _ = self.load_rs(idx);
+            let Some(sheet) = self.sheet(idx) else {
+                continue;
+            };
+            let (first, last) = (
+                (range.first.0 as u32, range.first.1 as u32),
+                (range.last.0 as u32, range.last.1 as u32),
+            );
+            deps.extend(
+                sheet
+                    .formulas
+                    .range((first.0, 0)..=(last.0, u32::MAX))
+                    .filter(|((_, col), _)| (first.1..=last.1).contains(col))
+                    .map(|(&(row, col), _)| (idx, row, col)),
+            );
+        }
+        deps
+    }
+    /// Sort the formula cells so each one comes after the formula cells it refers to
+    fn evaluation_order(&mut self, roots: Vec<CellKey>) -> Vec<CellKey> {
+        // Depth-first search with an explicit stack, chains of formulas could be very long.
+        // A cell is false while its dependencies are visited and true once it is sorted.
+        let mut visited = HashMap::<CellKey, bool>::new();
+        let mut order = Vec::new();
+        let mut stack = roots
+            .into_iter()
+            .rev()
+            .map(|k| (k, false))
+            .collect::<Vec<_>>();
+        while let Some((key, sorted)) = stack.pop() {
+            if sorted {
+                visited.insert(key, true);
+                order.push(key);
+                continue;
+            }
+            if visited.contains_key(&key) {
+                continue;
+            }
+            visited.insert(key, false);
+            stack.push((key, true));
+            for dep in self.dependencies(key) {
+                // A visited dependency still being visited is a circular reference
+                if !visited.contains_key(&dep) {
+                    stack.push((dep, false));
+                }
+            }
+        }
+        order
+    }
+    /**
+        Evaluate all supported formulas of a sheet and return its values with the results in
+        place of the cached values.
+    */
+    pub fn evaluate_sheet(&mut self, name: &str) -> PyResult<Range<Data>> {
+        let idx = self
+            .sheet_idx(name)
+            .ok_or_else(|| PyValueError::new_err(format!("Worksheet \"{name}\" is not found")))?;
+        self.load_rs(idx)?;
+        let roots = self
+            .sheet(idx)
+            .map(|s| s.formulas.keys().map(|&(r, c)| (idx, r, c)).collect())
+            .unwrap_or_default();
+        for key in self.evaluation_order(roots) {
+            let value = match self
+                .sheet(key.0)
+                .and_then(|s| s.formulas.get(&(key.1, key.2)))
+            {
+                Some(expr) => self.eval(expr, key.0, 0).into_value(),
+                None => continue,
+            };
+            if let Some(Some(sheet)) = self.sheets.get_mut(&key.0) {
+                sheet.results.insert((key.1, key.2), value);
+            }
+        }
+        let Some(Some(SheetData {
+            values, results, ..
+        })) = self.sheets.remove(&idx)
+        else {
+            return Ok(Range::empty());
+        };
+        // Rebuild the range, the results may be outside of the range of cached values
+        let start = values.start().unwrap_or_default();
+        let mut cells = values
+            .used_cells()
+            .map(|(i, j, v)| ((start.0 + i as u32, start.1 + j as u32), v.clone()))
+            .filter(|(pos, _)| !results.contains_key(pos))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .chain(results.into_iter().map(|(pos, v)| (pos, v.into_data())))
+            .map(|(pos, v)| Cell::new(pos, v))
+            .collect::<Vec<_>>();
+        cells.sort_unstable_by_key(|c| c.get_position());
+        Ok(Range::from_sparse(cells))
+    }
+    fn cell_value(&self, sheet: &SheetData, pos: (u32, u32)) -> Value {
+        if sheet.formulas.contains_key(&pos) {
+            // Not evaluated yet only in a circular reference
+            return sheet
+                .results
+                .get(&pos)
+                .cloned()
+                .unwrap_or(Value::Error(CellErrorType::Value));
+        }
+        sheet
+            .values
+            .get_value(pos)
+            .map(Value::from_data)
+            .unwrap_or(Value::Empty)
+    }
+    fn eval_ref(&self, range: &CellRange, sheet_idx: usize) -> Operand {
+        let idx = match &range.sheet {
+            Some(name) => self.sheet_idx(name),
+            None => Some(sheet_idx),
+        };
+        let Some(sheet) = idx.and_then(|idx| self.sheet(idx)) else {
+            return Value::Error(CellErrorType::Ref).into();
+        };
+        let (first, last) = (range.first, range.last);
+        let end = sheet.end.map_or((0, 0), |e| (e.0 as usize, e.1 as usize));
+        let rows = (first.0..=last.0.min(end.0))
+            .map(|row| {
+                (first.1..=last.1.min(end.1))
+                    .map(|col| self.cell_value(sheet, (row as u32, col as u32)))
+                    .collect()
+            })
+            .collect();
+        Operand::Array(Array {
+            n_rows: last.0 - first.0 + 1,
+            n_cols: last.1 - first.1 + 1,
+            rows,
+        })
+    }
+    /// Evaluate an expression in a sheet, `depth` is the depth of defined names
+    pub fn eval(&self, expr: &Expr, sheet: usize, depth: usize) -> Operand {
+        match expr {
+            Expr::Number(v) => Value::Number(*v).into(),
+            Expr::Str(v) => Value::Str(v.clone()).into(),
+            Expr::Bool(v) => Value::Bool(*v).into(),
+            Expr::Error(e) => Value::Error(e.clone()).into(),
+            Expr::Missing => Value::Empty.into(),
+            Expr::Array(rows) => Operand::Array(Array::from_rows(
+                rows.iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|e| self.eval(e, sheet, depth).into_value())
+                            .collect()
+                    })
+                    .collect(),
+            )),
+            Expr::Ref(range) => self.eval_ref(range, sheet),
+            Expr::Name(name) => match self.names.get(&name.to_lowercase()) {
+                Some(Some(expr)) if depth < MAX_NAME_DEPTH => self.eval(expr, sheet, depth + 1),
+                _ => Value::Error(CellErrorType::Name).into(),
+            },
+            Expr::Neg(e) => self.eval(e, sheet, depth).map(|v| {
+                v.as_number()
+                    .map(|n| Value::number(-n))
+                    .unwrap_or_else(Value::Error)
+            }),
+            Expr::Percent(e) => self.eval(e, sheet, depth).map(|v| {
+                v.as_number()
+                    .map(|n| Value::number(n / 100.0))
+                    .unwrap_or_else(Value::Error)
+            }),
+            Expr::Binary(op, a, b) => {
+                binary_operand(*op, self.eval(a, sheet, depth), self.eval(b, sheet, depth))
+            }
+            Expr::Call(name, args) => functions::call(self, name, args, sheet, depth),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CellRange;
+
+    /// Sheets by name, as (address, content) where content starting with "=" is a formula
+    struct Source(Vec<(String, Vec<(String, String)>)>);
+    impl SheetSource for Source {
+        fn load_sheet(&mut self, name: &str) -> PyResult<(Range<Data>, Range<String>)> {
+            let (_, cells) = self
+                .0
+                .iter()
+                .find(|(n, _)| n == name)
+                .ok_or_else(|| PyValueError::new_err("No such sheet"))?;
+            let mut values = Vec::new();
+            let mut formulas = Vec::new();
+            for (addr, content) in cells {
+                let pos = CellRange::parse(addr).unwrap().first;
+                let pos = (pos.0 as u32, pos.1 as u32);
+                match content.strip_prefix('=') {
+                    Some(formula) => {
+                        formulas.push(Cell::new(pos, formula.to_string()));
+                        values.push(Cell::new(pos, Data::String("cached".into())));
+                    }
+                    None => values.push(Cell::new(
+                        pos,
+                        content
+                            .parse()
+                            .map(Data::Float)
+                            .unwrap_or_else(|_| Data::String(content.clone())),
+                    )),
+                }
+            }
+            values.sort_unstable_by_key(|c| c.get_position());
+            formulas.sort_unstable_by_key(|c| c.get_position());
+            Ok((Range::from_sparse(values), Range::from_sparse(formulas)))
+        }
+    }
+
+    /// Evaluate the first sheet and return the values at `addrs`
+    fn evaluate(
+        sheets: &[(&str, &[(&str, &str)])],
+        names: &[(&str, &str)],
+        addrs: &[&str],
+    ) -> Vec<Data> {
+        let mut source = Source(
+            sheets
+                .iter()
+                .map(|(name, cells)| {
+                    let cells = cells
+                        .iter()
+                        .map(|(a, c)| (a.to_string(), c.to_string()))
+                        .collect();
+                    (name.to_string(), cells)
+                })
+                .collect(),
+        );
+        let sheetnames = sheets
+            .iter()
+            .map(|(n, _)| n.to_string())
+            .collect::<Vec<_>>();
+        let names = names
+            .iter()
+            .map(|(n, f)| (n.to_string(), f.to_string()))
+            .collect::<Vec<_>>();
+        let mut evaluator = Evaluator::new(&mut source, &sheetnames, &names);
+        let range = evaluator.evaluate_sheet(&sheetnames[0]).unwrap();
+        addrs
+            .iter()
+            .map(|addr| {
+                let pos = CellRange::parse(addr).unwrap().first;
+                range
+                    .get_value((pos.0 as u32, pos.1 as u32))
+                    .cloned()
+                    .unwrap_or(Data::Empty)
+            })
+            .collect()
+    }
+
+    const TABLE: [(&str, &str); 9] = [
+        ("A1", "1"),
+        ("A2", "2"),
+        ("A3", "3"),
+        ("B1", "10"),
+        ("B2", "20"),
+        ("B3", "30"),
+        ("C1", "apple"),
+        ("C2", "banana"),
+        ("C3", "cherry"),
+    ];
+
+    /// Evaluate formulas in column F next to `TABLE`
+    fn eval_all(formulas: &[&str]) -> Vec<Data> {
+        let addrs = (0..formulas.len())
+            .map(|i| format!("F{}", i + 1))
+            .collect::<Vec<_>>();
+        let mut cells = TABLE.to_vec();
+        cells.extend(
+            addrs
+                .iter()
+                .map(String::as_str)
+                .zip(formulas.iter().copied()),
+        );
+        let addrs = addrs.iter().map(String::as_str).collect::<Vec<_>>();
+        evaluate(&[("Sheet1", &cells)], &[], &addrs)
+    }
+
+    fn eval(formula: &str) -> Data {
+        eval_all(&[formula]).remove(0)
+    }
+
+    fn num(v: f64) -> Data {
+        Data::Float(v)
+    }
+
+    fn err(e: CellErrorType) -> Data {
+        Data::Error(e)
+    }
+
+    #[test]
+    fn operator_precedence() {
+        assert_eq!(eval("=1+2*3"), num(7.0));
+        assert_eq!(eval("=(1+2)*3"), num(9.0));
+        assert_eq!(eval("=-2^2"), num(4.0));
+        assert_eq!(eval("=2^3^2"), num(64.0));
+        assert_eq!(eval("=50%*2"), num(1.0));
+        assert_eq!(eval("=1+2&3"), Data::String("33".into()));
+        assert_eq!(eval("=1<2=TRUE"), Data::Bool(true));
+        assert_eq!(eval("=\"a\"<\"B\""), Data::Bool(true));
+        assert_eq!(eval("=1/0"), err(CellErrorType::Div0));
+        assert_eq!(eval("=C1+1"), err(CellErrorType::Value));
+    }
+
+    #[test]
+    fn array_literals() {
+        assert_eq!(eval("=SUM({1,2;3,4})"), num(10.0));
+        assert_eq!(eval("=INDEX({1,2;3,4},2,1)"), num(3.0));
+        assert_eq!(eval("=SUM({1,2}*{3,4})"), num(11.0));
+        // A row broadcast by a column
+        assert_eq!(eval("=SUM({1,2}*{10;20})"), num(90.0));
+        assert_eq!(eval("=SUM({1,2,3}+{1,2})"), err(CellErrorType::NA));
+        assert_eq!(eval("={5,6}"), num(5.0));
+    }
+
+    #[test]
+    fn cyclic_dependencies() {
+        let cells = [
+            ("A1", "=B1+1"),
+            ("B1", "=A1+1"),
+            ("C1", "=C1"),
+            ("D1", "=A1"),
+        ];
+        let values = evaluate(&[("Sheet1", &cells)], &[], &["A1", "B1", "C1", "D1"]);
+        assert!(values.iter().all(|v| *v == err(CellErrorType::Value)));
+        // Formulas are evaluated after the formulas they refer to, wherever they are
+        let cells = [
+            ("A1", "=A2+1"),
+            ("A2", "=Sheet2!A1*2"),
+            ("A3", "=SUM(A1:A2)"),
+        ];
+        let other = [("A1", "=B1+1"), ("B1", "4")];
+        let values = evaluate(
+            &[("Sheet1", &cells), ("Sheet2", &other)],
+            &[],
+            &["A1", "A2", "A3"],
+        );
+        assert_eq!(values, [num(11.0), num(10.0), num(21.0)]);
+    }
+
+    #[test]
+    fn whole_column_and_row_refs() {
+        assert_eq!(eval("=SUM(A:A)"), num(6.0));
+        assert_eq!(eval("=SUM(A:B)"), num(66.0));
+        assert_eq!(eval("=SUM(2:2)"), num(22.0));
+        assert_eq!(eval("=COUNTA(C:C)"), num(3.0));
+        assert_eq!(eval("=SUMPRODUCT((A:A>1)*B:B)"), num(50.0));
+        // A whole row broadcast by a whole column is clamped to the used range
+        let cells = [
+            ("A1", "1"),
+            ("B1", "2"),
+            ("A2", "3"),
+            ("D5", "=SUM(1:1*A:A)"),
+        ];
+        assert_eq!(evaluate(&[("Sheet1", &cells)], &[], &["D5"]), [num(12.0)]);
+        assert_eq!(eval("=SUM(Sheet9!A:A)"), err(CellErrorType::Ref));
+    }
+
+    #[test]
+    fn broadcast_is_limited() {
+        let row = Array::from_rows(vec![vec![Value::Number(1.0); 16_384]]);
+        let column = Array {
+            n_rows: 1_048_576,
+            n_cols: 1,
+            rows: vec![vec![Value::Number(1.0)]; 300],
+        };
+        let result = binary_operand(
+            BinaryOp::Mul,
+            Operand::Array(row.clone()),
+            Operand::Array(column),
+        );
+        assert!(matches!(
+            result,
+            Operand::Value(Value::Error(CellErrorType::Num))
+        ));
+        let column = Array::from_rows(vec![vec![Value::Number(2.0)]; 3]);
+        let Operand::Array(result) =
+            binary_operand(BinaryOp::Mul, Operand::Array(row), Operand::Array(column))
+        else {
+            panic!("expected an array");
+        };
+        assert_eq!((result.n_rows, result.n_cols), (3, 16_384));
+        assert_eq!(result.values().count(), 3 * 16_384);
+    }
+
+    #[test]
+    fn date_functions() {
+        assert_eq!(eval("=DATE(2024,1,31)"), num(45322.0));
+        assert_eq!(eval("=DATE(2024,14,1)"), num(45689.0));
+        assert_eq!(eval("=DATE(2024,3,0)"), num(45351.0));
+        assert_eq!(eval("=DATE(124,1,31)"), num(45322.0));
+        assert_eq!(eval("=DATE(1900,3,1)"), num(61.0));
+        assert_eq!(eval("=YEAR(45322)"), num(2024.0));
+        assert_eq!(eval("=MONTH(\"2024-02-29\")"), num(2.0));
+        assert_eq!(eval("=EOMONTH(45322,1)"), num(45351.0));
+        assert_eq!(eval("=EDATE(45322,1)"), num(45351.0));
+        assert_eq!(eval("=WEEKDAY(45322,2)"), num(3.0));
+        assert_eq!(eval("=DAYS(45351,45322)"), num(29.0));
+        assert_eq!(eval("=YEAR(-1)"), err(CellErrorType::Num));
+        assert_eq!(eval("=WEEKDAY(45322,9)"), err(CellErrorType::Num));
+    }
+
+    #[test]
+    fn date_overflow() {
+        for formula in [
+            "=DATE(2020,1,1E15)",
+            "=DATE(2020,1,-1E15)",
+            "=DATE(2020,1,1E300)",
+            "=DATE(1E17,1,1)",
+            "=DATE(1E300,1,1)",
+            "=DATE(2020,-1E300,1)",
+            "=DATE(2020,1E300,1)",
+            "=EDATE(45322,1E300)",
+            "=YEAR(1E300)",
+        ] {
+            assert_eq!(eval(formula), err(CellErrorType::Num), "{formula}");
+        }
+    }
+
+    #[test]
+    fn lookup_functions() {
+        assert_eq!(
+            eval("=VLOOKUP(2,A1:C3,3,FALSE)"),
+            Data::String("banana".into())
+        );
+        assert_eq!(eval("=VLOOKUP(2.5,A1:B3,2)"), num(20.0));
+        assert_eq!(eval("=VLOOKUP(9,A1:B3,2,FALSE)"), err(CellErrorType::NA));
+        assert_eq!(eval("=VLOOKUP(1,A1:B3,4,FALSE)"), err(CellErrorType::Ref));
+        assert_eq!(eval("=VLOOKUP(1,A1:B3,0,FALSE)"), err(CellErrorType::Value));
+        assert_eq!(eval("=HLOOKUP(10,B1:B3,3,FALSE)"), num(30.0));
+        assert_eq!(eval("=MATCH(\"b*\",C1:C3,0)"), num(2.0));
+        assert_eq!(eval("=MATCH(\"B?NANA\",C:C,0)"), num(2.0));
+        assert_eq!(eval("=MATCH(25,B1:B3)"), num(2.0));
+        assert_eq!(eval("=MATCH(25,B1:B3,0)"), err(CellErrorType::NA));
+        assert_eq!(eval("=INDEX(A1:C3,3,2)"), num(30.0));
+        assert_eq!(eval("=SUM(INDEX(A1:C3,0,2))"), num(60.0));
+        assert_eq!(eval("=INDEX(A1:C3,4,1)"), err(CellErrorType::Ref));
+        assert_eq!(
+            eval("=IFERROR(VLOOKUP(9,A1:B3,2,FALSE),\"none\")"),
+            Data::String("none".into())
+        );
+    }
+
+    #[test]
+    fn defined_names() {
+        let cells = [
+            ("A1", "1"),
+            ("A2", "2"),
+            ("B1", "=Total*2"),
+            ("B2", "=SUM(Dynamic)"),
+            ("B3", "=Structured"),
+            ("B4", "=Missing+1"),
+            ("B5", "=Outer"),
+            ("B6", "=FOO(1)"),
+        ];
+        let names = [
+            ("Total", "SUM(Sheet1!$A$1:$A$2)"),
+            ("Dynamic", "OFFSET(Sheet1!$A$1,0,0,2,1)"),
+            ("Structured", "Table1[Column]"),
+            ("Outer", "Inner*10"),
+            ("Inner", "Sheet1!$A$2"),
+        ];
+        let values = evaluate(
+            &[("Sheet1", &cells)],
+            &names,
+            &["B1", "B2", "B3", "B4", "B5", "B6"],
+        );
+        let cached = Data::String("cached".into());
+        assert_eq!(values[0], num(6.0));
+        // Names which are unsupported or not defined keep the cached values
+        assert_eq!(
+            values[1..4],
+            [cached.clone(), cached.clone(), cached.clone()]
+        );
+        assert_eq!(values[4], num(20.0));
+        assert_eq!(values[5], cached);
+    }
+}
@@ -0,0 +1,471 @@
+use crate::types::CellRange;
+use calamine::CellErrorType;
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+/// Maximum nesting of parentheses, function calls, array constants and unary operators, like
+/// Excel
+const MAX_DEPTH: usize = 64;
+
+const ERRORS: [(&str, CellErrorType); 8] = [
+    ("#NULL!", CellErrorType::Null),
+    ("#DIV/0!", CellErrorType::Div0),
+    ("#VALUE!", CellErrorType::Value),
+    ("#REF!", CellErrorType::Ref),
+    ("#NAME?", CellErrorType::Name),
+    ("#NUM!", CellErrorType::Num),
+    ("#N/A", CellErrorType::NA),
+    ("#GETTING_DATA", CellErrorType::GettingData),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A parsed formula.
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Number(f64),
+    Str(String),
+    Bool(bool),
+    Error(CellErrorType),
+    /// An argument left out, e.g. the last one of `IF(A1,1,)`
+    Missing,
+    /// An array constant like `{1,2;3,4}`, by rows
+    Array(Vec<Vec<Expr>>),
+    Ref(CellRange),
+    /// A defined name
+    Name(String),
+    Neg(Box<Expr>),
+    Percent(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// A function call by the upper case name
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Bool(bool),
+    Error(CellErrorType),
+    Ref(CellRange),
+    Name(String),
+    Func(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+    Semicolon,
+    LBrace,
+    RBrace,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '$' | '\\')
+}
+
+/// Drop the prefixes of functions added in later versions of Excel, e.g. `_xlfn.IFERROR`.
+fn function_name(name: &str) -> String {
+    let upper = name.to_uppercase();
+    let name = upper.strip_prefix("_XLFN.").unwrap_or(&upper);
+    name.strip_prefix("_XLWS.").unwrap_or(name).to_string()
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+}
+impl Lexer {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+    fn text(&self, start: usize) -> String {
+        self.chars[start..self.pos].iter().collect()
+    }
+    fn skip_word(&mut self) {
+        while self.peek(0).is_some_and(is_word_char) {
+            self.pos += 1;
+        }
+    }
+    /// Skip a cell or a range like `A1`, `$A$1:$B$2`, `A:C` or `1:3`.
+    fn skip_ref(&mut self) {
+        self.skip_word();
+        if self.peek(0) == Some(':') && self.peek(1).is_some_and(is_word_char) {
+            self.pos += 1;
+            self.skip_word();
+        }
+    }
+    fn reference(&self, start: usize) -> Option<Token> {
+        CellRange::parse(&self.text(start)).ok().map(Token::Ref)
+    }
+    fn string(&mut self) -> Option<Token> {
+        // A quote in a string is escaped by doubling it
+        let mut value = String::new();
+        self.pos += 1;
+        loop {
+            match self.peek(0)? {
+                '"' if self.peek(1) == Some('"') => {
+                    value.push('"');
+                    self.pos += 2;
+                }
+                '"' => {
+                    self.pos += 1;
+                    return Some(Token::Str(value));
+                }
+                c => {
+                    value.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+    fn error(&mut self) -> Option<Token> {
+        let rest = self.chars[self.pos..]
+            .iter()
+            .collect::<String>()
+            .to_uppercase();
+        let (text, error) = ERRORS.iter().find(|(text, _)| rest.starts_with(text))?;
+        self.pos += text.chars().count();
+        Some(Token::Error(error.clone()))
+    }
+    /// A reference qualified by a quoted sheet name, e.g. `'My Sheet'!A1`
+    fn quoted_reference(&mut self) -> Option<Token> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek(0)? {
+                '\'' if self.peek(1) == Some('\'') => self.pos += 2,
+                '\'' => break,
+                _ => self.pos += 1,
+            }
+        }
+        if self.peek(1) != Some('!') {
+            return None;
+        }
+        self.pos += 2;
+        self.skip_ref();
+        self.reference(start)
+    }
+    fn number(&mut self) -> Option<Token> {
+        let start = self.pos;
+        while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.peek(0) == Some(':') && self.pos > start {
+            // Whole rows like `1:3`
+            self.pos = start;
+            self.skip_ref();
+            return self.reference(start);
+        }
+        if self.peek(0) == Some('.') {
+            self.pos += 1;
+            while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        if matches!(self.peek(0), Some('e' | 'E')) {
+            let sign = usize::from(matches!(self.peek(1), Some('+' | '-')));
+            if self.peek(1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1 + sign;
+                while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+            }
+        }
+        self.text(start).parse().ok().map(Token::Number)
+    }
+    fn word(&mut self) -> Option<Token> {
+        let start = self.pos;
+        self.skip_word();
+        let word = self.text(start);
+        if self.peek(0) == Some('!') {
+            self.pos += 1;
+            self.skip_ref();
+            return self.reference(start);
+        }
+        if self.peek(0) == Some(':') {
+            self.pos = start;
+            self.skip_ref();
+            return self.reference(start);
+        }
+        let next = self.chars[self.pos..].iter().find(|c| !c.is_whitespace());
+        if next == Some(&'(') {
+            return Some(Token::Func(function_name(&word)));
+        }
+        match word.to_uppercase().as_str() {
+            "TRUE" => Some(Token::Bool(true)),
+            "FALSE" => Some(Token::Bool(false)),
+            _ => Some(self.reference(start).unwrap_or(Token::Name(word))),
+        }
+    }
+    fn op(&mut self, op: &'static str) -> Option<Token> {
+        self.pos += op.len();
+        Some(Token::Op(op))
+    }
+    fn tokenize(mut self) -> Option<Vec<Token>> {
+        let mut tokens = Vec::new();
+        while let Some(c) = self.peek(0) {
+            let token = match c {
+                c if c.is_whitespace() => {
+                    self.pos += 1;
+                    continue;
+                }
+                '"' => self.string()?,
+                '#' => self.error()?,
+                '\'' => self.quoted_reference()?,
+                '(' | ')' | ',' | ';' | '{' | '}' => {
+                    self.pos += 1;
+                    match c {
+                        '(' => Token::LParen,
+                        ')' => Token::RParen,
+                        ',' => Token::Comma,
+                        ';' => Token::Semicolon,
+                        '{' => Token::LBrace,
+                        _ => Token::RBrace,
+                    }
+                }
+                '<' if self.peek(1) == Some('=') => self.op("<=")?,
+                '<' if self.peek(1) == Some('>') => self.op("<>")?,
+                '>' if self.peek(1) == Some('=') => self.op(">=")?,
+                '+' => self.op("+")?,
+                '-' => self.op("-")?,
+                '*' => self.op("*")?,
+                '/' => self.op("/")?,
+                '^' => self.op("^")?,
+                '&' => self.op("&")?,
+                '%' => self.op("%")?,
+                '=' => self.op("=")?,
+                '<' => self.op("<")?,
+                '>' => self.op(">")?,
+                c if c.is_ascii_digit() || c == '.' => self.number()?,
+                c if is_word_char(c) => self.word()?,
+                // e.g. structured references like `Table1[Column]`
+                _ => return None,
+            };
+            tokens.push(token);
+        }
+        Some(tokens)
+    }
+}
+
+type BinaryLevel = fn(&mut Parser) -> Option<Expr>;
+
+struct Parser {
+    tokens: Peekable<IntoIter<Token>>,
+    depth: usize,
+}
+impl Parser {
+    fn expect(&mut self, token: Token) -> Option<()> {
+        (self.tokens.next()? == token).then_some(())
+    }
+    fn nested(&mut self, parse: BinaryLevel) -> Option<Expr> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+    fn binary(&mut self, ops: &[(&str, BinaryOp)], operand: BinaryLevel) -> Option<Expr> {
+        let mut lhs = operand(self)?;
+        while let Some(Token::Op(op)) = self.tokens.peek() {
+            let Some((_, op)) = ops.iter().find(|(s, _)| s == op) else {
+                break;
+            };
+            self.tokens.next();
+            lhs = Expr::Binary(*op, Box::new(lhs), Box::new(operand(self)?));
+        }
+        Some(lhs)
+    }
+    fn comparison(&mut self) -> Option<Expr> {
+        let ops = [
+            ("=", BinaryOp::Eq),
+            ("<>", BinaryOp::Ne),
+            ("<", BinaryOp::Lt),
+            ("<=", BinaryOp::Le),
+            (">", BinaryOp::Gt),
+            (">=", BinaryOp::Ge),
+        ];
+        self.binary(&ops, Self::concat)
+    }
+    fn concat(&mut self) -> Option<Expr> {
+        self.binary(&[("&", BinaryOp::Concat)], Self::additive)
+    }
+    fn additive(&mut self) -> Option<Expr> {
+        let ops = [("+", BinaryOp::Add), ("-", BinaryOp::Sub)];
+        self.binary(&ops, Self::multiplicative)
+    }
+    fn multiplicative(&mut self) -> Option<Expr> {
+        let ops = [("*", BinaryOp::Mul), ("/", BinaryOp::Div)];
+        self.binary(&ops, Self::power)
+    }
+    fn power(&mut self) -> Option<Expr> {
+        // Like Excel, `^` is left-associative and binds looser than negation: -2^2 = 4
+        self.binary(&[("^", BinaryOp::Pow)], Self::percent)
+    }
+    fn percent(&mut self) -> Option<Expr> {
+        let mut expr = self.unary()?;
+        while self.tokens.next_if_eq(&Token::Op("%")).is_some() {
+            expr = Expr::Percent(Box::new(expr));
+        }
+        Some(expr)
+    }
+    fn unary(&mut self) -> Option<Expr> {
+        if self.tokens.next_if_eq(&Token::Op("-")).is_some() {
+            return Some(Expr::Neg(Box::new(self.nested(Self::unary)?)));
+        }
+        if self.tokens.next_if_eq(&Token::Op("+")).is_some() {
+            return self.nested(Self::unary);
+        }
+        self.primary()
+    }
+    fn arguments(&mut self) -> Option<Vec<Expr>> {
+        self.expect(Token::LParen)?;
+        let mut args = Vec::new();
+        if self.tokens.next_if_eq(&Token::RParen).is_some() {
+            return Some(args);
+        }
+        loop {
+            let arg = match self.tokens.peek()? {
+                Token::Comma | Token::RParen => Expr::Missing,
+                _ => self.nested(Self::comparison)?,
+            };
+            args.push(arg);
+            match self.tokens.next()? {
+                Token::Comma => continue,
+                Token::RParen => return Some(args),
+                _ => return None,
+            }
+        }
+    }
+    fn array(&mut self) -> Option<Expr> {
+        let mut rows = vec![Vec::new()];
+        loop {
+            rows.last_mut()?.push(self.unary()?);
+            match self.tokens.next()? {
+                Token::Comma => continue,
+                Token::Semicolon => rows.push(Vec::new()),
+                Token::RBrace => return Some(Expr::Array(rows)),
+                _ => return None,
+            }
+        }
+    }
+    fn primary(&mut self) -> Option<Expr> {
+        match self.tokens.next()? {
+            Token::Number(n) => Some(Expr::Number(n)),
+            Token::Str(s) => Some(Expr::Str(s)),
+            Token::Bool(b) => Some(Expr::Bool(b)),
+            Token::Error(e) => Some(Expr::Error(e)),
+            Token::Ref(range) => Some(Expr::Ref(range)),
+            Token::Name(name) => Some(Expr::Name(name)),
+            Token::Func(name) => Some(Expr::Call(name, self.arguments()?)),
+            Token::LParen => {
+                let expr = self.nested(Self::comparison)?;
+                self.expect(Token::RParen)?;
+                Some(expr)
+            }
+            Token::LBrace => self.nested(Self::array),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a formula with or without the leading "=", None if it is not supported.
+pub fn parse_formula(formula: &str) -> Option<Expr> {
+    let formula = formula.trim();
+    let formula = formula.strip_prefix('=').unwrap_or(formula);
+    let lexer = Lexer {
+        chars: formula.chars().collect(),
+        pos: 0,
+    };
+    let mut parser = Parser {
+        tokens: lexer.tokenize()?.into_iter().peekable(),
+        depth: 0,
+    };
+    let expr = parser.comparison()?;
+    parser.tokens.next().is_none().then_some(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(formula: &str) -> Expr {
+        parse_formula(formula).unwrap()
+    }
+
+    #[test]
+    fn precedence() {
+        // 1+2*3 is 1+(2*3)
+        let Expr::Binary(BinaryOp::Add, _, rhs) = parse("=1+2*3") else {
+            panic!("expected an addition");
+        };
+        assert!(matches!(*rhs, Expr::Binary(BinaryOp::Mul, _, _)));
+        // -2^2 is (-2)^2
+        let Expr::Binary(BinaryOp::Pow, lhs, _) = parse("-2^2") else {
+            panic!("expected a power");
+        };
+        assert!(matches!(*lhs, Expr::Neg(_)));
+        // 1&2=3 is (1&2)=3
+        assert!(matches!(
+            parse("1&2=3"),
+            Expr::Binary(BinaryOp::Eq, lhs, _) if matches!(*lhs, Expr::Binary(BinaryOp::Concat, _, _))
+        ));
+    }
+
+    #[test]
+    fn array_literals() {
+        let Expr::Array(rows) = parse("={1,2;3,-4}") else {
+            panic!("expected an array");
+        };
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.len() == 2));
+        assert!(matches!(rows[1][1], Expr::Neg(_)));
+        assert!(parse_formula("={1,2").is_none());
+        assert!(parse_formula("={1,2;}").is_none());
+    }
+
+    #[test]
+    fn references_and_names() {
+        assert!(matches!(parse("A:A"), Expr::Ref(r) if r.last.0 == 1_048_575));
+        assert!(matches!(parse("1:3"), Expr::Ref(r) if r.last == (2, 16_383)));
+        assert!(
+            matches!(parse("'My Sheet'!$B$2"), Expr::Ref(r) if r.sheet.as_deref() == Some("My Sheet"))
+        );
+        assert!(matches!(parse("Rate"), Expr::Name(n) if n == "Rate"));
+        assert!(matches!(parse("_xlfn.IFERROR(1,2)"), Expr::Call(n, _) if n == "IFERROR"));
+        assert!(
+            matches!(parse("IF(A1,1,)"), Expr::Call(_, args) if matches!(args[2], Expr::Missing))
+        );
+        assert!(parse_formula("Table1[Column]").is_none());
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        for (open, close) in [("(", ")"), ("{", "}"), ("-", ""), ("ABS(", ")")] {
+            let formula = format!(
+                "{}1{}",
+                open.repeat(MAX_DEPTH - 1),
+                close.repeat(MAX_DEPTH - 1)
+            );
+            assert!(parse_formula(&formula).is_some(), "{open}");
+            // Deeper nesting is rejected instead of overflowing the stack
+            let formula = format!("{}1{}", open.repeat(100_000), close.repeat(100_000));
+            assert!(parse_formula(&formula).is_none(), "{open}");
+        }
+    }
+}
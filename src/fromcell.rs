@@ -7,10 +7,18 @@ pub trait FromCell {
     fn from_cell(cell: Option<&Data>, strict: bool) -> PyResult<Self>
     where
         Self: Sized;
+    /// The value error cells are read as when errors are read as missing values, None if the
+    /// type has no missing value
+    fn missing() -> Option<Self>
+    where
+        Self: Sized;
 }
 
 macro_rules! impl_from_cell {
     ($type:ty, $convert_method:ident, $default:expr, $type_name:expr) => {
+        impl_from_cell!($type, $convert_method, $default, $type_name, None);
+    };
+    ($type:ty, $convert_method:ident, $default:expr, $type_name:expr, $missing:expr) => {
         impl FromCell for $type {
             fn from_cell(cell: Option<&Data>, strict: bool) -> PyResult<Self> {
                 let result = match cell {
@@ -28,10 +36,13 @@ macro_rules! impl_from_cell {
                     Ok(result.unwrap_or($default))
                 }
             }
+            fn missing() -> Option<Self> {
+                $missing
+            }
         }
     };
 }
-impl_from_cell!(f64, as_f64, f64::NAN, "Float");
+impl_from_cell!(f64, as_f64, f64::NAN, "Float", Some(f64::NAN));
 impl_from_cell!(i64, as_i64, i64::default(), "Int");
 impl_from_cell!(String, as_string, String::default(), "String");
 impl_from_cell!(bool, get_bool, bool::default(), "Bool");
@@ -54,11 +65,14 @@ impl FromCell for CalamineData {
                 Data::DateTime(v) => CalamineData::DateTime(v.as_datetime().unwrap()),
                 Data::DateTimeIso(v) => CalamineData::Str(v.clone()),
                 Data::DurationIso(v) => CalamineData::Str(v.clone()),
-                Data::Error(_) => CalamineData::Str("error".into()),
+                Data::Error(e) => CalamineData::Error(e.into()),
                 Data::Empty => CalamineData::Empty,
             },
             None => CalamineData::Empty,
         };
         Ok(value)
     }
+    fn missing() -> Option<Self> {
+        Some(CalamineData::Empty)
+    }
 }
@@ -1,4 +1,6 @@
 pub mod conversations;
+pub mod dataframe;
+pub mod evaluate;
 pub mod fromcell;
 pub mod read;
 pub mod types;
@@ -13,7 +15,9 @@ use pyo3::prelude::*;
     Parameters
     ----------
     addr : str
-        The cell address string (e.g., "A1").
+        The cell address string. Lowercase (e.g., "a1"), absolute (e.g., "$A$1"),
+        R1C1 (e.g., "R1C1") and sheet-qualified (e.g., "'My Sheet'!A1") addresses are
+        also accepted, the sheet name is ignored.
 
     Returns
     -------
@@ -44,6 +48,50 @@ fn addr_to_idx(addr: String) -> PyResult<(usize, usize)> {
 fn idx_to_addr(row: usize, col: usize) -> PyResult<String> {
     types::CellAddr::Idx((row, col)).as_addr()
 }
+
+/**
+    Convert a range address string (e.g., "A1:C10") to 0-based indices of its first and last
+    cells.
+
+    Parameters
+    ----------
+    addr : str
+        The range address string. Single cells (e.g., "B2"), whole columns (e.g., "A:C"),
+        whole rows (e.g., "1:3"), absolute (e.g., "$A$1:$C$10"), R1C1 (e.g., "R1C1:R10C3") and
+        sheet-qualified (e.g., "'My Sheet'!B2:D9") addresses are also accepted, the sheet name
+        is ignored.
+
+    Returns
+    -------
+    Tuple[Tuple[int, int], Tuple[int, int]]
+        The (row, col) indices of the top-left and bottom-right cells.
+*/
+#[pyfunction]
+fn range_to_idx(addr: String) -> PyResult<((usize, usize), (usize, usize))> {
+    types::RangeAddr::Name(addr).as_idx()
+}
+
+/**
+    Convert 0-based indices of the first and last cells to a range address string
+    (e.g., "A1:C10").
+
+    Parameters
+    ----------
+    first : Tuple[int, int]
+        The 0-based (row, col) index of the top-left cell.
+    last : Tuple[int, int]
+        The 0-based (row, col) index of the bottom-right cell.
+
+    Returns
+    -------
+    str
+        The range address string, or a cell address string if `first` equals `last`.
+*/
+#[pyfunction]
+fn idx_to_range(first: (usize, usize), last: (usize, usize)) -> PyResult<String> {
+    let (first, last) = types::RangeAddr::Idx((first, last)).as_idx()?;
+    Ok(types::idx_to_range(first, last))
+}
 /// Returns the current version of the library.
 #[pyfunction]
 fn version() -> PyResult<String> {
@@ -56,14 +104,21 @@ fn fastxlsx(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<types::DType>()?;
     m.add_class::<types::DShape>()?;
     m.add_class::<types::RangeInfo>()?;
+    m.add_class::<types::Format>()?;
+    m.add_class::<types::ExcelError>()?;
     m.add_class::<read::ReadOnlyWorkbook>()?;
     m.add_class::<read::ReadOnlyWorksheet>()?;
+    m.add_class::<read::RowIterator>()?;
     m.add_class::<write::WriteOnlyWorkbook>()?;
     m.add_class::<write::WriteOnlyWorksheet>()?;
     m.add_function(wrap_pyfunction!(read::read_many, m)?)?;
+    m.add_function(wrap_pyfunction!(read::read_many_pandas, m)?)?;
     m.add_function(wrap_pyfunction!(write::write_many, m)?)?;
+    m.add_function(wrap_pyfunction!(write::write_many_to_bytes, m)?)?;
     m.add_function(wrap_pyfunction!(version, m)?)?;
     m.add_function(wrap_pyfunction!(addr_to_idx, m)?)?;
     m.add_function(wrap_pyfunction!(idx_to_addr, m)?)?;
+    m.add_function(wrap_pyfunction!(range_to_idx, m)?)?;
+    m.add_function(wrap_pyfunction!(idx_to_range, m)?)?;
     Ok(())
 }
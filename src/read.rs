@@ -1,63 +1,158 @@
+use crate::dataframe::{FrameColumn, FrameData};
+use crate::evaluate::Evaluator;
 use crate::fromcell::FromCell;
 use crate::types::{Array1Container, Array2Container, ValueContainer, WrappedValue};
-use crate::types::{CalamineData, CellAddr, DShape, DType, IdxOrName, ListOrDict, RangeInfo};
-use crate::utils::adjust_idx;
-use calamine::{open_workbook, Data, Range, Reader, Xlsx};
-use chrono::{NaiveDate, NaiveDateTime};
-use indexmap::IndexMap;
+use crate::types::{
+    CalamineData, CellAddr, CellRange, ColumnDTypes, DShape, DType, IdxOrName, ListOrDict,
+    RangeDTypes, RangeInfo, RangeOrAddr,
+};
+use crate::utils::{adjust_idx, extract_bytes};
+use calamine::{
+    Cell, Data, DataRef, DataType, Ods, Range, Reader, Sheets, Xls, Xlsb, Xlsx, XlsxError,
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use indexmap::{IndexMap, IndexSet};
 use ndarray::{Array1, Array2};
-use pyo3::exceptions::PyFileExistsError;
+use pyo3::exceptions::{PyFileExistsError, PyValueError};
 use pyo3::prelude::*;
 use rayon::prelude::*;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Cursor, Read, Seek};
 use std::path::PathBuf;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex, PoisonError};
+
+type WorksheetsValues = IndexMap<IdxOrName, ListOrDict<String, WrappedValue>>;
+/// Parsed parts of sheets by (sheet name, part)
+type SheetParts = HashMap<(String, SheetPart), Arc<Range<Data>>>;
+
+/// The formulas of a sheet as strings starting with "="
+fn formulas_to_data(formulas: Range<String>) -> Range<Data> {
+    let start = formulas.start().unwrap_or_default();
+    let cells = formulas
+        .used_cells()
+        .map(|(i, j, f)| {
+            let pos = (start.0 + i as u32, start.1 + j as u32);
+            Cell::new(pos, Data::String(format!("={f}")))
+        })
+        .collect();
+    Range::from_sparse(cells)
+}
+
+/// Get a cell of a range by its absolute position
+fn cell_at(range: &Range<Data>, pos: (usize, usize)) -> Option<&Data> {
+    let row = u32::try_from(pos.0).ok()?;
+    let col = u32::try_from(pos.1).ok()?;
+    range.get_value((row, col))
+}
 
 /// Read-only worksheet class
 #[pyclass]
 pub struct ReadOnlyWorksheet {
-    pub sheet: Range<Data>,
+    /// Shared with the cache of the workbook
+    pub sheet: Arc<Range<Data>>,
+    /// Number of rows counted from the first row of the sheet, not of the used range
     #[pyo3(get)]
     pub n_rows: usize,
+    /// Number of columns counted from the first column of the sheet, not of the used range
     #[pyo3(get)]
     pub n_cols: usize,
     #[pyo3(get)]
     pub title: String,
+    /// The defined names referring to this sheet, by lowercase name
+    pub names: IndexMap<String, CellRange>,
+    /// The formulas as strings starting with "=", None if they are not loaded
+    pub formulas: Option<Arc<Range<Data>>>,
+    /// Whether to read error cells as NaN for `DType.Float` and None for `DType.Any`
+    #[pyo3(get, set)]
+    pub errors_as_missing: bool,
 }
 impl ReadOnlyWorksheet {
-    pub fn new(sheet: Range<Data>, title: String) -> Self {
-        let (n_rows, n_cols) = sheet.get_size();
+    pub fn new(sheet: Arc<Range<Data>>, title: String, names: IndexMap<String, CellRange>) -> Self {
+        let (n_rows, n_cols) = match sheet.end() {
+            Some((row, col)) => (row as usize + 1, col as usize + 1),
+            None => (0, 0),
+        };
         Self {
             sheet,
             n_rows,
             n_cols,
             title,
+            names,
+            formulas: None,
+            errors_as_missing: false,
         }
     }
+    fn formulas_rs(&self) -> PyResult<&Range<Data>> {
+        self.formulas.as_deref().ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Formulas of worksheet \"{}\" are not loaded, get the worksheet with `formulas=True`",
+                self.title
+            ))
+        })
+    }
+    /// Replace a defined name by its position and shape
+    fn resolve_name(&self, name: &str, range_info: &RangeInfo) -> PyResult<RangeInfo> {
+        let range = self.names.get(&name.to_lowercase()).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Defined name \"{name}\" does not refer to a range of worksheet \"{}\"",
+                self.title
+            ))
+        })?;
+        range_info.with_bounds(range.first, range.last)
+    }
+    /// Get a cell by its absolute position, the used range of a sheet may not start from A1
+    fn cell(&self, pos: (usize, usize)) -> Option<&Data> {
+        cell_at(&self.sheet, pos)
+    }
     pub fn get_value_rs<T: FromCell>(&self, range_info: &RangeInfo) -> PyResult<ValueContainer<T>> {
-        let data_shape = range_info.data_shape;
+        if let Some(name) = &range_info.name {
+            return self.get_value_rs(&self.resolve_name(name, range_info)?);
+        }
         let pos = (
             adjust_idx(range_info.pos.0, self.n_rows),
             adjust_idx(range_info.pos.1, self.n_cols),
         );
-        let strict = range_info.strict;
+        // The shape is resolved against the values even when formulas are read, so both
+        // reads of a range line up
+        let source = if range_info.formulas {
+            self.formulas_rs()?
+        } else {
+            &self.sheet
+        };
+        self.get_shaped_value_rs(source, pos, range_info.data_shape, range_info.strict)
+    }
+    fn read_cell<T: FromCell>(&self, cell: Option<&Data>, strict: bool) -> PyResult<T> {
+        match (cell, T::missing()) {
+            (Some(Data::Error(_)), Some(missing)) if self.errors_as_missing => Ok(missing),
+            _ => T::from_cell(cell, strict),
+        }
+    }
+    fn is_empty_cell(&self, pos: (usize, usize)) -> bool {
+        matches!(self.cell(pos), None | Some(Data::Empty))
+    }
+    fn get_shaped_value_rs<T: FromCell>(
+        &self,
+        source: &Range<Data>,
+        pos: (usize, usize),
+        data_shape: DShape,
+        strict: bool,
+    ) -> PyResult<ValueContainer<T>> {
         match data_shape {
-            DShape::Scalar {} => Ok(ValueContainer::Scalar(T::from_cell(
-                self.sheet.get(pos),
-                strict,
-            )?)),
+            DShape::Scalar {} => Ok(ValueContainer::Scalar(
+                self.read_cell(cell_at(source, pos), strict)?,
+            )),
             DShape::Row { n_cols } => {
                 let arr_vec = (0..n_cols)
-                    .into_iter()
-                    .map(|j| T::from_cell(self.sheet.get((pos.0, pos.1 + j)), strict))
+                    .map(|j| self.read_cell(cell_at(source, (pos.0, pos.1 + j)), strict))
                     .collect::<PyResult<Vec<_>>>()?;
                 let arr = { unsafe { Array1::from_shape_vec_unchecked(n_cols, arr_vec) } };
                 Ok(ValueContainer::Array1(Array1Container { value: arr }))
             }
             DShape::Column { n_rows } => {
                 let arr_vec = (0..n_rows)
-                    .into_iter()
-                    .map(|i| T::from_cell(self.sheet.get((pos.0 + i, pos.1)), strict))
+                    .map(|i| self.read_cell(cell_at(source, (pos.0 + i, pos.1)), strict))
                     .collect::<PyResult<Vec<_>>>()?;
                 let arr = { unsafe { Array1::from_shape_vec_unchecked(n_rows, arr_vec) } };
                 Ok(ValueContainer::Array1(Array1Container { value: arr }))
@@ -67,8 +162,8 @@ impl ReadOnlyWorksheet {
 
                 for i in 0..n_rows {
                     for j in 0..n_cols {
-                        let cell = self.sheet.get((pos.0 + i, pos.1 + j));
-                        let value = T::from_cell(cell, strict)?;
+                        let cell = cell_at(source, (pos.0 + i, pos.1 + j));
+                        let value = self.read_cell(cell, strict)?;
                         arr_vec.push(value);
                     }
                 }
@@ -76,27 +171,282 @@ impl ReadOnlyWorksheet {
                     { unsafe { Array2::from_shape_vec_unchecked((n_rows, n_cols), arr_vec) } };
                 Ok(ValueContainer::Array2(Array2Container { value: arr }))
             }
+            DShape::MatrixToEnd {} => {
+                let (n_rows, n_cols) = self.resolve_size(pos, data_shape);
+                self.get_shaped_value_rs(source, pos, DShape::Matrix { n_rows, n_cols }, strict)
+            }
+            DShape::RowToEmpty {} => {
+                let (_, n_cols) = self.resolve_size(pos, data_shape);
+                self.get_shaped_value_rs(source, pos, DShape::Row { n_cols }, strict)
+            }
+            DShape::ColumnToEmpty {} => {
+                let (n_rows, _) = self.resolve_size(pos, data_shape);
+                self.get_shaped_value_rs(source, pos, DShape::Column { n_rows }, strict)
+            }
         }
     }
-}
-
-#[pymethods]
-impl ReadOnlyWorksheet {
-    /**
-        Read a single value from the worksheet based on the specified range.
-
-        Parameters
-        ----------
-        range_info : RangeInfo
-            The range information describing the position, shape, and data type of the value to read.
-
-        Returns
-        -------
-        Any
-            The value read from the specified range. Could be scalar or 1d-array or 2d-array.
-    */
-    fn read_value(&self, range_info: &RangeInfo) -> PyResult<WrappedValue> {
-        match range_info.dtype {
+    /// The (n_rows, n_cols) of a shape starting from `pos`, open-ended shapes are resolved
+    /// against the values of the sheet
+    fn resolve_size(&self, pos: (usize, usize), data_shape: DShape) -> (usize, usize) {
+        match data_shape {
+            DShape::Scalar {} => (1, 1),
+            DShape::Row { n_cols } => (1, n_cols),
+            DShape::Column { n_rows } => (n_rows, 1),
+            DShape::Matrix { n_rows, n_cols } => (n_rows, n_cols),
+            DShape::MatrixToEnd {} => (
+                self.n_rows.saturating_sub(pos.0),
+                self.n_cols.saturating_sub(pos.1),
+            ),
+            DShape::RowToEmpty {} => {
+                let n_cols = (pos.1..self.n_cols)
+                    .take_while(|&j| !self.is_empty_cell((pos.0, j)))
+                    .count();
+                (1, n_cols)
+            }
+            DShape::ColumnToEmpty {} => {
+                let n_rows = (pos.0..self.n_rows)
+                    .take_while(|&i| !self.is_empty_cell((i, pos.1)))
+                    .count();
+                (n_rows, 1)
+            }
+        }
+    }
+    /// The absolute position and the (n_rows, n_cols) of a range, with defined names, negative
+    /// positions and open-ended shapes resolved
+    fn resolve_range(&self, range_info: &RangeInfo) -> PyResult<((usize, usize), (usize, usize))> {
+        if let Some(name) = &range_info.name {
+            return self.resolve_range(&self.resolve_name(name, range_info)?);
+        }
+        let pos = (
+            adjust_idx(range_info.pos.0, self.n_rows),
+            adjust_idx(range_info.pos.1, self.n_cols),
+        );
+        Ok((pos, self.resolve_size(pos, range_info.data_shape)))
+    }
+    /// Read each column of a range with its own data type, by the index of the column in the
+    /// range
+    fn read_columns_rs(&self, range_info: &RangeInfo, dtypes: &[DType]) -> PyResult<WrappedValue> {
+        let (pos, (n_rows, n_cols)) = self.resolve_range(range_info)?;
+        if dtypes.len() != n_cols {
+            return Err(PyValueError::new_err(format!(
+                "Expected {n_cols} dtypes, got {}",
+                dtypes.len()
+            )));
+        }
+        dtypes
+            .iter()
+            .enumerate()
+            .map(|(j, &dtype)| {
+                let column = RangeInfo::new(
+                    (pos.0 as i32, (pos.1 + j) as i32),
+                    DShape::Column { n_rows },
+                    dtype,
+                    range_info.strict,
+                );
+                Ok((j, self.read_value_rs(&column)?))
+            })
+            .collect::<PyResult<IndexMap<_, _>>>()
+            .map(WrappedValue::Columns)
+    }
+    /// Whether a cell is read as a missing value of a DataFrame
+    fn is_missing_cell(&self, cell: Option<&Data>) -> bool {
+        match cell {
+            None | Some(Data::Empty) => true,
+            Some(Data::Error(_)) => self.errors_as_missing,
+            _ => false,
+        }
+    }
+    /// Infer the data type of a column from its values. Numbers are read as `DType.Int` if they
+    /// are all integers without missing values, text and mixed values as `DType.Any`.
+    fn infer_dtype(&self, pos: (usize, usize), n_rows: usize) -> DType {
+        let mut column_dtype = None;
+        let (mut has_missing, mut all_int) = (false, true);
+        for i in 0..n_rows {
+            let cell = self.cell((pos.0 + i, pos.1));
+            if self.is_missing_cell(cell) {
+                has_missing = true;
+                continue;
+            }
+            let dtype = match cell {
+                Some(Data::Int(_)) => DType::Float,
+                Some(Data::Float(v)) => {
+                    all_int &= v.fract() == 0.0 && v.abs() < (1u64 << 53) as f64;
+                    DType::Float
+                }
+                Some(Data::Bool(_)) => DType::Bool,
+                Some(Data::DateTime(_)) => DType::DateTime,
+                _ => return DType::Any,
+            };
+            match column_dtype {
+                None => column_dtype = Some(dtype),
+                Some(column_dtype) if column_dtype != dtype => return DType::Any,
+                _ => (),
+            }
+        }
+        match column_dtype {
+            Some(DType::Float) if all_int && !has_missing => DType::Int,
+            Some(DType::Bool) if has_missing => DType::Any,
+            Some(dtype) => dtype,
+            None => DType::Any,
+        }
+    }
+    /// Read a column of a DataFrame, missing values are read as NaN for `DType.Float`, None for
+    /// `DType.Any` and NaT for `DType.Date` and `DType.DateTime`, even in strict mode.
+    /// `DType.Int` columns with missing values are upcast to float64 and `DType.Bool` and
+    /// `DType.Str` columns to object, as pandas does, so missing values are NaN or None.
+    fn read_frame_column(
+        &self,
+        pos: (usize, usize),
+        n_rows: usize,
+        dtype: DType,
+        strict: bool,
+    ) -> PyResult<FrameColumn> {
+        let cells = (0..n_rows).map(|i| self.cell((pos.0 + i, pos.1)));
+        if let DType::Date | DType::DateTime = dtype {
+            let values = cells
+                .map(|cell| match cell {
+                    _ if self.is_missing_cell(cell) => Ok(None),
+                    Some(data) => match data.as_datetime() {
+                        Some(t) if dtype == DType::Date => Ok(Some(
+                            t.date()
+                                .and_time(NaiveTime::MIN)
+                                .and_utc()
+                                .timestamp_micros(),
+                        )),
+                        Some(t) => Ok(Some(t.and_utc().timestamp_micros())),
+                        None if strict => Err(PyValueError::new_err(format!(
+                            "Cell could not be parsed as {dtype:?}: {data:?}"
+                        ))),
+                        None => Ok(None),
+                    },
+                    None => Ok(None),
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            return Ok(FrameColumn::DateTimes(values));
+        }
+        fn read<'a, T: FromCell + Clone>(
+            ws: &ReadOnlyWorksheet,
+            cells: impl Iterator<Item = Option<&'a Data>>,
+            strict: bool,
+        ) -> PyResult<ValueContainer<T>> {
+            let values = cells
+                .map(|cell| match T::missing() {
+                    Some(missing) if ws.is_missing_cell(cell) => Ok(missing),
+                    _ => ws.read_cell(cell, strict),
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(ValueContainer::Array1(Array1Container::new(
+                Array1::from_vec(values),
+            )))
+        }
+        /// Read the values as `T`, converted to `U` which has a value for missing cells
+        fn read_upcast<'a, T: FromCell, U: Clone>(
+            ws: &ReadOnlyWorksheet,
+            cells: impl Iterator<Item = Option<&'a Data>>,
+            strict: bool,
+            upcast: impl Fn(T) -> U,
+            missing: U,
+        ) -> PyResult<ValueContainer<U>> {
+            let values = cells
+                .map(|cell| match ws.is_missing_cell(cell) {
+                    true => Ok(missing.clone()),
+                    false => ws.read_cell(cell, strict).map(&upcast),
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(ValueContainer::Array1(Array1Container::new(
+                Array1::from_vec(values),
+            )))
+        }
+        let has_missing = cells.clone().any(|cell| self.is_missing_cell(cell));
+        let value = match dtype {
+            DType::Int if has_missing => WrappedValue::Float(read_upcast(
+                self,
+                cells,
+                strict,
+                |v: i64| v as f64,
+                f64::NAN,
+            )?),
+            DType::Str if has_missing => WrappedValue::Any(read_upcast(
+                self,
+                cells,
+                strict,
+                CalamineData::Str,
+                CalamineData::Empty,
+            )?),
+            DType::Bool if has_missing => WrappedValue::Any(read_upcast(
+                self,
+                cells,
+                strict,
+                CalamineData::Bool,
+                CalamineData::Empty,
+            )?),
+            DType::Int => WrappedValue::Int(read(self, cells, strict)?),
+            DType::Float => WrappedValue::Float(read(self, cells, strict)?),
+            DType::Str => WrappedValue::Str(read(self, cells, strict)?),
+            DType::Bool => WrappedValue::Bool(read(self, cells, strict)?),
+            DType::Date | DType::DateTime | DType::Any => {
+                WrappedValue::Any(read(self, cells, strict)?)
+            }
+        };
+        Ok(FrameColumn::Values(value))
+    }
+    /// Read a range as the columns of a DataFrame, the used range if `range` is None. The data
+    /// types of columns missing in `dtypes` are inferred.
+    pub fn read_frame_rs(
+        &self,
+        range: Option<&RangeInfo>,
+        header: bool,
+        dtypes: Option<&ColumnDTypes>,
+        strict: bool,
+    ) -> PyResult<FrameData> {
+        let (pos, (n_rows, n_cols)) = match range.cloned().or_else(|| self.used_range()) {
+            Some(range_info) => self.resolve_range(&range_info)?,
+            None => ((0, 0), (0, 0)),
+        };
+        let (first_row, n_rows) = if header {
+            (pos.0 + 1, n_rows.saturating_sub(1))
+        } else {
+            (pos.0, n_rows)
+        };
+        let headers = header.then(|| {
+            (0..n_cols)
+                .map(|j| match self.cell((pos.0, pos.1 + j)) {
+                    None | Some(Data::Empty) => format!("Unnamed: {j}"),
+                    Some(Data::String(s)) => s.clone(),
+                    Some(data) => data.to_string(),
+                })
+                .collect::<Vec<_>>()
+        });
+        let infer = |j| self.infer_dtype((first_row, pos.1 + j), n_rows);
+        let dtypes = match dtypes {
+            Some(dtypes) => {
+                // Columns without a header row are labeled by their index
+                let labels = headers
+                    .clone()
+                    .unwrap_or_else(|| (0..n_cols).map(|j| j.to_string()).collect());
+                dtypes.resolve_with(&labels, infer)?
+            }
+            None => (0..n_cols).map(infer).collect(),
+        };
+        let columns = dtypes
+            .into_iter()
+            .enumerate()
+            .map(|(j, dtype)| self.read_frame_column((first_row, pos.1 + j), n_rows, dtype, strict))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(FrameData { headers, columns })
+    }
+    pub fn read_value_rs(&self, range_info: &RangeInfo) -> PyResult<WrappedValue> {
+        if range_info.formulas {
+            // Cells without a formula are read as None
+            return self
+                .get_value_rs::<CalamineData>(range_info)
+                .map(WrappedValue::Any);
+        }
+        let dtype = match &range_info.dtype {
+            RangeDTypes::One(dtype) => *dtype,
+            RangeDTypes::Columns(dtypes) => return self.read_columns_rs(range_info, dtypes),
+        };
+        match dtype {
             DType::Int => self.get_value_rs::<i64>(range_info).map(WrappedValue::Int),
             DType::Float => self
                 .get_value_rs::<f64>(range_info)
@@ -118,6 +468,43 @@ impl ReadOnlyWorksheet {
                 .map(WrappedValue::Any),
         }
     }
+    pub fn read_values_rs(
+        &self,
+        range_infos: ListOrDict<String, RangeInfo>,
+    ) -> PyResult<ListOrDict<String, WrappedValue>> {
+        match range_infos {
+            ListOrDict::List(range_info_list) => range_info_list
+                .iter()
+                .map(|range_info| self.read_value_rs(range_info))
+                .collect::<PyResult<Vec<_>>>()
+                .map(ListOrDict::List),
+            ListOrDict::Dict(range_info_dict) => range_info_dict
+                .iter()
+                .map(|(k, range_info)| self.read_value_rs(range_info).map(|v| (k.clone(), v)))
+                .collect::<PyResult<IndexMap<_, _>>>()
+                .map(ListOrDict::Dict),
+        }
+    }
+}
+
+#[pymethods]
+impl ReadOnlyWorksheet {
+    /**
+        Read a single value from the worksheet based on the specified range.
+
+        Parameters
+        ----------
+        range_info : RangeInfo
+            The range information describing the position, shape, and data type of the value to read.
+
+        Returns
+        -------
+        Any
+            The value read from the specified range. Could be scalar or 1d-array or 2d-array.
+    */
+    fn read_value(&self, py: Python<'_>, range_info: &RangeInfo) -> PyResult<WrappedValue> {
+        py.allow_threads(|| self.read_value_rs(range_info))
+    }
     /**
         Read multiple values from the worksheet based on a list of ranges.
 
@@ -133,20 +520,10 @@ impl ReadOnlyWorksheet {
     */
     fn read_values(
         &self,
+        py: Python<'_>,
         range_infos: ListOrDict<String, RangeInfo>,
     ) -> PyResult<ListOrDict<String, WrappedValue>> {
-        match range_infos {
-            ListOrDict::List(range_info_list) => range_info_list
-                .iter()
-                .map(|range_info| self.read_value(range_info))
-                .collect::<PyResult<Vec<_>>>()
-                .map(ListOrDict::List),
-            ListOrDict::Dict(range_info_dict) => range_info_dict
-                .iter()
-                .map(|(k, range_info)| self.read_value(range_info).map(|v| (k.clone(), v)))
-                .collect::<PyResult<IndexMap<_, _>>>()
-                .map(ListOrDict::Dict),
-        }
+        py.allow_threads(|| self.read_values_rs(range_infos))
     }
     /**
         Read a value from a specific cell in the worksheet.
@@ -176,177 +553,1518 @@ impl ReadOnlyWorksheet {
     ) -> PyResult<WrappedValue> {
         let pos = cell_addr.as_idx()?;
         let pos = (pos.0 as i32, pos.1 as i32);
-        let range_info = RangeInfo {
-            pos,
-            data_shape: DShape::Scalar {},
-            dtype,
-            strict,
-        };
-        self.read_value(&range_info)
-    }
-    fn __repr__(&self) -> String {
-        format!("<ReadOnlyWorksheet \"{}\">", self.title)
-    }
-}
-
-/// Read-only workbook class
-#[pyclass]
-pub struct ReadOnlyWorkbook {
-    #[pyo3(get)]
-    pub path: PathBuf,
-    pub xlsx: Xlsx<BufReader<File>>,
-    #[pyo3(get)]
-    pub n_sheets: usize,
-    #[pyo3(get)]
-    pub sheetnames: Vec<String>,
-}
-#[pymethods]
-impl ReadOnlyWorkbook {
-    /**
-        Generate a `ReadOnlyWorkbook` object.
-
-        Parameters
-        ----------
-        path : str
-            The path to the xlsx file.
-    */
-    #[new]
-    pub fn new(path: PathBuf) -> PyResult<Self> {
-        let xlsx: Xlsx<BufReader<File>> = open_workbook(&path).map_err(|e| {
-            PyErr::new::<PyFileExistsError, _>(format!("{path:?} could not be read as xlsx: {e}"))
-        })?;
-        let sheetnames = xlsx
-            .sheet_names()
-            .iter()
-            .map(|s| s.to_string())
-            .collect::<Vec<_>>();
-        let n_sheets = sheetnames.len();
-        Ok(Self {
-            path,
-            xlsx,
-            n_sheets,
-            sheetnames,
-        })
+        let range_info = RangeInfo::new(pos, DShape::Scalar {}, dtype, strict);
+        self.read_value_rs(&range_info)
     }
     /**
-        Get the sheet by sheet name.
+        Read the formula of a specific cell in the worksheet, the worksheet should be got with
+        `formulas=True`.
 
         Parameters
         ----------
-        name : str
-            The name of the sheet.
+        cell_addr : Union[Tuple[int, int], str]
+            The cell address, either as a tuple of (row, col) or a string (e.g., "A1").
 
         Returns
         -------
-        ReadOnlyWorksheet
+        Optional[str]
+            The formula starting with "=", e.g. "=SUM(A1:A10)", None if the cell has no formula.
     */
-    fn get_by_name(&mut self, sheet_name: String) -> PyResult<ReadOnlyWorksheet> {
-        match self.xlsx.worksheet_range(&sheet_name) {
-            Ok(sheet) => return Ok(ReadOnlyWorksheet::new(sheet, sheet_name)),
-            Err(e) => return Err(PyErr::new::<PyFileExistsError, _>(format!("{e}"))),
+    fn formula_value(&self, cell_addr: CellAddr) -> PyResult<Option<String>> {
+        let pos = cell_addr.as_idx()?;
+        match cell_at(self.formulas_rs()?, pos) {
+            Some(Data::String(formula)) => Ok(Some(formula.clone())),
+            _ => Ok(None),
         }
     }
+    /// Whether the formulas of the sheet are loaded
+    #[getter]
+    fn has_formulas(&self) -> bool {
+        self.formulas.is_some()
+    }
+    /// The 0-based position of the first cell of the used range as (row, col), None if the
+    /// sheet is empty
+    #[getter]
+    fn start(&self) -> Option<(u32, u32)> {
+        self.sheet.start()
+    }
+    /// The 0-based position of the last cell of the used range as (row, col), None if the
+    /// sheet is empty
+    #[getter]
+    fn end(&self) -> Option<(u32, u32)> {
+        self.sheet.end()
+    }
+    /// The used range of the sheet as a `RangeInfo` of `DShape.Matrix`, None if the sheet is
+    /// empty
+    #[getter]
+    fn used_range(&self) -> Option<RangeInfo> {
+        let (start, end) = (self.sheet.start()?, self.sheet.end()?);
+        let data_shape = DShape::Matrix {
+            n_rows: (end.0 - start.0) as usize + 1,
+            n_cols: (end.1 - start.1) as usize + 1,
+        };
+        Some(RangeInfo::new(
+            (start.0 as i32, start.1 as i32),
+            data_shape,
+            DType::Any,
+            true,
+        ))
+    }
     /**
-        Get the sheet by index.
+        Read the whole worksheet from A1 to the end of the used range.
 
         Parameters
         ----------
-        idx : int
-            The 0-based index of the sheet.
+        dtype : DType, default DType.Any
+            The expected data type of the values.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
 
         Returns
         -------
-        ReadOnlyWorksheet
+        np.ndarray | List[List[Any]]
+            The values as a 2d-array.
     */
-    fn get_by_idx(&mut self, idx: usize) -> PyResult<ReadOnlyWorksheet> {
-        if let Some(result) = self.xlsx.worksheet_range_at(idx) {
-            match result {
-                Ok(sheet) => {
-                    return Ok(ReadOnlyWorksheet::new(
-                        sheet,
-                        self.sheetnames.get(idx).unwrap().to_owned(),
-                    ))
-                }
-                Err(e) => return Err(PyErr::new::<PyFileExistsError, _>(format!("{e}"))),
-            }
-        } else {
-            return Err(PyErr::new::<PyFileExistsError, _>(format!(
-                "No sheet at index {idx}"
-            )));
-        }
+    #[pyo3(signature = (*, dtype = DType::Any, strict = true))]
+    fn to_array(&self, py: Python<'_>, dtype: DType, strict: bool) -> PyResult<WrappedValue> {
+        let range_info = RangeInfo::new((0, 0), DShape::MatrixToEnd {}, dtype, strict);
+        py.allow_threads(|| self.read_value_rs(&range_info))
     }
     /**
-        Get the sheet by index or name.
+        Iterate over the rows of the worksheet in chunks. To stream a sheet from the file
+        without parsing it as a whole, use `ReadOnlyWorkbook.iter_rows`.
 
         Parameters
         ----------
-        idx_or_name : Union[int, str]
-            The 0-based index or name of the sheet.
+        chunk_size : int, default 1000
+            The number of rows of each chunk, the last chunk may have fewer rows.
+        dtype : DType, default DType.Any
+            The expected data type of the values.
+        columns : Optional[List[int]], default None
+            The 0-based indices of the columns to read, in the order they are returned. If None,
+            read the columns from A to the end of the used range.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
 
         Returns
         -------
-        ReadOnlyWorksheet
+        Iterator[np.ndarray | List[List[Any]]]
+            The chunks of rows as 2d-arrays, from the first row of the sheet to the end of the
+            used range.
     */
-    fn get(&mut self, idx_or_name: IdxOrName) -> PyResult<ReadOnlyWorksheet> {
-        match idx_or_name {
-            IdxOrName::Idx(idx) => self.get_by_idx(adjust_idx(idx, self.n_sheets)),
-            IdxOrName::Name(name) => self.get_by_name(name),
-        }
+    #[pyo3(signature = (*, chunk_size = 1000, dtype = DType::Any, columns = None, strict = true))]
+    fn iter_rows(
+        &self,
+        chunk_size: usize,
+        dtype: DType,
+        columns: Option<Vec<usize>>,
+        strict: bool,
+    ) -> PyResult<RowIterator> {
+        RowIterator::loaded(self, chunk_size, dtype, columns, strict)
     }
     /**
-        Read values from multiple worksheets based on specified ranges.
+        Read a range as a pandas DataFrame, whose columns are built as typed arrays.
 
         Parameters
         ----------
-        worksheets_to_read : Dict[Union[str, int], Union[List[RangeInfo], Dict[str, RangeInfo]]]
-            A dictionary mapping worksheet identifiers (either by name or index) to a list or dict of 
-            `RangeInfo` objects. Each `RangeInfo` object describes a range of cells to read
-            from the corresponding worksheet.
+        range : Optional[Union[str, RangeInfo]], default None
+            The range to read, either as an address (e.g., "A1:D10") or a `RangeInfo`, whose
+            `dtype` is ignored. If None, read the used range of the worksheet.
+        header : bool, default True
+            Whether the first row of the range holds the headers of the columns. If False, the
+            columns are labeled by their 0-based index in the range.
+        dtypes : Optional[DType | List[DType] | Dict[str, DType]], default None
+            The data types of the columns, either one for all columns, a list in the order of
+            columns, or a dict by header (by index as str without header). The data types of
+            other columns are inferred from their values: numbers are read as `DType.Int` if
+            they are all integers without missing values, else as `DType.Float`, and text or
+            mixed values as `DType.Any`.
+        index_col : Optional[Union[int, str]], default None
+            The column to use as the index of the DataFrame, by its index in the range or its
+            header.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
 
         Returns
         -------
-        Dict[Union[str, int], Union[List[Any], Dict[str, Any]]]
-            A dictionary mapping worksheet identifiers (either by name or index) to a list or dict of
-            values read from the specified ranges, where each range is replaced by the corresponding 
-            data.
-
-        Examples
-        --------
-        >>> from fastxlsx import WorkbookReanonly, RangeInfo, DShape, DType
-        >>> wb = WorkbookReanonly("example.xlsx")
-        >>> worksheets_to_read = {
-                "Sheet1": [
-                    RangeInfo((0, 0), DShape.Matrix(2, 2), dtype=DType.Int),
-                    RangeInfo((2, 0), DShape.Column(3), dtype=DType.Str),
-                ],
-                1: [RangeInfo((0, 0), DShape.Row(5), dtype=DType.Float)],
-            }
-        >>> res = wb.read_worksheets(worksheets_to_read)
-        >>> res
-        {
-            "Sheet1": [np.array([[1, 2], [3, 4]]), ["A", "B", "C"]],
-            1: [np.array([1.1, 2.2, 3.3, 4.4, 5.5])],
-        }
+        pandas.DataFrame
+            `DType.Int`, `DType.Float` and `DType.Bool` columns are int64, float64 and bool,
+            `DType.Date` and `DType.DateTime` columns are datetime64, other columns are object.
+            Empty cells are read as NaN, NaT or None, even in strict mode: `DType.Int` columns
+            with empty cells are float64, and `DType.Bool` and `DType.Str` columns are object.
     */
-    fn read_worksheets(
-        &mut self,
-        worksheets_to_read: IndexMap<IdxOrName, ListOrDict<String, RangeInfo>>,
-    ) -> PyResult<IndexMap<IdxOrName, ListOrDict<String, WrappedValue>>> {
-        worksheets_to_read
-            .into_iter()
-            .map(|(idx_or_name, range_infos)| {
-                let ws = self.get(idx_or_name.clone())?;
-                Ok((idx_or_name, ws.read_values(range_infos)?))
-            })
-            .collect::<PyResult<IndexMap<IdxOrName, ListOrDict<String, WrappedValue>>>>()
+    #[pyo3(signature = (range = None, *, header = true, dtypes = None, index_col = None, strict = true))]
+    fn to_pandas(
+        &self,
+        py: Python<'_>,
+        range: Option<RangeOrAddr>,
+        header: bool,
+        dtypes: Option<ColumnDTypes>,
+        index_col: Option<IdxOrName>,
+        strict: bool,
+    ) -> PyResult<PyObject> {
+        let range = range.map(|range| range.range_info()).transpose()?;
+        let frame = py.allow_threads(|| {
+            self.read_frame_rs(range.as_ref(), header, dtypes.as_ref(), strict)
+        })?;
+        frame.to_pandas(py, index_col.as_ref())
     }
-    /// The sheets.
-    #[getter]
-    fn worksheets(&mut self) -> PyResult<Vec<ReadOnlyWorksheet>> {
-        (0..self.n_sheets)
-            .into_iter()
-            .map(|idx| self.get_by_idx(idx))
-            .collect::<PyResult<Vec<_>>>()
+    fn __repr__(&self) -> String {
+        format!("<ReadOnlyWorksheet \"{}\">", self.title)
+    }
+}
+
+/// Rows of a sheet read by a `RowIterator`, with positions relative to the first row and to
+/// the columns read
+struct RowChunk {
+    n_rows: usize,
+    n_cols: usize,
+    cells: Vec<Cell<Data>>,
+}
+impl RowChunk {
+    fn read_value_rs(
+        self,
+        dtype: DType,
+        strict: bool,
+        errors_as_missing: bool,
+    ) -> PyResult<WrappedValue> {
+        let ws = ReadOnlyWorksheet {
+            errors_as_missing,
+            ..ReadOnlyWorksheet::new(
+                Arc::new(Range::from_sparse(self.cells)),
+                String::new(),
+                IndexMap::new(),
+            )
+        };
+        let data_shape = DShape::Matrix {
+            n_rows: self.n_rows,
+            n_cols: self.n_cols,
+        };
+        ws.read_value_rs(&RangeInfo::new((0, 0), data_shape, dtype, strict))
+    }
+}
+
+/// Where the rows of a `RowIterator` are read from
+enum RowSource {
+    /// A parsed sheet, read from `next_row`
+    Loaded {
+        sheet: Arc<Range<Data>>,
+        columns: Vec<usize>,
+        next_row: usize,
+        n_rows: usize,
+    },
+    /// Chunks sent by a thread streaming the cells of the sheet
+    Streamed(Mutex<Receiver<Result<RowChunk, String>>>),
+}
+
+/// Send chunks of `chunk_size` rows from cells in row order, until the last row with a value.
+/// `n_cols` is the number of columns if they are known, else the width of the first chunk. When
+/// all columns are read, a cell past the `n_cols` columns is an error rather than dropped.
+fn send_row_chunks<'a, E: std::fmt::Display>(
+    mut next_cell: impl FnMut() -> Result<Option<Cell<DataRef<'a>>>, E>,
+    columns: Option<&[usize]>,
+    mut n_cols: Option<usize>,
+    chunk_size: usize,
+    sender: &SyncSender<Result<RowChunk, String>>,
+) -> Result<(), String> {
+    // The indices of each column read among the columns read, a column may be read twice
+    let col_idx: Option<HashMap<usize, Vec<usize>>> = columns.map(|columns| {
+        let mut col_idx = HashMap::<usize, Vec<usize>>::new();
+        for (j, &c) in columns.iter().enumerate() {
+            col_idx.entry(c).or_default().push(j);
+        }
+        col_idx
+    });
+    // Send the chunks of empty rows before `chunk`, stopping once the iterator is dropped
+    let send = |n_empty: usize, chunk: RowChunk| {
+        let n_cols = chunk.n_cols;
+        let empty = (0..n_empty).map(|_| RowChunk {
+            n_rows: chunk_size,
+            n_cols,
+            cells: Vec::new(),
+        });
+        empty
+            .chain(std::iter::once(chunk))
+            .all(|chunk| sender.send(Ok(chunk)).is_ok())
+    };
+    let width = |cells: &[Cell<Data>]| {
+        let width = cells.iter().map(|c| c.get_position().1 + 1);
+        width.max().unwrap_or_default() as usize
+    };
+    let mut first_row = 0;
+    let mut cells = Vec::new();
+    let mut last_row = None;
+    // Empty chunks not sent yet, as their width is only known from the first chunk with a value
+    let mut n_empty = 0;
+    while let Some(cell) = next_cell().map_err(|e| e.to_string())? {
+        if matches!(cell.get_value(), DataRef::Empty) {
+            continue;
+        }
+        let (row, col) = (
+            cell.get_position().0 as usize,
+            cell.get_position().1 as usize,
+        );
+        while row >= first_row + chunk_size {
+            if n_cols.is_none() && cells.is_empty() {
+                n_empty += 1;
+            } else {
+                let chunk = RowChunk {
+                    n_rows: chunk_size,
+                    n_cols: *n_cols.get_or_insert_with(|| width(&cells)),
+                    cells: std::mem::take(&mut cells),
+                };
+                if !send(std::mem::take(&mut n_empty), chunk) {
+                    // The iterator is dropped
+                    return Ok(());
+                }
+            }
+            first_row += chunk_size;
+        }
+        last_row = Some(row);
+        let slots = match &col_idx {
+            Some(col_idx) => col_idx.get(&col).map_or(&[][..], Vec::as_slice),
+            None => {
+                if let Some(n_cols) = n_cols.filter(|&n_cols| col >= n_cols) {
+                    return Err(format!(
+                        "Row {} has a value in column {}, past the {n_cols} columns of the first \
+                         rows, pass `columns` to read it",
+                        row + 1,
+                        col + 1
+                    ));
+                }
+                std::slice::from_ref(&col)
+            }
+        };
+        for &j in slots {
+            let pos = ((row - first_row) as u32, j as u32);
+            cells.push(Cell::new(pos, Data::from(cell.get_value().clone())));
+        }
+    }
+    if let Some(last_row) = last_row {
+        let chunk = RowChunk {
+            n_rows: last_row + 1 - first_row,
+            n_cols: n_cols.unwrap_or_else(|| width(&cells)),
+            cells,
+        };
+        send(n_empty, chunk);
+    }
+    Ok(())
+}
+
+/// Iterator over chunks of rows of a worksheet
+#[pyclass]
+pub struct RowIterator {
+    source: RowSource,
+    chunk_size: usize,
+    dtype: DType,
+    strict: bool,
+    errors_as_missing: bool,
+}
+impl RowIterator {
+    /// Iterate over the rows of a parsed sheet, all columns are read if `columns` is None
+    fn loaded(
+        ws: &ReadOnlyWorksheet,
+        chunk_size: usize,
+        dtype: DType,
+        columns: Option<Vec<usize>>,
+        strict: bool,
+    ) -> PyResult<Self> {
+        Self::check_chunk_size(chunk_size)?;
+        Ok(Self {
+            source: RowSource::Loaded {
+                sheet: ws.sheet.clone(),
+                columns: columns.unwrap_or_else(|| (0..ws.n_cols).collect()),
+                next_row: 0,
+                n_rows: ws.n_rows,
+            },
+            chunk_size,
+            dtype,
+            strict,
+            errors_as_missing: ws.errors_as_missing,
+        })
+    }
+    /// Iterate over the rows of a sheet parsed by another thread while they are read, all
+    /// columns of the dimension of the sheet are read if `columns` is None
+    fn streamed(
+        reader: WorkbookReader,
+        sheet_name: String,
+        chunk_size: usize,
+        dtype: DType,
+        columns: Option<Vec<usize>>,
+        strict: bool,
+        errors_as_missing: bool,
+    ) -> PyResult<Self> {
+        Self::check_chunk_size(chunk_size)?;
+        // Only the chunk being read and the next one are held in memory
+        let (sender, receiver) = sync_channel(1);
+        std::thread::spawn(move || {
+            let n_cols = columns.as_ref().map(|columns| columns.len());
+            let columns = columns.as_deref();
+            // The width of a sheet without a dimension record is only known from its cells
+            let width = |dims: calamine::Dimensions| {
+                (dims != calamine::Dimensions::default()).then(|| dims.end.1 as usize + 1)
+            };
+            let result = match reader {
+                Sheets::Xlsx(mut xlsx) => match xlsx.worksheet_cells_reader(&sheet_name) {
+                    Ok(mut cells) => {
+                        let n_cols = n_cols.or(width(cells.dimensions()));
+                        send_row_chunks(|| cells.next_cell(), columns, n_cols, chunk_size, &sender)
+                    }
+                    Err(e) => Err(e.to_string()),
+                },
+                Sheets::Xlsb(mut xlsb) => match xlsb.worksheet_cells_reader(&sheet_name) {
+                    Ok(mut cells) => {
+                        let n_cols = n_cols.or(width(cells.dimensions()));
+                        send_row_chunks(|| cells.next_cell(), columns, n_cols, chunk_size, &sender)
+                    }
+                    Err(e) => Err(e.to_string()),
+                },
+                _ => Err(format!("Sheet \"{sheet_name}\" could not be streamed")),
+            };
+            if let Err(e) = result {
+                let _ = sender.send(Err(e));
+            }
+        });
+        Ok(Self {
+            source: RowSource::Streamed(Mutex::new(receiver)),
+            chunk_size,
+            dtype,
+            strict,
+            errors_as_missing,
+        })
+    }
+    fn check_chunk_size(chunk_size: usize) -> PyResult<()> {
+        if chunk_size == 0 {
+            return Err(PyValueError::new_err("`chunk_size` must be at least 1"));
+        }
+        Ok(())
+    }
+    fn next_chunk(&mut self) -> PyResult<Option<RowChunk>> {
+        match &mut self.source {
+            RowSource::Loaded {
+                sheet,
+                columns,
+                next_row,
+                n_rows,
+            } => {
+                if *next_row >= *n_rows {
+                    return Ok(None);
+                }
+                let chunk_rows = self.chunk_size.min(*n_rows - *next_row);
+                let mut cells = Vec::new();
+                for i in 0..chunk_rows {
+                    for (j, &col) in columns.iter().enumerate() {
+                        let value = cell_at(sheet, (*next_row + i, col));
+                        if let Some(value) = value.filter(|v| !matches!(v, Data::Empty)) {
+                            cells.push(Cell::new((i as u32, j as u32), value.clone()));
+                        }
+                    }
+                }
+                *next_row += chunk_rows;
+                Ok(Some(RowChunk {
+                    n_rows: chunk_rows,
+                    n_cols: columns.len(),
+                    cells,
+                }))
+            }
+            // The thread has stopped when the channel is closed
+            RowSource::Streamed(receiver) => receiver
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .recv()
+                .ok()
+                .transpose()
+                .map_err(PyErr::new::<PyFileExistsError, _>),
+        }
+    }
+}
+#[pymethods]
+impl RowIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<WrappedValue>> {
+        py.allow_threads(|| {
+            let (dtype, strict, errors_as_missing) =
+                (self.dtype, self.strict, self.errors_as_missing);
+            self.next_chunk()?
+                .map(|chunk| chunk.read_value_rs(dtype, strict, errors_as_missing))
+                .transpose()
+        })
+    }
+}
+
+/// Anything a workbook could be read from, e.g. a file or an in-memory buffer.
+pub trait WorkbookSource: Read + Seek + Send + Sync {}
+impl<T: Read + Seek + Send + Sync> WorkbookSource for T {}
+
+/// The reader of a workbook in any of the supported formats
+pub type WorkbookReader = Sheets<Box<dyn WorkbookSource>>;
+
+const CFB_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+const ZIP_MAGIC: [u8; 4] = *b"PK\x03\x04";
+
+/// The file formats a workbook could be read as
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FileFormat {
+    Xlsx,
+    Xlsb,
+    Xls,
+    Ods,
+}
+impl FileFormat {
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "xlsx" | "xlsm" | "xlam" => Some(FileFormat::Xlsx),
+            "xlsb" => Some(FileFormat::Xlsb),
+            "xls" | "xla" => Some(FileFormat::Xls),
+            "ods" => Some(FileFormat::Ods),
+            _ => None,
+        }
+    }
+    /// The formats to try in order. Xls files are compound files and the others are zip
+    /// archives, which are told apart by their magic bytes. Zip archives are first tried as
+    /// the format of the extension.
+    fn candidates(magic: &[u8], extension: Option<Self>) -> Vec<Self> {
+        if magic.starts_with(&CFB_MAGIC) {
+            return vec![FileFormat::Xls];
+        }
+        if !magic.starts_with(&ZIP_MAGIC) {
+            // Not a workbook, only try it for the error message
+            return vec![extension.unwrap_or(FileFormat::Xlsx)];
+        }
+        let mut formats = vec![FileFormat::Xlsx, FileFormat::Xlsb, FileFormat::Ods];
+        if let Some(extension) = extension.filter(|f| formats.contains(f)) {
+            formats.retain(|f| *f != extension);
+            formats.insert(0, extension);
+        }
+        formats
+    }
+    fn name(&self) -> &'static str {
+        match self {
+            FileFormat::Xlsx => "xlsx",
+            FileFormat::Xlsb => "xlsb",
+            FileFormat::Xls => "xls",
+            FileFormat::Ods => "ods",
+        }
+    }
+    fn open(&self, reader: Box<dyn WorkbookSource>) -> Result<WorkbookReader, String> {
+        match self {
+            FileFormat::Xlsx => Xlsx::new(reader)
+                .map(Sheets::Xlsx)
+                .map_err(|e| e.to_string()),
+            FileFormat::Xlsb => Xlsb::new(reader)
+                .map(Sheets::Xlsb)
+                .map_err(|e| e.to_string()),
+            FileFormat::Xls => Xls::new(reader).map(Sheets::Xls).map_err(|e| e.to_string()),
+            FileFormat::Ods => Ods::new(reader).map(Sheets::Ods).map_err(|e| e.to_string()),
+        }
+    }
+    /// Whether opening a reader parses all the sheets, so another reader is not worth opening
+    /// to parse sheets in parallel
+    fn parses_on_open(self) -> bool {
+        matches!(self, FileFormat::Xls | FileFormat::Ods)
+    }
+}
+
+/// The parts of a sheet which are parsed separately
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SheetPart {
+    Values,
+    /// The values with formulas evaluated
+    Evaluated,
+    Formulas,
+}
+impl SheetPart {
+    fn load(
+        &self,
+        reader: &mut WorkbookReader,
+        sheetnames: &[String],
+        defined_names: &[(String, String)],
+        sheet_name: &str,
+    ) -> PyResult<Range<Data>> {
+        let to_err = |e: calamine::Error| PyErr::new::<PyFileExistsError, _>(format!("{e}"));
+        match self {
+            SheetPart::Values => reader.worksheet_range(sheet_name).map_err(to_err),
+            SheetPart::Evaluated => {
+                Evaluator::new(reader, sheetnames, defined_names).evaluate_sheet(sheet_name)
+            }
+            SheetPart::Formulas => reader
+                .worksheet_formula(sheet_name)
+                .map(formulas_to_data)
+                .map_err(to_err),
+        }
+    }
+}
+
+/// The rows and columns of a sheet which are read, for parsing only the top of large sheets
+struct ReadExtent {
+    last_row: u32,
+    /// Inclusive (first, last) columns
+    cols: Vec<(u32, u32)>,
+}
+impl ReadExtent {
+    /// None if any range is counted from the end of the sheet or its end is only known when it
+    /// is read, except rows until the first empty cell.
+    fn of(range_infos: &[&RangeInfo], names: &IndexMap<String, CellRange>) -> Option<Self> {
+        let mut last_row = 0;
+        let mut cols = Vec::with_capacity(range_infos.len());
+        for range_info in range_infos {
+            let (first, last) = match (&range_info.name, range_info.data_shape) {
+                (Some(name), _) => {
+                    let range = names.get(&name.to_lowercase())?;
+                    (range.first, range.last)
+                }
+                (None, DShape::RowToEmpty {}) if range_info.pos.0 >= 0 && range_info.pos.1 >= 0 => {
+                    let first = (range_info.pos.0 as usize, range_info.pos.1 as usize);
+                    (first, (first.0, u32::MAX as usize))
+                }
+                (None, _) => range_info.bounds().ok()?,
+            };
+            last_row = last_row.max(u32::try_from(last.0).ok()?);
+            let last_col = u32::try_from(last.1).unwrap_or(u32::MAX);
+            cols.push((u32::try_from(first.1).ok()?, last_col));
+        }
+        Some(Self { last_row, cols })
+    }
+    fn contains(&self, (row, col): (u32, u32)) -> bool {
+        row <= self.last_row
+            && self
+                .cols
+                .iter()
+                .any(|&(first, last)| first <= col && col <= last)
+    }
+    /// Parse the cells of the extent, stopping at the first row below it. None if the sheet
+    /// could not be streamed, i.e. it is not in a xlsx or xlsb file, or is a chart sheet.
+    fn load(&self, reader: &mut WorkbookReader, sheet_name: &str) -> PyResult<Option<Range<Data>>> {
+        let to_err = |e: String| PyErr::new::<PyFileExistsError, _>(e);
+        match reader {
+            Sheets::Xlsx(xlsx) => match xlsx.worksheet_cells_reader(sheet_name) {
+                Ok(mut cells) => self.collect(|| cells.next_cell()).map(Some).map_err(to_err),
+                Err(XlsxError::NotAWorksheet(_)) => Ok(None),
+                Err(e) => Err(to_err(e.to_string())),
+            },
+            Sheets::Xlsb(xlsb) => {
+                let mut cells = xlsb
+                    .worksheet_cells_reader(sheet_name)
+                    .map_err(|e| to_err(e.to_string()))?;
+                self.collect(|| cells.next_cell()).map(Some).map_err(to_err)
+            }
+            _ => Ok(None),
+        }
+    }
+    /// Collect the non-empty cells of the extent from cells in row order
+    fn collect<'a, E: std::fmt::Display>(
+        &self,
+        mut next_cell: impl FnMut() -> Result<Option<Cell<DataRef<'a>>>, E>,
+    ) -> Result<Range<Data>, String> {
+        let mut cells = Vec::new();
+        while let Some(cell) = next_cell().map_err(|e| e.to_string())? {
+            let pos = cell.get_position();
+            if pos.0 > self.last_row {
+                break;
+            }
+            if self.contains(pos) && !matches!(cell.get_value(), DataRef::Empty) {
+                cells.push(Cell::new(pos, Data::from(cell.get_value().clone())));
+            }
+        }
+        Ok(Range::from_sparse(cells))
+    }
+}
+
+/// Where a workbook is read from, opened again by each thread parsing its sheets
+pub enum SourceData {
+    Path(PathBuf),
+    Bytes(Arc<[u8]>),
+}
+impl SourceData {
+    fn open(&self) -> std::io::Result<Box<dyn WorkbookSource>> {
+        match self {
+            SourceData::Path(path) => Ok(Box::new(BufReader::new(File::open(path)?))),
+            SourceData::Bytes(data) => Ok(Box::new(Cursor::new(data.clone()))),
+        }
+    }
+}
+
+/// Read-only workbook class
+#[pyclass]
+pub struct ReadOnlyWorkbook {
+    /// None if the workbook is read from memory
+    #[pyo3(get)]
+    pub path: Option<PathBuf>,
+    pub source: SourceData,
+    format: FileFormat,
+    pub reader: WorkbookReader,
+    #[pyo3(get)]
+    pub n_sheets: usize,
+    #[pyo3(get)]
+    pub sheetnames: Vec<String>,
+    /// The defined names as (name, reference)
+    pub defined_names: Vec<(String, String)>,
+    /// Whether to evaluate formulas instead of reading their cached values
+    #[pyo3(get, set)]
+    pub evaluate: bool,
+    /// Whether to read error cells as NaN for `DType.Float` and None for `DType.Any`
+    #[pyo3(get, set)]
+    pub errors_as_missing: bool,
+    /// Whether to keep parsed sheets to reuse when they are got again
+    #[pyo3(get, set)]
+    pub cache: bool,
+    /// The parsed parts of sheets by sheet name, kept if `cache` is set
+    pub sheet_cache: SheetParts,
+}
+impl ReadOnlyWorkbook {
+    pub fn from_path(path: PathBuf) -> PyResult<Self> {
+        Self::from_source(SourceData::Path(path))
+    }
+    pub fn from_bytes_rs(data: Vec<u8>) -> PyResult<Self> {
+        Self::from_source(SourceData::Bytes(data.into()))
+    }
+    /// Read a workbook in the format detected from its content, the source is opened again
+    /// for each format tried.
+    pub fn from_source(source: SourceData) -> PyResult<Self> {
+        let path = match &source {
+            SourceData::Path(path) => Some(path.clone()),
+            SourceData::Bytes(_) => None,
+        };
+        let name = match &path {
+            Some(path) => format!("{path:?}"),
+            None => "Data".to_string(),
+        };
+        let open = || {
+            source.open().map_err(|e| {
+                PyErr::new::<PyFileExistsError, _>(format!("{name} could not be opened: {e}"))
+            })
+        };
+        let mut magic = Vec::with_capacity(CFB_MAGIC.len());
+        open()?
+            .take(CFB_MAGIC.len() as u64)
+            .read_to_end(&mut magic)
+            .map_err(|e| {
+                PyErr::new::<PyFileExistsError, _>(format!("{name} could not be read: {e}"))
+            })?;
+        let extension = path
+            .as_ref()
+            .and_then(|path| path.extension()?.to_str())
+            .and_then(FileFormat::from_extension);
+        let mut first_error = None;
+        for format in FileFormat::candidates(&magic, extension) {
+            match format.open(open()?) {
+                Ok(reader) => return Ok(Self::from_reader(reader, format, source)),
+                Err(e) => {
+                    first_error.get_or_insert_with(|| {
+                        format!("{name} could not be read as {}: {e}", format.name())
+                    });
+                }
+            }
+        }
+        Err(PyErr::new::<PyFileExistsError, _>(
+            first_error.unwrap_or_default(),
+        ))
+    }
+    fn from_reader(reader: WorkbookReader, format: FileFormat, source: SourceData) -> Self {
+        let sheetnames = reader.sheet_names();
+        let n_sheets = sheetnames.len();
+        let defined_names = reader.defined_names().to_vec();
+        let path = match &source {
+            SourceData::Path(path) => Some(path.clone()),
+            SourceData::Bytes(_) => None,
+        };
+        Self {
+            path,
+            source,
+            format,
+            reader,
+            n_sheets,
+            sheetnames,
+            defined_names,
+            evaluate: false,
+            errors_as_missing: false,
+            cache: false,
+            sheet_cache: HashMap::new(),
+        }
+    }
+    /// Open another reader of the workbook, for a thread parsing sheets
+    fn open_reader(&self) -> Result<WorkbookReader, String> {
+        let reader = self.source.open().map_err(|e| e.to_string())?;
+        self.format.open(reader)
+    }
+    /// The defined names referring to a range of the sheet, names of constants, formulas or
+    /// multiple ranges are skipped.
+    fn names_of_sheet(&self, title: &str) -> IndexMap<String, CellRange> {
+        let title = title.to_lowercase();
+        self.defined_names
+            .iter()
+            .filter_map(|(name, reference)| {
+                let range = CellRange::parse(reference.trim_start_matches('=')).ok()?;
+                (range.sheet.as_ref()?.to_lowercase() == title)
+                    .then(|| (name.to_lowercase(), range))
+            })
+            .collect()
+    }
+    /// The part holding the values of sheets, with formulas evaluated if `evaluate` is set
+    fn values_part(&self) -> SheetPart {
+        if self.evaluate {
+            SheetPart::Evaluated
+        } else {
+            SheetPart::Values
+        }
+    }
+    /// Get a part of a sheet from the cache, or parse it and cache it if `cache` is set
+    fn part_rs(&mut self, sheet_name: &str, part: SheetPart) -> PyResult<Arc<Range<Data>>> {
+        let key = (sheet_name.to_string(), part);
+        if let Some(range) = self.sheet_cache.get(&key).filter(|_| self.cache) {
+            return Ok(range.clone());
+        }
+        let range = part.load(
+            &mut self.reader,
+            &self.sheetnames,
+            &self.defined_names,
+            sheet_name,
+        )?;
+        let range = Arc::new(range);
+        if self.cache {
+            self.sheet_cache.insert(key, range.clone());
+        }
+        Ok(range)
+    }
+    /// Get parts of sheets like `part_rs`, parsing the parts which are not cached in parallel
+    /// with a reader for each chunk of parts, one chunk per thread
+    fn parts_par_rs(&mut self, keys: Vec<(String, SheetPart)>) -> PyResult<SheetParts> {
+        let mut parts = HashMap::new();
+        let mut to_load = IndexSet::new();
+        for key in keys {
+            match self.sheet_cache.get(&key).filter(|_| self.cache) {
+                Some(range) => {
+                    parts.insert(key, range.clone());
+                }
+                None => {
+                    to_load.insert(key);
+                }
+            }
+        }
+        // Opening another reader is only worth it when the parts are parsed in parallel, and
+        // when opening it does not parse the whole workbook again
+        let n_threads = rayon::current_num_threads();
+        if to_load.len() <= 1 || n_threads <= 1 || self.format.parses_on_open() {
+            for (sheet_name, part) in to_load {
+                let range = self.part_rs(&sheet_name, part)?;
+                parts.insert((sheet_name, part), range);
+            }
+            return Ok(parts);
+        }
+        let to_load = to_load.into_iter().collect::<Vec<_>>();
+        let chunk_size = to_load.len().div_ceil(n_threads);
+        let loaded = to_load
+            .par_chunks(chunk_size)
+            .map(|chunk| {
+                let mut reader = self.open_reader().map_err(PyFileExistsError::new_err)?;
+                chunk
+                    .iter()
+                    .map(|(sheet_name, part)| {
+                        let range = part.load(
+                            &mut reader,
+                            &self.sheetnames,
+                            &self.defined_names,
+                            sheet_name,
+                        )?;
+                        Ok(((sheet_name.clone(), *part), Arc::new(range)))
+                    })
+                    .collect::<PyResult<Vec<_>>>()
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        for (key, range) in loaded.into_iter().flatten() {
+            if self.cache {
+                self.sheet_cache.insert(key.clone(), range.clone());
+            }
+            parts.insert(key, range);
+        }
+        Ok(parts)
+    }
+    fn worksheet(
+        &self,
+        sheet_name: String,
+        sheet: Arc<Range<Data>>,
+        formulas: Option<Arc<Range<Data>>>,
+    ) -> ReadOnlyWorksheet {
+        let names = self.names_of_sheet(&sheet_name);
+        ReadOnlyWorksheet {
+            errors_as_missing: self.errors_as_missing,
+            formulas,
+            ..ReadOnlyWorksheet::new(sheet, sheet_name, names)
+        }
+    }
+    /// Get sheets by (name, whether to load the formulas), parsing them in parallel
+    pub fn worksheets_par_rs(
+        &mut self,
+        sheets: Vec<(String, bool)>,
+    ) -> PyResult<Vec<ReadOnlyWorksheet>> {
+        let parts = self.parts_par_rs(self.part_keys(&sheets))?;
+        Ok(self.worksheets_from_parts(sheets, &parts))
+    }
+    /// The keys of the parts of (sheet name, whether to load formulas)
+    fn part_keys(&self, sheets: &[(String, bool)]) -> Vec<(String, SheetPart)> {
+        let values_part = self.values_part();
+        sheets
+            .iter()
+            .flat_map(|(sheet_name, formulas)| {
+                let formulas = formulas.then(|| (sheet_name.clone(), SheetPart::Formulas));
+                std::iter::once((sheet_name.clone(), values_part)).chain(formulas)
+            })
+            .collect()
+    }
+    fn worksheets_from_parts(
+        &self,
+        sheets: Vec<(String, bool)>,
+        parts: &SheetParts,
+    ) -> Vec<ReadOnlyWorksheet> {
+        let values_part = self.values_part();
+        sheets
+            .into_iter()
+            .map(|(sheet_name, formulas)| {
+                let part = |part| parts.get(&(sheet_name.clone(), part)).cloned();
+                let sheet = part(values_part).unwrap_or_default();
+                let formulas = if formulas {
+                    part(SheetPart::Formulas)
+                } else {
+                    None
+                };
+                self.worksheet(sheet_name, sheet, formulas)
+            })
+            .collect()
+    }
+    /// Parse only the rows and columns read from sheets which are not cached, when the ranges
+    /// read from them are known beforehand. The parts are not cached as they are incomplete.
+    fn partial_parts_rs(
+        &mut self,
+        worksheets_to_read: &IndexMap<IdxOrName, ListOrDict<String, RangeInfo>>,
+    ) -> PyResult<SheetParts> {
+        let mut parts = SheetParts::new();
+        if self.evaluate || !matches!(self.reader, Sheets::Xlsx(_) | Sheets::Xlsb(_)) {
+            return Ok(parts);
+        }
+        let mut range_infos_by_sheet: IndexMap<String, Vec<&RangeInfo>> = IndexMap::new();
+        for (idx_or_name, range_infos) in worksheets_to_read {
+            let sheet_range_infos = range_infos_by_sheet
+                .entry(self.sheet_name_rs(idx_or_name)?)
+                .or_default();
+            match range_infos {
+                ListOrDict::List(list) => sheet_range_infos.extend(list),
+                ListOrDict::Dict(dict) => sheet_range_infos.extend(dict.values()),
+            }
+        }
+        let mut to_load = Vec::new();
+        for (sheet_name, range_infos) in range_infos_by_sheet {
+            let key = (sheet_name, SheetPart::Values);
+            if (self.cache && self.sheet_cache.contains_key(&key))
+                || range_infos.iter().any(|r| r.formulas)
+            {
+                continue;
+            }
+            if let Some(extent) = ReadExtent::of(&range_infos, &self.names_of_sheet(&key.0)) {
+                to_load.push((key.0, extent));
+            }
+        }
+        // Like `parts_par_rs`, each thread parses its chunk of sheets with its own reader
+        let n_threads = rayon::current_num_threads();
+        if to_load.len() <= 1 || n_threads <= 1 || self.format.parses_on_open() {
+            for (sheet_name, extent) in to_load {
+                if let Some(range) = extent.load(&mut self.reader, &sheet_name)? {
+                    parts.insert((sheet_name, SheetPart::Values), Arc::new(range));
+                }
+            }
+            return Ok(parts);
+        }
+        let chunk_size = to_load.len().div_ceil(n_threads);
+        let loaded = to_load
+            .par_chunks(chunk_size)
+            .map(|chunk| {
+                let mut reader = self.open_reader().map_err(PyFileExistsError::new_err)?;
+                chunk
+                    .iter()
+                    .map(|(sheet_name, extent)| {
+                        let range = extent.load(&mut reader, sheet_name)?;
+                        Ok(range.map(|range| (sheet_name.clone(), range)))
+                    })
+                    .collect::<PyResult<Vec<_>>>()
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        for (sheet_name, range) in loaded.into_iter().flatten().flatten() {
+            parts.insert((sheet_name, SheetPart::Values), Arc::new(range));
+        }
+        Ok(parts)
+    }
+    fn sheet_name_rs(&self, idx_or_name: &IdxOrName) -> PyResult<String> {
+        match idx_or_name {
+            IdxOrName::Idx(idx) => match self.sheetnames.get(adjust_idx(*idx, self.n_sheets)) {
+                Some(name) => Ok(name.clone()),
+                None => Err(PyErr::new::<PyFileExistsError, _>(format!(
+                    "No sheet at index {idx}"
+                ))),
+            },
+            IdxOrName::Name(name) => Ok(name.clone()),
+        }
+    }
+    pub fn evict_rs(&mut self, idx_or_name: IdxOrName) {
+        if let Ok(sheet_name) = self.sheet_name_rs(&idx_or_name) {
+            self.sheet_cache.retain(|(name, _), _| *name != sheet_name);
+        }
+    }
+    pub fn get_by_name_rs(
+        &mut self,
+        sheet_name: String,
+        formulas: bool,
+    ) -> PyResult<ReadOnlyWorksheet> {
+        let sheet = self.part_rs(&sheet_name, self.values_part())?;
+        let formulas = if formulas {
+            Some(self.part_rs(&sheet_name, SheetPart::Formulas)?)
+        } else {
+            None
+        };
+        Ok(self.worksheet(sheet_name, sheet, formulas))
+    }
+    pub fn get_by_idx_rs(&mut self, idx: usize, formulas: bool) -> PyResult<ReadOnlyWorksheet> {
+        match self.sheetnames.get(idx) {
+            Some(title) => self.get_by_name_rs(title.to_owned(), formulas),
+            None => Err(PyErr::new::<PyFileExistsError, _>(format!(
+                "No sheet at index {idx}"
+            ))),
+        }
+    }
+    pub fn get_rs(
+        &mut self,
+        idx_or_name: IdxOrName,
+        formulas: bool,
+    ) -> PyResult<ReadOnlyWorksheet> {
+        let sheet_name = self.sheet_name_rs(&idx_or_name)?;
+        self.get_by_name_rs(sheet_name, formulas)
+    }
+    /// Iterate over chunks of rows of a sheet, streamed from the file unless the sheet is cached
+    /// or could only be parsed as a whole
+    pub fn iter_rows_rs(
+        &mut self,
+        idx_or_name: &IdxOrName,
+        chunk_size: usize,
+        dtype: DType,
+        columns: Option<Vec<usize>>,
+        strict: bool,
+    ) -> PyResult<RowIterator> {
+        let sheet_name = self.sheet_name_rs(idx_or_name)?;
+        let is_chart =
+            self.reader.sheets_metadata().iter().any(|sheet| {
+                sheet.name == sheet_name && sheet.typ == calamine::SheetType::ChartSheet
+            });
+        if is_chart {
+            return Err(PyErr::new::<PyFileExistsError, _>(format!(
+                "Sheet \"{sheet_name}\" is a chart sheet, which has no rows"
+            )));
+        }
+        let cached = self.cache
+            && self
+                .sheet_cache
+                .contains_key(&(sheet_name.clone(), self.values_part()));
+        // Unknown sheets are got to raise the same error as `get`
+        if cached
+            || self.evaluate
+            || !self.sheetnames.contains(&sheet_name)
+            || !matches!(self.reader, Sheets::Xlsx(_) | Sheets::Xlsb(_))
+        {
+            let ws = self.get_by_name_rs(sheet_name, false)?;
+            return RowIterator::loaded(&ws, chunk_size, dtype, columns, strict);
+        }
+        let reader = self
+            .open_reader()
+            .map_err(PyErr::new::<PyFileExistsError, _>)?;
+        RowIterator::streamed(
+            reader,
+            sheet_name,
+            chunk_size,
+            dtype,
+            columns,
+            strict,
+            self.errors_as_missing,
+        )
+    }
+    /// The xlsx reader with its tables loaded, None for other formats which have no tables
+    fn tables_rs(&mut self) -> PyResult<Option<&mut Xlsx<Box<dyn WorkbookSource>>>> {
+        match &mut self.reader {
+            Sheets::Xlsx(xlsx) => {
+                xlsx.load_tables()
+                    .map_err(|e| PyErr::new::<PyFileExistsError, _>(format!("{e}")))?;
+                Ok(Some(xlsx))
+            }
+            _ => Ok(None),
+        }
+    }
+    pub fn table_names_rs(&mut self, sheet_name: Option<&str>) -> PyResult<Vec<String>> {
+        let Some(xlsx) = self.tables_rs()? else {
+            return Ok(Vec::new());
+        };
+        let names = match sheet_name {
+            Some(sheet_name) => xlsx.table_names_in_sheet(sheet_name),
+            None => xlsx.table_names(),
+        };
+        Ok(names.into_iter().cloned().collect())
+    }
+    pub fn read_table_rs(
+        &mut self,
+        name: &str,
+        dtypes: &ColumnDTypes,
+        strict: bool,
+    ) -> PyResult<IndexMap<String, WrappedValue>> {
+        let xlsx = self.tables_rs()?.ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Table \"{name}\" not found, tables are only read from xlsx files"
+            ))
+        })?;
+        let table = xlsx
+            .table_by_name(name)
+            .map_err(|e| PyValueError::new_err(format!("{e}")))?;
+        let dtypes = dtypes.resolve(table.columns())?;
+        let Some((first_row, first_col)) = table.data().start() else {
+            return Err(PyValueError::new_err(format!(
+                "Table \"{name}\" has no data"
+            )));
+        };
+        let n_rows = table.data().height();
+        let columns = table.columns().to_vec();
+        // The data of a table keeps its absolute position, read it as a worksheet
+        let ws = ReadOnlyWorksheet {
+            errors_as_missing: self.errors_as_missing,
+            ..ReadOnlyWorksheet::new(
+                Arc::new(table.data().clone()),
+                table.sheet_name().to_string(),
+                IndexMap::new(),
+            )
+        };
+        columns
+            .into_iter()
+            .zip(dtypes)
+            .enumerate()
+            .map(|(j, (header, dtype))| {
+                let range_info = RangeInfo::new(
+                    (first_row as i32, (first_col as usize + j) as i32),
+                    DShape::Column { n_rows },
+                    dtype,
+                    strict,
+                );
+                Ok((header, ws.read_value_rs(&range_info)?))
+            })
+            .collect()
+    }
+    pub fn read_worksheets_rs(
+        &mut self,
+        worksheets_to_read: IndexMap<IdxOrName, ListOrDict<String, RangeInfo>>,
+    ) -> PyResult<WorksheetsValues> {
+        let sheets = worksheets_to_read
+            .iter()
+            .map(|(idx_or_name, range_infos)| {
+                // Only parse the formulas of sheets they are read from
+                let formulas = match range_infos {
+                    ListOrDict::List(list) => list.iter().any(|r| r.formulas),
+                    ListOrDict::Dict(dict) => dict.values().any(|r| r.formulas),
+                };
+                Ok((self.sheet_name_rs(idx_or_name)?, formulas))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let mut parts = self.partial_parts_rs(&worksheets_to_read)?;
+        let keys = self
+            .part_keys(&sheets)
+            .into_iter()
+            .filter(|key| !parts.contains_key(key))
+            .collect();
+        parts.extend(self.parts_par_rs(keys)?);
+        let worksheets = self.worksheets_from_parts(sheets, &parts);
+        let values = worksheets_to_read
+            .into_iter()
+            .zip(worksheets)
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|((idx_or_name, range_infos), ws)| {
+                Ok((idx_or_name, ws.read_values_rs(range_infos)?))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(values.into_iter().collect())
+    }
+    pub fn read_all_sheets_rs(
+        &mut self,
+        dtype: DType,
+        strict: bool,
+    ) -> PyResult<IndexMap<String, WrappedValue>> {
+        let sheets = self
+            .sheetnames
+            .iter()
+            .map(|sheet_name| (sheet_name.clone(), false))
+            .collect();
+        let worksheets = self.worksheets_par_rs(sheets)?;
+        let range_info = RangeInfo::new((0, 0), DShape::MatrixToEnd {}, dtype, strict);
+        let values = worksheets
+            .into_par_iter()
+            .map(|ws| Ok((ws.title.clone(), ws.read_value_rs(&range_info)?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(values.into_iter().collect())
+    }
+}
+#[pymethods]
+impl ReadOnlyWorkbook {
+    /**
+        Generate a `ReadOnlyWorkbook` object.
+
+        Parameters
+        ----------
+        path : str
+            The path to the workbook file. Xlsx, xlsm, xlsb, xls and ods files are supported, the
+            format is detected from the content.
+        evaluate : bool, default False
+            Whether to evaluate formulas instead of reading their cached values, for files
+            written without cached values. Formulas calling unsupported functions, or referring
+            to defined names which are not defined or not supported, keep their cached values.
+        errors_as_missing : bool, default False
+            Whether to read error cells, e.g. `#N/A`, as NaN for `DType.Float` and None for
+            `DType.Any` instead of `ExcelError`s, and without raising for `DType.Float` when
+            `strict` is True.
+        cache : bool, default False
+            Whether to keep parsed sheets to reuse when they are got again, see `evict` and
+            `clear_cache`. Cached sheets stay in memory until they are evicted, so only enable it
+            when sheets are got more than once.
+    */
+    #[new]
+    #[pyo3(signature = (path, *, evaluate = false, errors_as_missing = false, cache = false))]
+    pub fn new(
+        py: Python<'_>,
+        path: PathBuf,
+        evaluate: bool,
+        errors_as_missing: bool,
+        cache: bool,
+    ) -> PyResult<Self> {
+        let workbook = py.allow_threads(|| Self::from_path(path))?;
+        Ok(Self {
+            evaluate,
+            errors_as_missing,
+            cache,
+            ..workbook
+        })
+    }
+    /**
+        Generate a `ReadOnlyWorkbook` object from the content of a workbook file in memory.
+
+        Parameters
+        ----------
+        data : bytes | bytearray | memoryview | BinaryIO
+            The content of the workbook file in any supported format, or a readable binary
+            file-like object, e.g. an `io.BytesIO` or a file opened with "rb". Other objects,
+            e.g. a file opened in text mode, raise `TypeError`.
+        evaluate : bool, default False
+            Whether to evaluate formulas instead of reading their cached values, for files
+            written without cached values. Formulas calling unsupported functions, or referring
+            to defined names which are not defined or not supported, keep their cached values.
+        errors_as_missing : bool, default False
+            Whether to read error cells, e.g. `#N/A`, as NaN for `DType.Float` and None for
+            `DType.Any` instead of `ExcelError`s, and without raising for `DType.Float` when
+            `strict` is True.
+        cache : bool, default False
+            Whether to keep parsed sheets to reuse when they are got again, see `evict` and
+            `clear_cache`. Cached sheets stay in memory until they are evicted, so only enable it
+            when sheets are got more than once.
+
+        Returns
+        -------
+        ReadOnlyWorkbook
+    */
+    #[staticmethod]
+    #[pyo3(signature = (data, *, evaluate = false, errors_as_missing = false, cache = false))]
+    pub fn from_bytes(
+        py: Python<'_>,
+        data: &Bound<'_, PyAny>,
+        evaluate: bool,
+        errors_as_missing: bool,
+        cache: bool,
+    ) -> PyResult<Self> {
+        let data = extract_bytes(data)?;
+        let workbook = py.allow_threads(|| Self::from_bytes_rs(data))?;
+        Ok(Self {
+            evaluate,
+            errors_as_missing,
+            cache,
+            ..workbook
+        })
+    }
+    /**
+        Get the sheet by sheet name.
+
+        Parameters
+        ----------
+        name : str
+            The name of the sheet.
+        formulas : bool, default False
+            Whether to also load the formulas of the sheet, which could then be read with
+            `formula_value` or `RangeInfo`s with `formulas=True`.
+
+        Returns
+        -------
+        ReadOnlyWorksheet
+    */
+    #[pyo3(signature = (sheet_name, *, formulas = false))]
+    fn get_by_name(
+        &mut self,
+        py: Python<'_>,
+        sheet_name: String,
+        formulas: bool,
+    ) -> PyResult<ReadOnlyWorksheet> {
+        py.allow_threads(|| self.get_by_name_rs(sheet_name, formulas))
+    }
+    /**
+        Get the sheet by index.
+
+        Parameters
+        ----------
+        idx : int
+            The 0-based index of the sheet.
+        formulas : bool, default False
+            Whether to also load the formulas of the sheet, which could then be read with
+            `formula_value` or `RangeInfo`s with `formulas=True`.
+
+        Returns
+        -------
+        ReadOnlyWorksheet
+    */
+    #[pyo3(signature = (idx, *, formulas = false))]
+    fn get_by_idx(
+        &mut self,
+        py: Python<'_>,
+        idx: usize,
+        formulas: bool,
+    ) -> PyResult<ReadOnlyWorksheet> {
+        py.allow_threads(|| self.get_by_idx_rs(idx, formulas))
+    }
+    /**
+        Get the sheet by index or name.
+
+        Parameters
+        ----------
+        idx_or_name : Union[int, str]
+            The 0-based index or name of the sheet.
+        formulas : bool, default False
+            Whether to also load the formulas of the sheet, which could then be read with
+            `formula_value` or `RangeInfo`s with `formulas=True`.
+
+        Returns
+        -------
+        ReadOnlyWorksheet
+    */
+    #[pyo3(signature = (idx_or_name, *, formulas = false))]
+    fn get(
+        &mut self,
+        py: Python<'_>,
+        idx_or_name: IdxOrName,
+        formulas: bool,
+    ) -> PyResult<ReadOnlyWorksheet> {
+        py.allow_threads(|| self.get_rs(idx_or_name, formulas))
+    }
+    /**
+        Read values from multiple worksheets based on specified ranges, the worksheets are parsed
+        in parallel.
+
+        Sheets of xlsx and xlsb files which are not cached are only parsed up to the last row
+        read, when the ends of all ranges read from them are known beforehand.
+
+        Parameters
+        ----------
+        worksheets_to_read : Dict[Union[str, int], Union[List[RangeInfo], Dict[str, RangeInfo]]]
+            A dictionary mapping worksheet identifiers (either by name or index) to a list or dict of
+            `RangeInfo` objects. Each `RangeInfo` object describes a range of cells to read
+            from the corresponding worksheet.
+
+        Returns
+        -------
+        Dict[Union[str, int], Union[List[Any], Dict[str, Any]]]
+            A dictionary mapping worksheet identifiers (either by name or index) to a list or dict of
+            values read from the specified ranges, where each range is replaced by the corresponding
+            data.
+
+        Examples
+        --------
+        >>> from fastxlsx import WorkbookReanonly, RangeInfo, DShape, DType
+        >>> wb = WorkbookReanonly("example.xlsx")
+        >>> worksheets_to_read = {
+                "Sheet1": [
+                    RangeInfo((0, 0), DShape.Matrix(2, 2), dtype=DType.Int),
+                    RangeInfo((2, 0), DShape.Column(3), dtype=DType.Str),
+                ],
+                1: [RangeInfo((0, 0), DShape.Row(5), dtype=DType.Float)],
+            }
+        >>> res = wb.read_worksheets(worksheets_to_read)
+        >>> res
+        {
+            "Sheet1": [np.array([[1, 2], [3, 4]]), ["A", "B", "C"]],
+            1: [np.array([1.1, 2.2, 3.3, 4.4, 5.5])],
+        }
+    */
+    fn read_worksheets(
+        &mut self,
+        py: Python<'_>,
+        worksheets_to_read: IndexMap<IdxOrName, ListOrDict<String, RangeInfo>>,
+    ) -> PyResult<WorksheetsValues> {
+        py.allow_threads(|| self.read_worksheets_rs(worksheets_to_read))
+    }
+    /**
+        Read all sheets from A1 to the end of their used ranges, the sheets are parsed in
+        parallel.
+
+        Parameters
+        ----------
+        dtype : DType, default DType.Any
+            The expected data type of the values.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+
+        Returns
+        -------
+        Dict[str, np.ndarray | List[List[Any]]]
+            A dictionary mapping sheet names to their values as 2d-arrays.
+    */
+    #[pyo3(signature = (*, dtype = DType::Any, strict = true))]
+    fn read_all_sheets(
+        &mut self,
+        py: Python<'_>,
+        dtype: DType,
+        strict: bool,
+    ) -> PyResult<IndexMap<String, WrappedValue>> {
+        py.allow_threads(|| self.read_all_sheets_rs(dtype, strict))
+    }
+    /**
+        Iterate over the rows of a sheet in chunks, streaming them from the file so that the
+        whole sheet is never held in memory.
+
+        Parameters
+        ----------
+        sheet : Union[str, int]
+            The name or index of the sheet.
+        chunk_size : int, default 1000
+            The number of rows of each chunk, the last chunk may have fewer rows.
+        dtype : DType, default DType.Any
+            The expected data type of the values.
+        columns : Optional[List[int]], default None
+            The 0-based indices of the columns to read, in the order they are returned. If None,
+            read the columns from A to the end of the sheet. A streamed sheet without its
+            dimensions recorded in the file is as wide as its first chunk, and a later row
+            with a value past it raises an error.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+
+        Returns
+        -------
+        Iterator[np.ndarray | List[List[Any]]]
+            The chunks of rows as 2d-arrays, from the first row of the sheet to its last row
+            with a value.
+
+        Notes
+        -----
+        Sheets of xlsx and xlsb files are streamed by another thread, which parses the next
+        chunk while the current one is processed. Cached sheets, sheets of other formats and
+        sheets of workbooks opened with `evaluate=True` are parsed as a whole first. Streamed
+        sheets are not cached. Chart sheets have no rows and raise an error.
+    */
+    #[pyo3(signature = (sheet, *, chunk_size = 1000, dtype = DType::Any, columns = None, strict = true))]
+    fn iter_rows(
+        &mut self,
+        py: Python<'_>,
+        sheet: IdxOrName,
+        chunk_size: usize,
+        dtype: DType,
+        columns: Option<Vec<usize>>,
+        strict: bool,
+    ) -> PyResult<RowIterator> {
+        py.allow_threads(|| self.iter_rows_rs(&sheet, chunk_size, dtype, columns, strict))
+    }
+    /**
+        Get the names of tables (ListObjects) in the workbook, which only xlsx files have.
+
+        Parameters
+        ----------
+        sheet_name : Optional[str], default None
+            Only get the tables in this sheet if specified.
+
+        Returns
+        -------
+        List[str]
+    */
+    #[pyo3(signature = (sheet_name = None))]
+    fn table_names(&mut self, py: Python<'_>, sheet_name: Option<String>) -> PyResult<Vec<String>> {
+        py.allow_threads(|| self.table_names_rs(sheet_name.as_deref()))
+    }
+    /**
+        Read a table (ListObject) by its name.
+
+        Parameters
+        ----------
+        name : str
+            The name of the table.
+        dtypes : DType | List[DType] | Dict[str, DType], default DType.Any
+            The data types of the columns, either one for all columns, a list in the order of
+            columns, or a dict by header where missing columns are read as `DType.Any`.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+
+        Returns
+        -------
+        Dict[str, Union[np.ndarray, List[Any]]]
+            A dictionary mapping headers to the columns of data, excluding the header and
+            total rows.
+    */
+    #[pyo3(signature = (name, dtypes = ColumnDTypes::One(DType::Any), *, strict = true))]
+    fn read_table(
+        &mut self,
+        py: Python<'_>,
+        name: String,
+        dtypes: ColumnDTypes,
+        strict: bool,
+    ) -> PyResult<IndexMap<String, WrappedValue>> {
+        py.allow_threads(|| self.read_table_rs(&name, &dtypes, strict))
+    }
+    /**
+        The defined names (named ranges) of the workbook.
+
+        Returns
+        -------
+        Dict[str, str]
+            A dictionary mapping names to what they refer to, e.g. {"rate": "Inputs!$B$2"}.
+            Names referring to a range of a sheet could be read with `RangeInfo.from_name`.
+    */
+    #[getter]
+    fn defined_names(&self) -> IndexMap<String, String> {
+        self.defined_names.iter().cloned().collect()
+    }
+    /// The sheets.
+    #[getter]
+    fn worksheets(&mut self, py: Python<'_>) -> PyResult<Vec<ReadOnlyWorksheet>> {
+        let sheets = self
+            .sheetnames
+            .iter()
+            .map(|sheet_name| (sheet_name.clone(), false))
+            .collect();
+        py.allow_threads(|| self.worksheets_par_rs(sheets))
+    }
+    /**
+        Drop a sheet from the cache, so it is parsed again when it is got next time.
+
+        Parameters
+        ----------
+        sheet : int | str
+            The 0-based index or the name of the sheet.
+    */
+    fn evict(&mut self, sheet: IdxOrName) {
+        self.evict_rs(sheet)
+    }
+    /// Drop all sheets from the cache.
+    fn clear_cache(&mut self) {
+        self.sheet_cache.clear()
     }
 }
 /**
@@ -361,6 +2079,13 @@ impl ReadOnlyWorkbook {
     workbooks_to_read : Dict[str, Dict[Union[int, str], Union[List[RangeInfo], Dict[str, RangeInfo]]]]
         A dictionary mapping workbook file paths to nested dictionaries. Each nested dictionary
         maps worksheet identifiers (either by name or index) to a list or dict of `RangeInfo` objects.
+    contents : Optional[Dict[str, Union[bytes, bytearray, memoryview, BinaryIO]]], default None
+        The content of workbooks in memory. Workbooks whose key is found here are read from
+        the content instead of from the file path.
+    evaluate : bool, default False
+        Whether to evaluate formulas instead of reading their cached values.
+    errors_as_missing : bool, default False
+        Whether to read error cells as NaN for `DType.Float` and None for `DType.Any`.
 
     Returns
     -------
@@ -399,14 +2124,374 @@ impl ReadOnlyWorkbook {
         }
 */
 #[pyfunction]
+#[pyo3(signature = (workbooks_to_read, *, contents = None, evaluate = false, errors_as_missing = false))]
 pub fn read_many(
+    py: Python<'_>,
     workbooks_to_read: IndexMap<String, IndexMap<IdxOrName, ListOrDict<String, RangeInfo>>>,
-) -> PyResult<IndexMap<String, IndexMap<IdxOrName, ListOrDict<String, WrappedValue>>>> {
-    workbooks_to_read
-        .into_par_iter()
-        .map(|(path, worksheets)| {
-            let mut workbook = ReadOnlyWorkbook::new(PathBuf::from(path.clone()))?;
-            Ok((path, workbook.read_worksheets(worksheets)?))
+    contents: Option<IndexMap<String, Bound<'_, PyAny>>>,
+    evaluate: bool,
+    errors_as_missing: bool,
+) -> PyResult<IndexMap<String, WorksheetsValues>> {
+    let mut contents = contents
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, data)| Ok((key, extract_bytes(&data)?)))
+        .collect::<PyResult<IndexMap<_, _>>>()?;
+    let workbooks_to_read = workbooks_to_read
+        .into_iter()
+        .map(|(key, worksheets)| {
+            let data = contents.swap_remove(&key);
+            (key, data, worksheets)
+        })
+        .collect::<Vec<_>>();
+    py.allow_threads(|| {
+        workbooks_to_read
+            .into_par_iter()
+            .map(|(key, data, worksheets)| {
+                let mut workbook = match data {
+                    Some(data) => ReadOnlyWorkbook::from_bytes_rs(data)?,
+                    None => ReadOnlyWorkbook::from_path(PathBuf::from(&key))?,
+                };
+                workbook.evaluate = evaluate;
+                workbook.errors_as_missing = errors_as_missing;
+                Ok((key, workbook.read_worksheets_rs(worksheets)?))
+            })
+            .collect()
+    })
+}
+
+/**
+    Read ranges of multiple workbooks as pandas DataFrames, the workbooks are read in parallel.
+
+    Parameters
+    ----------
+    frames_to_read : Dict[str, Dict[Union[int, str], Optional[Union[str, RangeInfo]]]]
+        A dictionary mapping workbook file paths to dictionaries, which map worksheet
+        identifiers (either by name or index) to the range to read, None for the used range.
+    header : bool, default True
+        Whether the first row of each range holds the headers of the columns.
+    dtypes : Optional[DType | List[DType] | Dict[str, DType]], default None
+        The data types of the columns of every range, see `ReadOnlyWorksheet.to_pandas`.
+    index_col : Optional[Union[int, str]], default None
+        The column to use as the index of every DataFrame.
+    strict : bool, default True
+        Whether to enforce strict type checking. If True, raise error when
+        the `dtype` does not match, else use default value.
+    contents : Optional[Dict[str, Union[bytes, bytearray, memoryview, BinaryIO]]], default None
+        The contents of workbooks by their key in `frames_to_read`, read instead of the file.
+    evaluate : bool, default False
+        Whether to evaluate formulas instead of reading their cached values.
+    errors_as_missing : bool, default False
+        Whether to read error cells as missing values.
+
+    Returns
+    -------
+    Dict[str, Dict[Union[int, str], pandas.DataFrame]]
+        The DataFrames, mirroring the structure of `frames_to_read`, with columns typed as in
+        `ReadOnlyWorksheet.to_pandas`.
+*/
+#[pyfunction]
+#[pyo3(signature = (frames_to_read, *, header = true, dtypes = None, index_col = None, strict = true, contents = None, evaluate = false, errors_as_missing = false))]
+#[allow(clippy::too_many_arguments)]
+pub fn read_many_pandas(
+    py: Python<'_>,
+    frames_to_read: IndexMap<String, IndexMap<IdxOrName, Option<RangeOrAddr>>>,
+    header: bool,
+    dtypes: Option<ColumnDTypes>,
+    index_col: Option<IdxOrName>,
+    strict: bool,
+    contents: Option<IndexMap<String, Bound<'_, PyAny>>>,
+    evaluate: bool,
+    errors_as_missing: bool,
+) -> PyResult<IndexMap<String, IndexMap<IdxOrName, PyObject>>> {
+    let mut contents = contents
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, data)| Ok((key, extract_bytes(&data)?)))
+        .collect::<PyResult<IndexMap<_, _>>>()?;
+    let frames_to_read = frames_to_read
+        .into_iter()
+        .map(|(key, ranges)| {
+            let ranges = ranges
+                .into_iter()
+                .map(|(sheet, range)| Ok((sheet, range.map(|r| r.range_info()).transpose()?)))
+                .collect::<PyResult<Vec<_>>>()?;
+            let data = contents.swap_remove(&key);
+            Ok((key, data, ranges))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let frames = py.allow_threads(|| {
+        frames_to_read
+            .into_par_iter()
+            .map(|(key, data, ranges)| {
+                let mut workbook = match data {
+                    Some(data) => ReadOnlyWorkbook::from_bytes_rs(data)?,
+                    None => ReadOnlyWorkbook::from_path(PathBuf::from(&key))?,
+                };
+                workbook.evaluate = evaluate;
+                workbook.errors_as_missing = errors_as_missing;
+                let sheets = ranges
+                    .iter()
+                    .map(|(sheet, _)| Ok((workbook.sheet_name_rs(sheet)?, false)))
+                    .collect::<PyResult<Vec<_>>>()?;
+                let worksheets = workbook.worksheets_par_rs(sheets)?;
+                let frames = ranges
+                    .into_iter()
+                    .zip(worksheets)
+                    .map(|((sheet, range), ws)| {
+                        let frame =
+                            ws.read_frame_rs(range.as_ref(), header, dtypes.as_ref(), strict)?;
+                        Ok((sheet, frame))
+                    })
+                    .collect::<PyResult<Vec<_>>>()?;
+                Ok((key, frames))
+            })
+            .collect::<PyResult<Vec<_>>>()
+    })?;
+    frames
+        .into_iter()
+        .map(|(key, frames)| {
+            let frames = frames
+                .into_iter()
+                .map(|(sheet, frame)| Ok((sheet, frame.to_pandas(py, index_col.as_ref())?)))
+                .collect::<PyResult<IndexMap<_, _>>>()?;
+            Ok((key, frames))
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_xlsxwriter::{Chart, ChartType, Workbook};
+
+    /// The (n_rows, n_cols, cells) of a chunk, with the cells sorted by position
+    type Chunk = (usize, usize, Vec<((u32, u32), i64)>);
+
+    /// Send the chunks of cells given as (row, col, value) in row order, as read without a
+    /// dimension record if `n_cols` is None
+    fn send(
+        cells: &[(u32, u32, i64)],
+        columns: Option<&[usize]>,
+        n_cols: Option<usize>,
+        chunk_size: usize,
+    ) -> Result<Vec<Chunk>, String> {
+        let (sender, receiver) = sync_channel(64);
+        let mut cells = cells
+            .iter()
+            .map(|&(row, col, value)| Cell::new((row, col), DataRef::Int(value)));
+        send_row_chunks(
+            || Ok::<_, String>(cells.next()),
+            columns,
+            n_cols,
+            chunk_size,
+            &sender,
+        )?;
+        drop(sender);
+        receiver
+            .into_iter()
+            .map(|chunk| {
+                let chunk = chunk?;
+                let mut cells = chunk
+                    .cells
+                    .iter()
+                    .map(|c| match c.get_value() {
+                        Data::Int(value) => (c.get_position(), *value),
+                        value => panic!("unexpected value {value:?}"),
+                    })
+                    .collect::<Vec<_>>();
+                cells.sort();
+                Ok((chunk.n_rows, chunk.n_cols, cells))
+            })
+            .collect()
+    }
+
+    /// A workbook with `n_rows` rows of 2 columns in "Sheet1", and a chart sheet "Chart1"
+    fn workbook(n_rows: u32) -> ReadOnlyWorkbook {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        for row in 0..n_rows {
+            worksheet.write_number(row, 0, row).unwrap();
+            worksheet.write_number(row, 1, row * 2).unwrap();
+        }
+        let mut chart = Chart::new(ChartType::Column);
+        chart.add_series().set_values("Sheet1!$A$1:$A$3");
+        workbook
+            .add_chartsheet()
+            .insert_chart(0, 0, &chart)
+            .unwrap();
+        let source = SourceData::Bytes(workbook.save_to_buffer().unwrap().into());
+        let reader = FileFormat::Xlsx.open(source.open().unwrap()).unwrap();
+        ReadOnlyWorkbook::from_reader(reader, FileFormat::Xlsx, source)
+    }
+
+    #[test]
+    fn width_of_first_chunk() {
+        // Without a dimension record, the width is taken from the first chunk
+        let cells = [(0, 0, 1), (0, 1, 2), (1, 0, 3), (2, 1, 4)];
+        assert_eq!(
+            send(&cells, None, None, 2).unwrap(),
+            vec![
+                (2, 2, vec![((0, 0), 1), ((0, 1), 2), ((1, 0), 3)]),
+                (1, 2, vec![((0, 1), 4)]),
+            ]
+        );
+        // A wider row in the first chunk widens it
+        assert_eq!(
+            send(&[(0, 0, 1), (1, 3, 2)], None, None, 2).unwrap(),
+            vec![(2, 4, vec![((0, 0), 1), ((1, 3), 2)])]
+        );
+        // A wider row after the first chunk is an error rather than dropped
+        let err = send(&[(0, 0, 1), (2, 3, 2)], None, None, 2).unwrap_err();
+        assert!(err.contains("column 4"), "{err}");
+        let err = send(&[(0, 0, 1), (0, 2, 2)], None, Some(2), 2).unwrap_err();
+        assert!(err.contains("column 3"), "{err}");
+    }
+
+    #[test]
+    fn columns_filter() {
+        let cells = [(0, 0, 1), (0, 1, 2), (0, 2, 3), (0, 5, 4), (1, 1, 5)];
+        // Columns are read in the given order, columns read twice fill both slots, and
+        // columns not read are skipped even when past the width
+        assert_eq!(
+            send(&cells, Some(&[2, 0, 2]), Some(3), 10).unwrap(),
+            vec![(2, 3, vec![((0, 0), 3), ((0, 1), 1), ((0, 2), 3)])]
+        );
+    }
+
+    #[test]
+    fn shorter_last_chunk() {
+        let cells = (0..5).map(|row| (row, 0, row as i64)).collect::<Vec<_>>();
+        let chunks = send(&cells, None, Some(1), 2).unwrap();
+        let n_rows = chunks.iter().map(|chunk| chunk.0).collect::<Vec<_>>();
+        assert_eq!(n_rows, vec![2, 2, 1]);
+        assert_eq!(chunks[2].2, vec![((0, 0), 4)]);
+        // Leading empty chunks have the width of the first chunk with a value
+        assert_eq!(
+            send(&[(4, 1, 1), (5, 0, 2)], None, None, 2).unwrap(),
+            vec![
+                (2, 2, vec![]),
+                (2, 2, vec![]),
+                (2, 2, vec![((0, 1), 1), ((1, 0), 2)]),
+            ]
+        );
+        assert!(send(&[], None, None, 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn receiver_dropped() {
+        // Sending stops without blocking once the iterator is dropped
+        let (sender, receiver) = sync_channel(1);
+        drop(receiver);
+        let mut row = 0;
+        let next_cell = || {
+            row += 1;
+            Ok::<_, String>(Some(Cell::new((row, 0), DataRef::Int(1))))
+        };
+        assert!(send_row_chunks(next_cell, None, Some(1), 1, &sender).is_ok());
+    }
+
+    #[test]
+    fn streamed_rows() {
+        let mut wb = workbook(25);
+        let sheet = IdxOrName::Name("Sheet1".to_string());
+        let mut iter = wb.iter_rows_rs(&sheet, 10, DType::Any, None, true).unwrap();
+        assert!(matches!(iter.source, RowSource::Streamed(_)));
+        let mut shapes = Vec::new();
+        while let Some(chunk) = iter.next_chunk().unwrap() {
+            shapes.push((chunk.n_rows, chunk.n_cols, chunk.cells.len()));
+        }
+        assert_eq!(shapes, vec![(10, 2, 20), (10, 2, 20), (5, 2, 10)]);
+        // Dropping the iterator before the end stops the thread streaming the rows
+        let mut iter = wb.iter_rows_rs(&sheet, 1, DType::Any, None, true).unwrap();
+        assert!(iter.next_chunk().unwrap().is_some());
+        drop(iter);
+    }
+
+    #[test]
+    fn chart_sheet_rows() {
+        let mut wb = workbook(3);
+        let chart = IdxOrName::Name("Chart1".to_string());
+        assert!(wb.iter_rows_rs(&chart, 10, DType::Any, None, true).is_err());
+    }
+
+    #[test]
+    fn frame_columns_with_empty_cells() {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        for (row, value) in [(0, 1), (2, 3)] {
+            worksheet.write_number(row, 0, value).unwrap();
+            worksheet.write_string(row, 1, value.to_string()).unwrap();
+            worksheet.write_boolean(row, 2, value == 1).unwrap();
+        }
+        for row in 0..3 {
+            worksheet.write_number(row, 3, row).unwrap();
+        }
+        let source = SourceData::Bytes(workbook.save_to_buffer().unwrap().into());
+        let reader = FileFormat::Xlsx.open(source.open().unwrap()).unwrap();
+        let mut wb = ReadOnlyWorkbook::from_reader(reader, FileFormat::Xlsx, source);
+        let ws = wb.get_by_idx_rs(0, false).unwrap();
+        let dtypes = ColumnDTypes::List(vec![DType::Int, DType::Str, DType::Bool, DType::Int]);
+        let frame = ws.read_frame_rs(None, false, Some(&dtypes), true).unwrap();
+        let values = |column: &FrameColumn| match column {
+            FrameColumn::Values(WrappedValue::Float(ValueContainer::Array1(c))) => {
+                format!("{:?}", c.value.to_vec())
+            }
+            FrameColumn::Values(WrappedValue::Int(ValueContainer::Array1(c))) => {
+                format!("{:?}", c.value.to_vec())
+            }
+            FrameColumn::Values(WrappedValue::Any(ValueContainer::Array1(c))) => {
+                let values = c.value.iter().map(|value| match value {
+                    CalamineData::Str(s) => s.clone(),
+                    CalamineData::Bool(b) => b.to_string(),
+                    CalamineData::Empty => "None".to_string(),
+                    _ => panic!("unexpected value"),
+                });
+                format!("{:?}", values.collect::<Vec<_>>())
+            }
+            _ => panic!("unexpected column type"),
+        };
+        // Int columns with empty cells are read as floats, Str and Bool columns as objects
+        assert_eq!(values(&frame.columns[0]), "[1.0, NaN, 3.0]");
+        assert_eq!(values(&frame.columns[1]), r#"["1", "None", "3"]"#);
+        assert_eq!(values(&frame.columns[2]), r#"["true", "None", "false"]"#);
+        assert_eq!(values(&frame.columns[3]), "[0, 1, 2]");
+        // Values which do not match the data type still fail in strict mode
+        let dtypes = ColumnDTypes::One(DType::Bool);
+        assert!(ws.read_frame_rs(None, false, Some(&dtypes), true).is_err());
+    }
+
+    #[test]
+    fn partial_parts_of_sheets() {
+        let mut workbook = Workbook::new();
+        for sheet in 0..3 {
+            let worksheet = workbook.add_worksheet();
+            for row in 0..10 {
+                worksheet.write_number(row, 0, sheet * 100 + row).unwrap();
+                worksheet.write_number(row, 1, -1).unwrap();
+            }
+        }
+        let source = SourceData::Bytes(workbook.save_to_buffer().unwrap().into());
+        let reader = FileFormat::Xlsx.open(source.open().unwrap()).unwrap();
+        let mut wb = ReadOnlyWorkbook::from_reader(reader, FileFormat::Xlsx, source);
+        let range = RangeInfo::from_addr("A2:A3", RangeDTypes::One(DType::Any), true).unwrap();
+        let to_read = (0..3)
+            .map(|idx| (IdxOrName::Idx(idx), ListOrDict::List(vec![range.clone()])))
+            .collect();
+        // Only the rows and columns read are parsed, by threads parsing chunks of the sheets
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let parts = pool.install(|| wb.partial_parts_rs(&to_read)).unwrap();
+        assert_eq!(parts.len(), 3);
+        for (sheet, title) in ["Sheet1", "Sheet2", "Sheet3"].into_iter().enumerate() {
+            let part = &parts[&(title.to_string(), SheetPart::Values)];
+            let values = part.cells().map(|(_, _, value)| value.to_string());
+            let first = sheet * 100;
+            let expected = (first..=first + 2).map(|v| v.to_string());
+            assert_eq!(values.collect::<Vec<_>>(), expected.collect::<Vec<_>>());
+            assert_eq!(part.end(), Some((2, 0)));
+        }
+    }
+}
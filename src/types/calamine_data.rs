@@ -2,7 +2,9 @@ use chrono::{NaiveDate, NaiveDateTime};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::IntoPyObject;
-use rust_xlsxwriter::IntoExcelData;
+
+use super::{error_value, ExcelError, WriteFormat};
+
 #[derive(Clone)]
 pub enum CalamineData {
     Int(i64),
@@ -11,6 +13,7 @@ pub enum CalamineData {
     Bool(bool),
     Date(NaiveDate),
     DateTime(NaiveDateTime),
+    Error(ExcelError),
     Empty,
 }
 impl<'py> IntoPyObject<'py> for CalamineData {
@@ -18,7 +21,7 @@ impl<'py> IntoPyObject<'py> for CalamineData {
     type Output = Bound<'py, Self::Target>;
     type Error = PyErr;
 
-    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, PyErr> {
         match self {
             CalamineData::Int(v) => Ok(v.into_pyobject(py)?.into_any()),
             CalamineData::Float(v) => Ok(v.into_pyobject(py)?.into_any()),
@@ -26,57 +29,59 @@ impl<'py> IntoPyObject<'py> for CalamineData {
             CalamineData::Bool(v) => Ok(v.into_pyobject(py)?.as_any().clone()),
             CalamineData::Date(v) => Ok(v.into_pyobject(py)?.as_any().clone()),
             CalamineData::DateTime(v) => Ok(v.into_pyobject(py)?.as_any().clone()),
+            CalamineData::Error(v) => Ok(Bound::new(py, v)?.into_any()),
             CalamineData::Empty => Ok(py.None().into_pyobject(py)?.into_any()),
         }
     }
 }
 
-macro_rules! impl_into_excel_data {
-    ($($variant:ident),+) => {
-        impl IntoExcelData for CalamineData {
-            fn write(
-                self,
-                worksheet: &mut rust_xlsxwriter::Worksheet,
-                row: rust_xlsxwriter::RowNum,
-                col: rust_xlsxwriter::ColNum,
-            ) -> Result<&mut rust_xlsxwriter::Worksheet, rust_xlsxwriter::XlsxError> {
-                match self {
-                    $(CalamineData::$variant(v) => v.write(worksheet, row, col),)+
-                    CalamineData::Date(t) => {
-                        let date_format = rust_xlsxwriter::Format::new().set_num_format("yyyy/mm/dd");
-                        worksheet.write_datetime_with_format(row, col, &t, &date_format)
-                    },
-                    CalamineData::DateTime(t) => {
-                        let date_format = rust_xlsxwriter::Format::new().set_num_format("yyyy/mm/dd hh:mm:ss");
-                        worksheet.write_datetime_with_format(row, col, &t, &date_format)
-                    },
-                    CalamineData::Empty => Ok(worksheet),
-                }
+impl CalamineData {
+    pub fn write_to_cell(
+        &self,
+        sheet: &mut rust_xlsxwriter::Worksheet,
+        row: rust_xlsxwriter::RowNum,
+        col: rust_xlsxwriter::ColNum,
+        format: &WriteFormat,
+    ) -> Result<(), rust_xlsxwriter::XlsxError> {
+        match (self, &format.base) {
+            (CalamineData::Int(v), Some(f)) => sheet.write_with_format(row, col, *v, f),
+            (CalamineData::Int(v), None) => sheet.write(row, col, *v),
+            (CalamineData::Float(v), Some(f)) => sheet.write_with_format(row, col, *v, f),
+            (CalamineData::Float(v), None) => sheet.write(row, col, *v),
+            (CalamineData::Str(v), Some(f)) => sheet.write_with_format(row, col, v, f),
+            (CalamineData::Str(v), None) => sheet.write(row, col, v),
+            (CalamineData::Bool(v), Some(f)) => sheet.write_with_format(row, col, *v, f),
+            (CalamineData::Bool(v), None) => sheet.write(row, col, *v),
+            (CalamineData::Date(t), _) => {
+                sheet.write_datetime_with_format(row, col, t, &format.date)
             }
-
-            fn write_with_format<'a>(
-                self,
-                worksheet: &'a mut rust_xlsxwriter::Worksheet,
-                row: rust_xlsxwriter::RowNum,
-                col: rust_xlsxwriter::ColNum,
-                format: &rust_xlsxwriter::Format,
-            ) -> Result<&'a mut rust_xlsxwriter::Worksheet, rust_xlsxwriter::XlsxError> {
-                match self {
-                    $(CalamineData::$variant(v) => v.write_with_format(worksheet, row, col, format),)+
-                    CalamineData::Date(t) => t.write_with_format(worksheet, row, col, format),
-                    CalamineData::DateTime(t) => t.write_with_format(worksheet, row, col, format),
-                    CalamineData::Empty => Ok(worksheet),
-                }
+            (CalamineData::DateTime(t), _) => {
+                sheet.write_datetime_with_format(row, col, t, &format.datetime)
+            }
+            (CalamineData::Error(e), Some(f)) => {
+                sheet.write_formula_with_format(row, col, error_formula(e), f)
             }
+            (CalamineData::Error(e), None) => sheet.write_formula(row, col, error_formula(e)),
+            (CalamineData::Empty, Some(f)) => sheet.write_blank(row, col, f),
+            (CalamineData::Empty, None) => Ok(sheet),
         }
-    };
+        .map(|_| ())
+    }
+}
+
+/// Error cells are written as formulas of the error, e.g. `=#N/A`, with a marked result, as
+/// rust_xlsxwriter could not write error cells. The formula is replaced by the error once the
+/// workbook is saved.
+fn error_formula(error: &ExcelError) -> rust_xlsxwriter::Formula {
+    rust_xlsxwriter::Formula::new(format!("={}", error.as_str())).set_result(error_value(error))
 }
-impl_into_excel_data!(Int, Float, Str, Bool);
 
 impl<'py> FromPyObject<'py> for CalamineData {
     fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
         if ob.is_none() {
             return Ok(Self::Empty);
+        } else if let Ok(error) = ob.extract::<ExcelError>() {
+            return Ok(Self::Error(error));
         } else if let Ok(bool_val) = ob.extract::<bool>() {
             return Ok(Self::Bool(bool_val));
         } else if let Ok(int_val) = ob.extract::<i64>() {
@@ -91,6 +96,6 @@ impl<'py> FromPyObject<'py> for CalamineData {
         } else if let Ok(date_val) = ob.extract::<NaiveDate>() {
             return Ok(Self::Date(date_val));
         }
-        return Err(PyValueError::new_err("Invalid type"));
+        Err(PyValueError::new_err("Invalid type"))
     }
 }
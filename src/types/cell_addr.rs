@@ -1,6 +1,205 @@
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
+/// Number of rows of a worksheet in Excel
+pub const MAX_ROWS: usize = 1_048_576;
+/// Number of columns of a worksheet in Excel
+pub const MAX_COLS: usize = 16_384;
+
+fn invalid_addr(addr: &str) -> PyErr {
+    PyValueError::new_err(format!("Invalid cell address: {addr}"))
+}
+
+/// Check a 0-based (row, col) is inside a worksheet.
+fn check_idx(idx: (usize, usize)) -> PyResult<()> {
+    if idx.0 >= MAX_ROWS || idx.1 >= MAX_COLS {
+        return Err(PyValueError::new_err(format!(
+            "Cell index {idx:?} is out of the worksheet, which has {MAX_ROWS} rows and {MAX_COLS} columns"
+        )));
+    }
+    Ok(())
+}
+
+/// Convert a 0-based column index to its letters, e.g. 0 -> "A", 26 -> "AA".
+pub fn col_to_letters(col: usize) -> String {
+    // Bijective base-26: there is no zero digit, "Z" is followed by "AA"
+    let mut letters = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        letters.push(b'A' + ((n - 1) % 26) as u8);
+        n = (n - 1) / 26;
+    }
+    letters.iter().rev().map(|&c| c as char).collect()
+}
+
+/// Convert column letters (case-insensitive) to a 0-based column index, e.g. "AA" -> 26.
+fn letters_to_col(letters: &str) -> Option<usize> {
+    let col = letters.chars().try_fold(0_usize, |col, c| {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        let x = c.to_ascii_uppercase() as usize - 'A' as usize + 1;
+        col.checked_mul(26)?.checked_add(x)
+    })?;
+    (1..=MAX_COLS).contains(&col).then(|| col - 1)
+}
+
+/// Convert a 1-based row or column number to a 0-based index, which should not exceed `max`.
+fn number_to_idx(number: &str, max: usize) -> Option<usize> {
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let n = number.parse::<usize>().ok()?;
+    (1..=max).contains(&n).then(|| n - 1)
+}
+
+/// One side of a reference, a missing row or column means a whole column or row.
+#[derive(Clone, Copy)]
+struct RefPart {
+    row: Option<usize>,
+    col: Option<usize>,
+}
+
+/// Parse `R1C1`-style cell reference, only absolute references are supported.
+fn parse_r1c1(part: &str) -> Option<RefPart> {
+    let rest = part.strip_prefix(['R', 'r'])?;
+    let (row, col) = rest.split_once(['C', 'c'])?;
+    Some(RefPart {
+        row: Some(number_to_idx(row, MAX_ROWS)?),
+        col: Some(number_to_idx(col, MAX_COLS)?),
+    })
+}
+
+/// Parse `A1`, `$A$1`, `A` or `1`.
+fn parse_a1(part: &str) -> Option<RefPart> {
+    let rest = part.strip_prefix('$').unwrap_or(part);
+    let n_letters = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    let (letters, rest) = rest.split_at(n_letters);
+    let number = match rest.strip_prefix('$') {
+        // `$` should be followed by the row number, and not be repeated
+        Some(number) if !letters.is_empty() && !number.is_empty() => number,
+        Some(_) => return None,
+        None => rest,
+    };
+    let part = RefPart {
+        row: if number.is_empty() {
+            None
+        } else {
+            Some(number_to_idx(number, MAX_ROWS)?)
+        },
+        col: if letters.is_empty() {
+            None
+        } else {
+            Some(letters_to_col(letters)?)
+        },
+    };
+    (part.row.is_some() || part.col.is_some()).then_some(part)
+}
+
+fn parse_part(part: &str) -> Option<RefPart> {
+    parse_r1c1(part).or_else(|| parse_a1(part))
+}
+
+/// Split the sheet name from a reference like `'My Sheet'!A1` or `Sheet1!A1`.
+fn split_sheet(addr: &str) -> Option<(Option<String>, &str)> {
+    if let Some(quoted) = addr.strip_prefix('\'') {
+        // A quote in the sheet name is escaped by doubling it
+        let mut sheet = String::new();
+        let mut chars = quoted.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c != '\'' {
+                sheet.push(c);
+            } else if matches!(chars.peek(), Some((_, '\''))) {
+                sheet.push('\'');
+                chars.next();
+            } else {
+                let rest = quoted[i + 1..].strip_prefix('!')?;
+                return (!sheet.is_empty()).then_some((Some(sheet), rest));
+            }
+        }
+        return None;
+    }
+    match addr.rsplit_once('!') {
+        Some((sheet, rest)) => (!sheet.is_empty()).then(|| (Some(sheet.to_string()), rest)),
+        None => Some((None, addr)),
+    }
+}
+
+/// A parsed cell or range reference.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellRange {
+    /// The sheet name if the reference is sheet-qualified
+    pub sheet: Option<String>,
+    /// The 0-based (row, col) of the top-left cell
+    pub first: (usize, usize),
+    /// The 0-based (row, col) of the bottom-right cell
+    pub last: (usize, usize),
+}
+impl CellRange {
+    /**
+        Parse a reference, which could be:
+
+        - a cell: `A1`, `a1`, `$A$1`, `R1C1`
+        - a range: `A1:C10`, `R1C1:R10C3`
+        - whole columns or rows: `A:C`, `1:3`
+        - any of above qualified by a sheet name: `Sheet1!A1`, `'My Sheet'!B2:D9`
+    */
+    pub fn parse(addr: &str) -> PyResult<Self> {
+        let (sheet, range) = split_sheet(addr.trim()).ok_or_else(|| invalid_addr(addr))?;
+        let (first, last) = match range.split_once(':') {
+            Some((first, last)) => (
+                parse_part(first).ok_or_else(|| invalid_addr(addr))?,
+                parse_part(last).ok_or_else(|| invalid_addr(addr))?,
+            ),
+            None => {
+                let part = parse_part(range).ok_or_else(|| invalid_addr(addr))?;
+                if part.row.is_none() || part.col.is_none() {
+                    // A single row or column should be written as `1:1` or `A:A`
+                    return Err(invalid_addr(addr));
+                }
+                (part, part)
+            }
+        };
+        let ((r0, r1), (c0, c1)) = match ((first.row, first.col), (last.row, last.col)) {
+            ((Some(r0), Some(c0)), (Some(r1), Some(c1))) => ((r0, r1), (c0, c1)),
+            // Whole columns
+            ((None, Some(c0)), (None, Some(c1))) => ((0, MAX_ROWS - 1), (c0, c1)),
+            // Whole rows
+            ((Some(r0), None), (Some(r1), None)) => ((r0, r1), (0, MAX_COLS - 1)),
+            _ => return Err(invalid_addr(addr)),
+        };
+        // Like Excel, `C10:A1` is the same as `A1:C10`
+        Ok(Self {
+            sheet,
+            first: (r0.min(r1), c0.min(c1)),
+            last: (r0.max(r1), c0.max(c1)),
+        })
+    }
+    pub fn is_cell(&self) -> bool {
+        self.first == self.last
+    }
+}
+
+/// Format a 0-based (row, col) as `A1`.
+pub fn idx_to_addr(row: usize, col: usize) -> String {
+    format!("{}{}", col_to_letters(col), row + 1)
+}
+
+/// Format a 0-based range as `A1:C10`, or `A1` if it is a single cell.
+pub fn idx_to_range(first: (usize, usize), last: (usize, usize)) -> String {
+    if first == last {
+        idx_to_addr(first.0, first.1)
+    } else {
+        format!(
+            "{}:{}",
+            idx_to_addr(first.0, first.1),
+            idx_to_addr(last.0, last.1)
+        )
+    }
+}
+
 #[derive(Clone, FromPyObject, IntoPyObject)]
 pub enum CellAddr {
     Idx((usize, usize)),
@@ -11,46 +210,147 @@ impl CellAddr {
         match self {
             CellAddr::Idx(idx) => Ok(*idx),
             CellAddr::Name(name) => {
-                let (letters, number) =
-                    name.split_at(name.chars().position(|c| c.is_digit(10)).ok_or(
-                        PyValueError::new_err(format!("Invalid cell address: {name}")),
-                    )?);
-                let mut col: usize = 0;
-                let _ = letters.chars().try_for_each(|c| {
-                    let x = c as usize - 'A' as usize + 1;
-                    if x > 26_usize {
-                        return Err(PyValueError::new_err(format!(
-                            "Invalid cell address: {name}"
-                        )));
-                    } else {
-                        col = col * 26 + x
-                    }
-                    Ok(())
-                })?;
-                col -= 1;
-
-                let row = number
-                    .parse::<usize>()
-                    .map_err(|_| PyValueError::new_err(format!("Invalid cell address: {name}")))?
-                    - 1;
-
-                Ok((row, col))
+                let range = CellRange::parse(name)?;
+                if !range.is_cell() {
+                    return Err(PyValueError::new_err(format!(
+                        "Expected a single cell address, got a range: {name}"
+                    )));
+                }
+                Ok(range.first)
             }
         }
     }
     pub fn as_addr(&self) -> PyResult<String> {
         match self {
             CellAddr::Idx((row, col)) => {
-                let mut letters = String::new();
-                let mut col = *col;
-                while col >= 26 {
-                    letters.push((col % 26 + 'A' as usize) as u8 as char);
-                    col /= 26;
-                }
-                letters.push((col + 'A' as usize) as u8 as char);
-                Ok(format!("{}{}", letters, row + 1))
+                check_idx((*row, *col))?;
+                Ok(idx_to_addr(*row, *col))
             }
             CellAddr::Name(name) => Ok(name.clone()),
         }
     }
 }
+
+#[derive(Clone, FromPyObject)]
+pub enum RangeAddr {
+    Idx(((usize, usize), (usize, usize))),
+    Name(String),
+}
+impl RangeAddr {
+    pub fn as_idx(&self) -> PyResult<((usize, usize), (usize, usize))> {
+        match self {
+            RangeAddr::Idx((first, last)) => {
+                check_idx(*first)?;
+                check_idx(*last)?;
+                if first.0 > last.0 || first.1 > last.1 {
+                    return Err(PyValueError::new_err(
+                        "Invalid range: the first cell must be above and to the left of the last cell",
+                    ));
+                }
+                Ok((*first, *last))
+            }
+            RangeAddr::Name(name) => {
+                let range = CellRange::parse(name)?;
+                Ok((range.first, range.last))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(addr: &str) -> CellRange {
+        CellRange::parse(addr).unwrap()
+    }
+
+    #[test]
+    fn parse_cells() {
+        for addr in ["A1", "a1", "$A$1", "$A1", "A$1", "R1C1", "r1c1"] {
+            assert_eq!(parse(addr).first, (0, 0), "{addr}");
+            assert!(parse(addr).is_cell(), "{addr}");
+        }
+        assert_eq!(parse("XFD1048576").first, (MAX_ROWS - 1, MAX_COLS - 1));
+        assert_eq!(parse("R10C3").first, (9, 2));
+    }
+
+    #[test]
+    fn parse_ranges() {
+        let range = parse("B2:D9");
+        assert_eq!((range.first, range.last), ((1, 1), (8, 3)));
+        assert_eq!(parse("R2C2:R9C4"), range);
+        let columns = parse("A:C");
+        assert_eq!((columns.first, columns.last), ((0, 0), (MAX_ROWS - 1, 2)));
+        let rows = parse("1:3");
+        assert_eq!((rows.first, rows.last), ((0, 0), (2, MAX_COLS - 1)));
+    }
+
+    #[test]
+    fn parse_reversed_ranges() {
+        assert_eq!(parse("D9:B2"), parse("B2:D9"));
+        assert_eq!(parse("B9:D2"), parse("B2:D9"));
+        assert_eq!(parse("C:A"), parse("A:C"));
+        assert!(RangeAddr::Idx(((8, 3), (1, 1))).as_idx().is_err());
+    }
+
+    #[test]
+    fn parse_sheet_names() {
+        let range = parse("Sheet1!A1");
+        assert_eq!(range.sheet.as_deref(), Some("Sheet1"));
+        let range = parse("'My Sheet'!B2:D9");
+        assert_eq!(range.sheet.as_deref(), Some("My Sheet"));
+        assert_eq!((range.first, range.last), ((1, 1), (8, 3)));
+        assert_eq!(parse("'It''s'!A1").sheet.as_deref(), Some("It's"));
+        assert_eq!(parse("'a!b'!A1").sheet.as_deref(), Some("a!b"));
+        assert_eq!(parse("A1").sheet, None);
+    }
+
+    #[test]
+    fn reject_invalid() {
+        for addr in [
+            "",
+            "A",
+            "1",
+            "A0",
+            "R0C1",
+            "R1C0",
+            "XFE1",
+            "A1048577",
+            "$$A1",
+            "A$$1",
+            "A$",
+            "1A",
+            "A1:",
+            ":A1",
+            "A:1",
+            "A1:C",
+            "!A1",
+            "''!A1",
+            "'Sheet1!A1",
+            "'Sheet1'A1",
+        ] {
+            assert!(CellRange::parse(addr).is_err(), "{addr}");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for (row, col) in [(0, 0), (9, 25), (99, 26), (0, 701), (0, 702)] {
+            let addr = idx_to_addr(row, col);
+            assert_eq!(parse(&addr).first, (row, col), "{addr}");
+        }
+        assert_eq!(col_to_letters(MAX_COLS - 1), "XFD");
+        for addr in ["A1", "B2:D9", "A1:XFD1048576"] {
+            let range = parse(addr);
+            assert_eq!(idx_to_range(range.first, range.last), addr);
+        }
+    }
+
+    #[test]
+    fn cell_addr_rejects_ranges() {
+        assert_eq!(CellAddr::Name("C5".into()).as_idx().unwrap(), (4, 2));
+        assert!(CellAddr::Name("A1:B2".into()).as_idx().is_err());
+        assert!(CellAddr::Idx((MAX_ROWS, 0)).as_addr().is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rust_xlsxwriter::{Color, FormatAlign, FormatBorder, FormatUnderline};
+
+pub const DEFAULT_DATE_NUM_FORMAT: &str = "yyyy/mm/dd";
+pub const DEFAULT_DATETIME_NUM_FORMAT: &str = "yyyy/mm/dd hh:mm:ss";
+
+fn parse_color(color: &str) -> PyResult<Color> {
+    let color = match color.to_lowercase().as_str() {
+        "black" => Color::Black,
+        "blue" => Color::Blue,
+        "brown" => Color::Brown,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "green" => Color::Green,
+        "lime" => Color::Lime,
+        "magenta" => Color::Magenta,
+        "navy" => Color::Navy,
+        "orange" => Color::Orange,
+        "pink" => Color::Pink,
+        "purple" => Color::Purple,
+        "red" => Color::Red,
+        "silver" => Color::Silver,
+        "white" => Color::White,
+        "yellow" => Color::Yellow,
+        other => {
+            let hex = other.strip_prefix('#').unwrap_or(other);
+            if hex.len() != 6 {
+                return Err(PyValueError::new_err(format!("Invalid color: {color}")));
+            }
+            let rgb = u32::from_str_radix(hex, 16)
+                .map_err(|_| PyValueError::new_err(format!("Invalid color: {color}")))?;
+            Color::RGB(rgb)
+        }
+    };
+    Ok(color)
+}
+
+fn parse_border(border: &str) -> PyResult<FormatBorder> {
+    match border {
+        "none" => Ok(FormatBorder::None),
+        "thin" => Ok(FormatBorder::Thin),
+        "medium" => Ok(FormatBorder::Medium),
+        "dashed" => Ok(FormatBorder::Dashed),
+        "dotted" => Ok(FormatBorder::Dotted),
+        "thick" => Ok(FormatBorder::Thick),
+        "double" => Ok(FormatBorder::Double),
+        "hair" => Ok(FormatBorder::Hair),
+        "medium_dashed" => Ok(FormatBorder::MediumDashed),
+        "dash_dot" => Ok(FormatBorder::DashDot),
+        "medium_dash_dot" => Ok(FormatBorder::MediumDashDot),
+        "dash_dot_dot" => Ok(FormatBorder::DashDotDot),
+        "medium_dash_dot_dot" => Ok(FormatBorder::MediumDashDotDot),
+        "slant_dash_dot" => Ok(FormatBorder::SlantDashDot),
+        _ => Err(PyValueError::new_err(format!(
+            "Invalid border style: {border}"
+        ))),
+    }
+}
+
+fn parse_align(align: &str) -> PyResult<FormatAlign> {
+    match align {
+        "general" => Ok(FormatAlign::General),
+        "left" => Ok(FormatAlign::Left),
+        "center" => Ok(FormatAlign::Center),
+        "right" => Ok(FormatAlign::Right),
+        "fill" => Ok(FormatAlign::Fill),
+        "justify" => Ok(FormatAlign::Justify),
+        "center_across" => Ok(FormatAlign::CenterAcross),
+        "distributed" => Ok(FormatAlign::Distributed),
+        _ => Err(PyValueError::new_err(format!(
+            "Invalid horizontal alignment: {align}"
+        ))),
+    }
+}
+
+fn parse_valign(valign: &str) -> PyResult<FormatAlign> {
+    match valign {
+        "top" => Ok(FormatAlign::Top),
+        "center" => Ok(FormatAlign::VerticalCenter),
+        "bottom" => Ok(FormatAlign::Bottom),
+        "justify" => Ok(FormatAlign::VerticalJustify),
+        "distributed" => Ok(FormatAlign::VerticalDistributed),
+        _ => Err(PyValueError::new_err(format!(
+            "Invalid vertical alignment: {valign}"
+        ))),
+    }
+}
+
+/// Class to describe the format of cells.
+#[pyclass]
+#[derive(Clone)]
+pub struct Format {
+    pub format: rust_xlsxwriter::Format,
+    #[pyo3(get)]
+    pub num_format: Option<String>,
+}
+#[pymethods]
+impl Format {
+    /**
+        Generate a Format object.
+
+        Parameters
+        ----------
+        bold : bool, default False
+            Whether to use bold font.
+        italic : bool, default False
+            Whether to use italic font.
+        underline : bool, default False
+            Whether to underline the text.
+        font_name : Optional[str], default None
+            The font name, e.g. "Calibri".
+        font_size : Optional[float], default None
+            The font size in points.
+        font_color : Optional[str], default None
+            The font color, as a "#RRGGBB" string or a color name (e.g., "red").
+        bg_color : Optional[str], default None
+            The background fill color, as a "#RRGGBB" string or a color name.
+        border : Optional[str], default None
+            The border style of all four sides, e.g. "thin", "medium", "thick", "dashed",
+            "dotted", "double".
+        border_color : Optional[str], default None
+            The border color, as a "#RRGGBB" string or a color name.
+        align : Optional[str], default None
+            The horizontal alignment, one of "general", "left", "center", "right", "fill",
+            "justify", "center_across", "distributed".
+        valign : Optional[str], default None
+            The vertical alignment, one of "top", "center", "bottom", "justify", "distributed".
+        num_format : Optional[str], default None
+            The number format, e.g. "0.00%" or "yyyy-mm-dd".
+        text_wrap : bool, default False
+            Whether to wrap the text in the cell.
+    */
+    #[new]
+    #[pyo3(signature = (
+        *,
+        bold = false,
+        italic = false,
+        underline = false,
+        font_name = None,
+        font_size = None,
+        font_color = None,
+        bg_color = None,
+        border = None,
+        border_color = None,
+        align = None,
+        valign = None,
+        num_format = None,
+        text_wrap = false
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        bold: bool,
+        italic: bool,
+        underline: bool,
+        font_name: Option<String>,
+        font_size: Option<f64>,
+        font_color: Option<String>,
+        bg_color: Option<String>,
+        border: Option<String>,
+        border_color: Option<String>,
+        align: Option<String>,
+        valign: Option<String>,
+        num_format: Option<String>,
+        text_wrap: bool,
+    ) -> PyResult<Self> {
+        let mut format = rust_xlsxwriter::Format::new();
+        if bold {
+            format = format.set_bold();
+        }
+        if italic {
+            format = format.set_italic();
+        }
+        if underline {
+            format = format.set_underline(FormatUnderline::Single);
+        }
+        if let Some(font_name) = font_name {
+            format = format.set_font_name(font_name);
+        }
+        if let Some(font_size) = font_size {
+            format = format.set_font_size(font_size);
+        }
+        if let Some(font_color) = font_color {
+            format = format.set_font_color(parse_color(&font_color)?);
+        }
+        if let Some(bg_color) = bg_color {
+            format = format.set_background_color(parse_color(&bg_color)?);
+        }
+        if let Some(border) = border {
+            format = format.set_border(parse_border(&border)?);
+        }
+        if let Some(border_color) = border_color {
+            format = format.set_border_color(parse_color(&border_color)?);
+        }
+        if let Some(align) = align {
+            format = format.set_align(parse_align(&align)?);
+        }
+        if let Some(valign) = valign {
+            format = format.set_align(parse_valign(&valign)?);
+        }
+        if let Some(num_format) = &num_format {
+            format = format.set_num_format(num_format);
+        }
+        if text_wrap {
+            format = format.set_text_wrap();
+        }
+        Ok(Self { format, num_format })
+    }
+    fn __repr__(&self) -> String {
+        match &self.num_format {
+            Some(num_format) => format!("<Format num_format=\"{}\">", num_format),
+            None => "<Format>".to_string(),
+        }
+    }
+}
+
+impl Format {
+    /// Override the number format of `format`, starting from a default format if it is None.
+    pub fn with_num_format(format: Option<Format>, num_format: Option<String>) -> Option<Format> {
+        match num_format {
+            Some(num_format) => Some(Format {
+                format: format
+                    .map(|f| f.format)
+                    .unwrap_or_default()
+                    .set_num_format(&num_format),
+                num_format: Some(num_format),
+            }),
+            None => format,
+        }
+    }
+}
+
+/// The formats to apply for one write, resolved for each kind of value.
+///
+/// Dates and datetimes always need a number format to be displayed as such, so they fall
+/// back to a default one when the user format does not provide it.
+pub struct WriteFormat {
+    pub base: Option<rust_xlsxwriter::Format>,
+    pub date: rust_xlsxwriter::Format,
+    pub datetime: rust_xlsxwriter::Format,
+}
+impl WriteFormat {
+    pub fn new(format: Option<&Format>, date_num_format: &str, datetime_num_format: &str) -> Self {
+        let with_num_format = |num_format: &str| match format {
+            Some(Format {
+                format,
+                num_format: Some(_),
+            }) => format.clone(),
+            Some(Format { format, .. }) => format.clone().set_num_format(num_format),
+            None => rust_xlsxwriter::Format::new().set_num_format(num_format),
+        };
+        Self {
+            base: format.map(|f| f.format.clone()),
+            date: with_num_format(date_num_format),
+            datetime: with_num_format(datetime_num_format),
+        }
+    }
+}
@@ -6,7 +6,7 @@ use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 use pyo3::types::PyAny;
 
-use super::WriteToSheet;
+use super::{WriteFormat, WriteToSheet};
 
 #[derive(Clone)]
 pub struct Array1Container<T> {
@@ -20,6 +20,9 @@ impl<T: Clone> Array1Container<T> {
     pub fn len(&self) -> usize {
         self.value.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
     pub fn mapv<U>(&self, f: impl Fn(T) -> U) -> Array1Container<U> {
         Array1Container {
             value: self.value.mapv(f),
@@ -104,11 +107,13 @@ macro_rules! impl_into_exceldata_for_array1_container_other {
                 sheet: &mut rust_xlsxwriter::Worksheet,
                 start: (u32, u16),
                 is_column: bool,
+                format: &WriteFormat,
             ) -> Result<(), rust_xlsxwriter::XlsxError> {
-                if is_column {
-                    sheet.write_column(start.0, start.1, self.value.clone())?
-                } else {
-                    sheet.write_row(start.0, start.1, self.value.clone())?
+                match (is_column, &format.base) {
+                    (true, Some(f)) => sheet.write_column_with_format(start.0, start.1, self.value.clone(), f)?,
+                    (true, None) => sheet.write_column(start.0, start.1, self.value.clone())?,
+                    (false, Some(f)) => sheet.write_row_with_format(start.0, start.1, self.value.clone(), f)?,
+                    (false, None) => sheet.write_row(start.0, start.1, self.value.clone())?,
                 };
                 Ok(())
             }
@@ -117,25 +122,26 @@ macro_rules! impl_into_exceldata_for_array1_container_other {
 }
 
 macro_rules! impl_into_exceldata_for_array1_container_datetime {
-    ($($type:ty)* => $format:expr) => ($(
+    ($($type:ty)* => $format_field:ident) => ($(
         impl WriteToSheet for Array1Container<$type> {
             fn write_to_sheet(
                 &self,
                 sheet: &mut rust_xlsxwriter::Worksheet,
                 start: (u32, u16),
                 is_column: bool,
+                format: &WriteFormat,
             ) -> Result<(), rust_xlsxwriter::XlsxError> {
-                let date_format = rust_xlsxwriter::Format::new().set_num_format($format);
+                let date_format = &format.$format_field;
                 if is_column {
                     self.value.indexed_iter().try_for_each(|(i, v)| {
                         sheet
-                            .write_datetime_with_format(start.0 + i as u32, start.1, v, &date_format)
+                            .write_datetime_with_format(start.0 + i as u32, start.1, v, date_format)
                             .map(|_| ())
                     })?
                 } else {
                     self.value.indexed_iter().try_for_each(|(j, v)| {
                         sheet
-                            .write_datetime_with_format(start.0, start.1 + j as u16, v, &date_format)
+                            .write_datetime_with_format(start.0, start.1 + j as u16, v, date_format)
                             .map(|_| ())
                     })?
                 };
@@ -144,6 +150,24 @@ macro_rules! impl_into_exceldata_for_array1_container_datetime {
         }
     )*)
 }
-impl_into_exceldata_for_array1_container_other!(bool i64 f64 String CalamineData);
-impl_into_exceldata_for_array1_container_datetime! {NaiveDate => "yyyy/mm/dd"}
-impl_into_exceldata_for_array1_container_datetime! {NaiveDateTime => "yyyy/mm/dd hh:mm:ss"}
+
+impl WriteToSheet for Array1Container<CalamineData> {
+    fn write_to_sheet(
+        &self,
+        sheet: &mut rust_xlsxwriter::Worksheet,
+        start: (u32, u16),
+        is_column: bool,
+        format: &WriteFormat,
+    ) -> Result<(), rust_xlsxwriter::XlsxError> {
+        self.value.indexed_iter().try_for_each(|(i, v)| {
+            if is_column {
+                v.write_to_cell(sheet, start.0 + i as u32, start.1, format)
+            } else {
+                v.write_to_cell(sheet, start.0, start.1 + i as u16, format)
+            }
+        })
+    }
+}
+impl_into_exceldata_for_array1_container_other!(bool i64 f64 String);
+impl_into_exceldata_for_array1_container_datetime! {NaiveDate => date}
+impl_into_exceldata_for_array1_container_datetime! {NaiveDateTime => datetime}
@@ -6,7 +6,7 @@ use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 use pyo3::types::PyAny;
 
-use super::WriteToSheet;
+use super::{WriteFormat, WriteToSheet};
 
 #[derive(Clone)]
 pub struct Array2Container<T> {
@@ -19,6 +19,9 @@ impl<T: Clone> Array2Container<T> {
     pub fn len(&self) -> usize {
         self.value.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
     pub fn nrows(&self) -> usize {
         self.value.nrows()
     }
@@ -129,12 +132,22 @@ macro_rules! impl_into_exceldata_for_array2_container_other {
                 sheet: &mut rust_xlsxwriter::Worksheet,
                 start: (u32, u16),
                 _is_column: bool,
+                format: &WriteFormat,
             ) -> Result<(), rust_xlsxwriter::XlsxError> {
-                sheet.write_row_matrix(
-                    start.0,
-                    start.1,
-                    self.value.outer_iter().map(|row| row.to_vec()),
-                )?;
+                match &format.base {
+                    Some(f) => self.value.outer_iter().enumerate().try_for_each(|(i, row)| {
+                        sheet
+                            .write_row_with_format(start.0 + i as u32, start.1, row.to_vec(), f)
+                            .map(|_| ())
+                    })?,
+                    None => {
+                        sheet.write_row_matrix(
+                            start.0,
+                            start.1,
+                            self.value.outer_iter().map(|row| row.to_vec()),
+                        )?;
+                    }
+                };
                 Ok(())
             }
         }
@@ -142,18 +155,19 @@ macro_rules! impl_into_exceldata_for_array2_container_other {
 }
 
 macro_rules! impl_into_exceldata_for_array2_container_datetime {
-    ($($type:ty)* => $format:expr) => ($(
+    ($($type:ty)* => $format_field:ident) => ($(
         impl WriteToSheet for Array2Container<$type> {
             fn write_to_sheet(
                 &self,
                 sheet: &mut rust_xlsxwriter::Worksheet,
                 start: (u32, u16),
                 _is_column: bool,
+                format: &WriteFormat,
             ) -> Result<(), rust_xlsxwriter::XlsxError> {
-                let date_format = rust_xlsxwriter::Format::new().set_num_format($format);
+                let date_format = &format.$format_field;
                 self.value.indexed_iter().try_for_each(|((i, j), v)| {
                     sheet
-                        .write_datetime_with_format(start.0 + i as u32, start.1 + j as u16, v, &date_format)
+                        .write_datetime_with_format(start.0 + i as u32, start.1 + j as u16, v, date_format)
                         .map(|_| ())
                 })?;
                 Ok(())
@@ -161,6 +175,20 @@ macro_rules! impl_into_exceldata_for_array2_container_datetime {
         }
     )*)
 }
-impl_into_exceldata_for_array2_container_other!(bool i64 f64 String CalamineData);
-impl_into_exceldata_for_array2_container_datetime! {NaiveDate => "yyyy/mm/dd"}
-impl_into_exceldata_for_array2_container_datetime! {NaiveDateTime => "yyyy/mm/dd hh:mm:ss"}
+
+impl WriteToSheet for Array2Container<CalamineData> {
+    fn write_to_sheet(
+        &self,
+        sheet: &mut rust_xlsxwriter::Worksheet,
+        start: (u32, u16),
+        _is_column: bool,
+        format: &WriteFormat,
+    ) -> Result<(), rust_xlsxwriter::XlsxError> {
+        self.value.indexed_iter().try_for_each(|((i, j), v)| {
+            v.write_to_cell(sheet, start.0 + i as u32, start.1 + j as u16, format)
+        })
+    }
+}
+impl_into_exceldata_for_array2_container_other!(bool i64 f64 String);
+impl_into_exceldata_for_array2_container_datetime! {NaiveDate => date}
+impl_into_exceldata_for_array2_container_datetime! {NaiveDateTime => datetime}
@@ -1,5 +1,6 @@
-use crate::types::{CalamineData, DShape, DType};
+use crate::types::{is_marked, CalamineData, DShape, DType, FormulaData, FormulaKind, WriteFormat};
 use chrono::{NaiveDate, NaiveDateTime};
+use indexmap::IndexMap;
 use pyo3::prelude::*;
 use pyo3::types::PyAny;
 
@@ -88,6 +89,7 @@ pub trait WriteToSheet {
         sheet: &mut rust_xlsxwriter::Worksheet,
         start: (u32, u16),
         is_column: bool,
+        format: &WriteFormat,
     ) -> Result<(), rust_xlsxwriter::XlsxError>;
 }
 
@@ -99,11 +101,13 @@ macro_rules! impl_into_exceldata_for_value_container_other {
                 sheet: &mut rust_xlsxwriter::Worksheet,
                 start: (u32, u16),
                 is_column: bool,
+                format: &WriteFormat,
             ) -> Result<(), rust_xlsxwriter::XlsxError> {
-                match self {
-                    ValueContainer::Scalar(v) => sheet.write(start.0, start.1, v.clone()).map(|_| ()),
-                    ValueContainer::Array1(arr1) => arr1.write_to_sheet(sheet, start, is_column),
-                    ValueContainer::Array2(arr2) => arr2.write_to_sheet(sheet, start, is_column),
+                match (self, &format.base) {
+                    (ValueContainer::Scalar(v), Some(f)) => sheet.write_with_format(start.0, start.1, v.clone(), f).map(|_| ()),
+                    (ValueContainer::Scalar(v), None) => sheet.write(start.0, start.1, v.clone()).map(|_| ()),
+                    (ValueContainer::Array1(arr1), _) => arr1.write_to_sheet(sheet, start, is_column, format),
+                    (ValueContainer::Array2(arr2), _) => arr2.write_to_sheet(sheet, start, is_column, format),
                 }
             }
         }
@@ -111,28 +115,43 @@ macro_rules! impl_into_exceldata_for_value_container_other {
 }
 
 macro_rules! impl_into_exceldata_for_value_container_datetime {
-    ($($type:ty)* => $format:expr) => ($(
+    ($($type:ty)* => $format_field:ident) => ($(
         impl WriteToSheet for ValueContainer<$type> {
             fn write_to_sheet(
                 &self,
                 sheet: &mut rust_xlsxwriter::Worksheet,
                 start: (u32, u16),
                 is_column: bool,
+                format: &WriteFormat,
             ) -> Result<(), rust_xlsxwriter::XlsxError> {
-                let date_format = rust_xlsxwriter::Format::new().set_num_format($format);
                 match self {
-                    ValueContainer::Scalar(v) => sheet.write_datetime_with_format(start.0, start.1, v, &date_format).map(|_| ()),
-                    ValueContainer::Array1(arr1) => arr1.write_to_sheet(sheet, start, is_column),
-                    ValueContainer::Array2(arr2) => arr2.write_to_sheet(sheet, start, is_column)
+                    ValueContainer::Scalar(v) => sheet.write_datetime_with_format(start.0, start.1, v, &format.$format_field).map(|_| ()),
+                    ValueContainer::Array1(arr1) => arr1.write_to_sheet(sheet, start, is_column, format),
+                    ValueContainer::Array2(arr2) => arr2.write_to_sheet(sheet, start, is_column, format)
                 }
             }
         }
     )*)
 }
 
-impl_into_exceldata_for_value_container_other!(bool i64 f64 String CalamineData);
-impl_into_exceldata_for_value_container_datetime! {NaiveDate => "yyyy/mm/dd"}
-impl_into_exceldata_for_value_container_datetime! {NaiveDateTime => "yyyy/mm/dd hh:mm:ss"}
+impl WriteToSheet for ValueContainer<CalamineData> {
+    fn write_to_sheet(
+        &self,
+        sheet: &mut rust_xlsxwriter::Worksheet,
+        start: (u32, u16),
+        is_column: bool,
+        format: &WriteFormat,
+    ) -> Result<(), rust_xlsxwriter::XlsxError> {
+        match self {
+            ValueContainer::Scalar(v) => v.write_to_cell(sheet, start.0, start.1, format),
+            ValueContainer::Array1(arr1) => arr1.write_to_sheet(sheet, start, is_column, format),
+            ValueContainer::Array2(arr2) => arr2.write_to_sheet(sheet, start, is_column, format),
+        }
+    }
+}
+impl_into_exceldata_for_value_container_other!(bool i64 f64 String);
+impl_into_exceldata_for_value_container_datetime! {NaiveDate => date}
+impl_into_exceldata_for_value_container_datetime! {NaiveDateTime => datetime}
 
 #[derive(Clone, IntoPyObject)]
 pub enum WrappedValue {
@@ -143,6 +162,9 @@ pub enum WrappedValue {
     Date(ValueContainer<NaiveDate>),
     DateTime(ValueContainer<NaiveDateTime>),
     Any(ValueContainer<CalamineData>),
+    Formula(FormulaData),
+    /// Columns read with their own data types, by their index in the range
+    Columns(IndexMap<usize, WrappedValue>),
 }
 impl WrappedValue {
     pub fn get_dtype(&self) -> DType {
@@ -154,6 +176,9 @@ impl WrappedValue {
             WrappedValue::Date(_) => DType::Date,
             WrappedValue::DateTime(_) => DType::DateTime,
             WrappedValue::Any(_) => DType::Any,
+            // Formulas are stored as their text
+            WrappedValue::Formula(_) => DType::Str,
+            WrappedValue::Columns(_) => DType::Any,
         }
     }
 
@@ -166,6 +191,37 @@ impl WrappedValue {
             WrappedValue::Date(ref v) => v.get_shape(is_column),
             WrappedValue::DateTime(ref v) => v.get_shape(is_column),
             WrappedValue::Any(ref v) => v.get_shape(is_column),
+            WrappedValue::Formula(ref v) => match v.kind {
+                FormulaKind::Single => DShape::Scalar {},
+                FormulaKind::Array(shape) | FormulaKind::DynamicArray(shape) => DShape::Matrix {
+                    n_rows: shape.0 as usize,
+                    n_cols: shape.1 as usize,
+                },
+            },
+            WrappedValue::Columns(ref columns) => {
+                let n_rows = match columns.values().next().map(|v| v.get_shape(true)) {
+                    Some(DShape::Column { n_rows }) => n_rows,
+                    Some(_) => 1,
+                    None => 0,
+                };
+                DShape::Matrix {
+                    n_rows,
+                    n_cols: columns.len(),
+                }
+            }
+        }
+    }
+    /// Whether any cell is written with a marked formula result, to be given its type once
+    /// the workbook is saved
+    pub fn has_marked_cells(&self) -> bool {
+        let is_error = |v: &CalamineData| matches!(v, CalamineData::Error(_));
+        match self {
+            WrappedValue::Any(ValueContainer::Scalar(v)) => is_error(v),
+            WrappedValue::Any(ValueContainer::Array1(arr1)) => arr1.value.iter().any(is_error),
+            WrappedValue::Any(ValueContainer::Array2(arr2)) => arr2.value.iter().any(is_error),
+            WrappedValue::Formula(formula) => formula.result.as_deref().is_some_and(is_marked),
+            WrappedValue::Columns(columns) => columns.values().any(Self::has_marked_cells),
+            _ => false,
         }
     }
 }
@@ -175,15 +231,24 @@ impl WriteToSheet for WrappedValue {
         sheet: &mut rust_xlsxwriter::Worksheet,
         start: (u32, u16),
         is_column: bool,
+        format: &WriteFormat,
     ) -> Result<(), rust_xlsxwriter::XlsxError> {
         match self {
-            WrappedValue::Int(ref v) => v.write_to_sheet(sheet, start, is_column),
-            WrappedValue::Float(ref v) => v.write_to_sheet(sheet, start, is_column),
-            WrappedValue::Str(ref v) => v.write_to_sheet(sheet, start, is_column),
-            WrappedValue::Bool(ref v) => v.write_to_sheet(sheet, start, is_column),
-            WrappedValue::Date(ref v) => v.write_to_sheet(sheet, start, is_column),
-            WrappedValue::DateTime(ref v) => v.write_to_sheet(sheet, start, is_column),
-            WrappedValue::Any(ref v) => v.write_to_sheet(sheet, start, is_column),
+            WrappedValue::Int(ref v) => v.write_to_sheet(sheet, start, is_column, format),
+            WrappedValue::Float(ref v) => v.write_to_sheet(sheet, start, is_column, format),
+            WrappedValue::Str(ref v) => v.write_to_sheet(sheet, start, is_column, format),
+            WrappedValue::Bool(ref v) => v.write_to_sheet(sheet, start, is_column, format),
+            WrappedValue::Date(ref v) => v.write_to_sheet(sheet, start, is_column, format),
+            WrappedValue::DateTime(ref v) => v.write_to_sheet(sheet, start, is_column, format),
+            WrappedValue::Any(ref v) => v.write_to_sheet(sheet, start, is_column, format),
+            WrappedValue::Formula(ref v) => v.write_to_sheet(sheet, start, is_column, format),
+            WrappedValue::Columns(ref columns) => {
+                for (j, column) in columns {
+                    let start = (start.0, start.1 + *j as u16);
+                    column.write_to_sheet(sheet, start, true, format)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,51 @@
+use calamine::CellErrorType;
+use pyo3::prelude::*;
+
+/// Enumeration for the error values of cells, e.g. `#N/A` or `#DIV/0!`.
+#[pyclass(eq, eq_int)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ExcelError {
+    Div0,
+    NA,
+    Name,
+    Null,
+    Num,
+    Ref,
+    Value,
+    GettingData,
+}
+#[pymethods]
+impl ExcelError {
+    /// The error as it is shown in Excel, e.g. "#N/A"
+    fn __str__(&self) -> &'static str {
+        self.as_str()
+    }
+}
+impl ExcelError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExcelError::Div0 => "#DIV/0!",
+            ExcelError::NA => "#N/A",
+            ExcelError::Name => "#NAME?",
+            ExcelError::Null => "#NULL!",
+            ExcelError::Num => "#NUM!",
+            ExcelError::Ref => "#REF!",
+            ExcelError::Value => "#VALUE!",
+            ExcelError::GettingData => "#GETTING_DATA",
+        }
+    }
+}
+impl From<&CellErrorType> for ExcelError {
+    fn from(error: &CellErrorType) -> Self {
+        match error {
+            CellErrorType::Div0 => ExcelError::Div0,
+            CellErrorType::NA => ExcelError::NA,
+            CellErrorType::Name => ExcelError::Name,
+            CellErrorType::Null => ExcelError::Null,
+            CellErrorType::Num => ExcelError::Num,
+            CellErrorType::Ref => ExcelError::Ref,
+            CellErrorType::Value => ExcelError::Value,
+            CellErrorType::GettingData => ExcelError::GettingData,
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use pyo3::prelude::*;
+use pyo3::IntoPyObject;
+
+use super::{bool_result, error_result, CalamineData, WriteFormat, WriteToSheet};
+
+#[derive(Clone, Copy)]
+pub enum FormulaKind {
+    Single,
+    /// Array formula, with the (n_rows, n_cols) of the range it applies to
+    Array((u32, u16)),
+    /// Dynamic array formula, with the (n_rows, n_cols) of the range it spills into
+    DynamicArray((u32, u16)),
+}
+
+#[derive(Clone)]
+pub struct FormulaData {
+    pub formula: String,
+    pub result: Option<String>,
+    pub kind: FormulaKind,
+}
+impl FormulaData {
+    pub fn new(formula: String, value: Option<CalamineData>, kind: FormulaKind) -> Self {
+        Self {
+            formula,
+            result: value.and_then(formula_result),
+            kind,
+        }
+    }
+}
+
+fn excel_serial(datetime: NaiveDateTime) -> f64 {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .unwrap_or_default();
+    (datetime - epoch).num_milliseconds() as f64 / 86_400_000.0
+}
+
+/// Convert a cached value into the string stored as the result of a formula.
+fn formula_result(value: CalamineData) -> Option<String> {
+    match value {
+        CalamineData::Int(v) => Some(v.to_string()),
+        CalamineData::Float(v) => Some(v.to_string()),
+        CalamineData::Str(v) => Some(v),
+        CalamineData::Bool(v) => Some(bool_result(v)),
+        CalamineData::Date(v) => v
+            .and_hms_opt(0, 0, 0)
+            .map(|t| excel_serial(t).to_string()),
+        CalamineData::DateTime(v) => Some(excel_serial(v).to_string()),
+        CalamineData::Error(v) => Some(error_result(&v)),
+        CalamineData::Empty => None,
+    }
+}
+
+impl<'py> IntoPyObject<'py> for FormulaData {
+    type Target = PyAny;
+    type Output = Bound<'py, Self::Target>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(self.formula.into_pyobject(py)?.into_any())
+    }
+}
+
+impl WriteToSheet for FormulaData {
+    fn write_to_sheet(
+        &self,
+        sheet: &mut rust_xlsxwriter::Worksheet,
+        start: (u32, u16),
+        _is_column: bool,
+        format: &WriteFormat,
+    ) -> Result<(), rust_xlsxwriter::XlsxError> {
+        let mut formula = rust_xlsxwriter::Formula::new(&self.formula);
+        if let Some(result) = &self.result {
+            formula = formula.set_result(result);
+        }
+        let last = |shape: (u32, u16)| (start.0 + shape.0 - 1, start.1 + shape.1 - 1);
+        match (self.kind, &format.base) {
+            (FormulaKind::Single, Some(f)) => {
+                sheet.write_formula_with_format(start.0, start.1, formula, f)
+            }
+            (FormulaKind::Single, None) => sheet.write_formula(start.0, start.1, formula),
+            (FormulaKind::Array(shape), Some(f)) => {
+                let last = last(shape);
+                sheet.write_array_formula_with_format(start.0, start.1, last.0, last.1, formula, f)
+            }
+            (FormulaKind::Array(shape), None) => {
+                let last = last(shape);
+                sheet.write_array_formula(start.0, start.1, last.0, last.1, formula)
+            }
+            (FormulaKind::DynamicArray(shape), Some(f)) => {
+                let last = last(shape);
+                sheet.write_dynamic_array_formula_with_format(
+                    start.0, start.1, last.0, last.1, formula, f,
+                )
+            }
+            (FormulaKind::DynamicArray(shape), None) => {
+                let last = last(shape);
+                sheet.write_dynamic_array_formula(start.0, start.1, last.0, last.1, formula)
+            }
+        }
+        .map(|_| ())
+    }
+}
@@ -1,18 +1,34 @@
 use indexmap::IndexMap;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use std::hash::Hash;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::PathBuf;
 mod calamine_data;
 mod cell_addr;
+mod cell_format;
 mod containers;
+mod excel_error;
+mod formula;
+mod table;
+mod typed_cells;
 pub use calamine_data::CalamineData;
-pub use cell_addr::CellAddr;
+pub use cell_addr::{
+    idx_to_addr, idx_to_range, CellAddr, CellRange, RangeAddr, MAX_COLS, MAX_ROWS,
+};
+pub use cell_format::{Format, WriteFormat, DEFAULT_DATETIME_NUM_FORMAT, DEFAULT_DATE_NUM_FORMAT};
 pub use containers::{
     Array1Container, Array2Container, ValueContainer, WrappedValue, WriteToSheet,
 };
+pub use excel_error::ExcelError;
+pub use formula::{FormulaData, FormulaKind};
+pub use table::{TableToWrite, TotalRow};
+pub use typed_cells::{
+    bool_result, error_result, error_value, is_marked, save_to_buffer, save_to_path,
+};
 
 /// Enumeration for data types.
 #[pyclass(eq, eq_int)]
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum DType {
     Int,
     Float,
@@ -25,26 +41,98 @@ pub enum DType {
 
 /// Class to describe the shape of data.
 #[pyclass(eq)]
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub enum DShape {
     Scalar {},
-    Row { n_cols: usize },
-    Column { n_rows: usize },
-    Matrix { n_rows: usize, n_cols: usize },
+    Row {
+        n_cols: usize,
+    },
+    Column {
+        n_rows: usize,
+    },
+    Matrix {
+        n_rows: usize,
+        n_cols: usize,
+    },
+    /// Matrix to the end of the used range of the sheet
+    MatrixToEnd {},
+    /// Row until the first empty cell
+    RowToEmpty {},
+    /// Column until the first empty cell
+    ColumnToEmpty {},
+}
+impl DShape {
+    /// Whether the size is only known after it is resolved against a worksheet
+    pub fn is_open_ended(&self) -> bool {
+        matches!(
+            self,
+            DShape::MatrixToEnd {} | DShape::RowToEmpty {} | DShape::ColumnToEmpty {}
+        )
+    }
+    /// The narrowest shape of a (n_rows, n_cols) range, i.e. a single row is a `Row`.
+    pub fn from_size(n_rows: usize, n_cols: usize) -> PyResult<Self> {
+        match (n_rows, n_cols) {
+            (0, _) | (_, 0) => Err(PyValueError::new_err(
+                "The shape of a range must be at least 1x1",
+            )),
+            (1, 1) => Ok(DShape::Scalar {}),
+            (1, n_cols) => Ok(DShape::Row { n_cols }),
+            (n_rows, 1) => Ok(DShape::Column { n_rows }),
+            (n_rows, n_cols) => Ok(DShape::Matrix { n_rows, n_cols }),
+        }
+    }
+    fn repr(&self) -> String {
+        match self {
+            DShape::Scalar {} => "DShape.Scalar()".to_string(),
+            DShape::Row { n_cols } => format!("DShape.Row(n_cols={n_cols})"),
+            DShape::Column { n_rows } => format!("DShape.Column(n_rows={n_rows})"),
+            DShape::Matrix { n_rows, n_cols } => {
+                format!("DShape.Matrix(n_rows={n_rows}, n_cols={n_cols})")
+            }
+            DShape::MatrixToEnd {} => "DShape.MatrixToEnd()".to_string(),
+            DShape::RowToEmpty {} => "DShape.RowToEmpty()".to_string(),
+            DShape::ColumnToEmpty {} => "DShape.ColumnToEmpty()".to_string(),
+        }
+    }
+}
+
+/// The data type of a range, or of each column of a range.
+#[derive(Clone, PartialEq, Eq, Hash, FromPyObject, IntoPyObject)]
+pub enum RangeDTypes {
+    One(DType),
+    Columns(Vec<DType>),
+}
+impl RangeDTypes {
+    fn repr(&self) -> String {
+        match self {
+            RangeDTypes::One(dtype) => format!("DType.{dtype:?}"),
+            RangeDTypes::Columns(dtypes) => {
+                let dtypes = dtypes.iter().map(|dtype| format!("DType.{dtype:?}"));
+                format!("[{}]", dtypes.collect::<Vec<_>>().join(", "))
+            }
+        }
+    }
 }
 
 /// Class to describe the range of data.
 #[pyclass]
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct RangeInfo {
     #[pyo3(get, set)]
     pub pos: (i32, i32),
     #[pyo3(get, set)]
     pub data_shape: DShape,
+    /// One data type for the whole range, or a list of one per column
     #[pyo3(get, set)]
-    pub dtype: DType,
+    pub dtype: RangeDTypes,
     #[pyo3(get, set)]
     pub strict: bool,
+    /// The defined name to read instead of `pos` and `data_shape`
+    #[pyo3(get)]
+    pub name: Option<String>,
+    /// Whether to read the formulas instead of the values
+    #[pyo3(get, set)]
+    pub formulas: bool,
 }
 #[pymethods]
 impl RangeInfo {
@@ -58,30 +146,69 @@ impl RangeInfo {
             Negative refer counted from the end of the sheet.
         data_shape : DShape
             The shape of the data in the range.
-        dtype : DType, default DType.Any
-            The data type of the range.
+        dtype : DType | List[DType], default DType.Any
+            The data type of the range, or a list of one data type per column to read the
+            range as a dict of columns by their 0-based index in the range.
         strict : bool, default True
             Whether to enforce strict type checking. If True, raise error when
             the `dtype` does not match, else use default value.
+        formulas : bool, default False
+            Whether to read the formulas of the range instead of the values, as strings starting
+            with "=" or None for cells without a formula. `dtype` and `strict` are ignored, and
+            the worksheet should be got with `formulas=True`.
     */
     #[new]
-    #[pyo3(signature = (pos, data_shape = DShape::Scalar{}, *, dtype = DType::Any, strict = true))]
-    pub fn new(pos: (i32, i32), data_shape: DShape, dtype: DType, strict: bool) -> Self {
+    #[pyo3(signature = (pos, data_shape = DShape::Scalar{}, *, dtype = RangeDTypes::One(DType::Any), strict = true, formulas = false))]
+    pub fn py_new(
+        pos: (i32, i32),
+        data_shape: DShape,
+        dtype: RangeDTypes,
+        strict: bool,
+        formulas: bool,
+    ) -> Self {
         Self {
-            pos,
-            data_shape,
             dtype,
-            strict,
+            formulas,
+            ..Self::new(pos, data_shape, DType::Any, strict)
+        }
+    }
+    /**
+        Generate a RangeInfo object referring to a defined name (named range) of the workbook.
+        The position and shape are resolved when it is read.
+
+        Parameters
+        ----------
+        name : str
+            The defined name, case-insensitive.
+        dtype : DType | List[DType], default DType.Any
+            The data type of the range, or a list of one data type per column.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+    */
+    #[staticmethod]
+    #[pyo3(signature = (name, *, dtype = RangeDTypes::One(DType::Any), strict = true))]
+    pub fn from_name(name: String, dtype: RangeDTypes, strict: bool) -> Self {
+        Self {
+            name: Some(name),
+            dtype,
+            ..Self::new((0, 0), DShape::Scalar {}, DType::Any, strict)
         }
     }
-    /// The shape of the range as (n_rows, n_cols)
+    /// The shape of the range as (n_rows, n_cols), unknown for open-ended shapes
     #[getter]
-    pub fn shape(&self) -> (usize, usize) {
+    pub fn shape(&self) -> PyResult<(usize, usize)> {
+        self.check_not_named()?;
         match self.data_shape {
-            DShape::Scalar {} => (1, 1),
-            DShape::Row { n_cols } => (1, n_cols),
-            DShape::Column { n_rows } => (n_rows, 1),
-            DShape::Matrix { n_rows, n_cols } => (n_rows, n_cols),
+            DShape::Scalar {} => Ok((1, 1)),
+            DShape::Row { n_cols } => Ok((1, n_cols)),
+            DShape::Column { n_rows } => Ok((n_rows, 1)),
+            DShape::Matrix { n_rows, n_cols } => Ok((n_rows, n_cols)),
+            DShape::MatrixToEnd {} | DShape::RowToEmpty {} | DShape::ColumnToEmpty {} => {
+                Err(PyValueError::new_err(
+                    "The shape of an open-ended range is only known when it is read",
+                ))
+            }
         }
     }
     /// The 0-based starting position of the range as (row, col)
@@ -89,14 +216,274 @@ impl RangeInfo {
     pub fn start(&self) -> (i32, i32) {
         self.pos
     }
-    /// The 0-based ending position of the range as (row, col)
+    /// The 0-based ending position of the range as (row, col), unknown for open-ended shapes
     #[getter]
-    pub fn end(&self) -> (i32, i32) {
-        let (n_rows, n_cols) = self.shape();
-        (
+    pub fn end(&self) -> PyResult<(i32, i32)> {
+        let (n_rows, n_cols) = self.shape()?;
+        Ok((
             self.pos.0 + n_rows as i32 - 1,
             self.pos.1 + n_cols as i32 - 1,
-        )
+        ))
+    }
+    /**
+        Generate a RangeInfo object from a cell or range address, the shape is inferred from
+        the size of the range.
+
+        Parameters
+        ----------
+        addr : str
+            The cell or range address, e.g. "B2" for `DShape.Scalar`, "B2:D2" for `DShape.Row`,
+            "B2:B10" for `DShape.Column` and "B2:D10" for `DShape.Matrix`. Whole columns or
+            rows (e.g., "A:C") span to the limits of the worksheet.
+        dtype : DType | List[DType], default DType.Any
+            The data type of the range, or a list of one data type per column.
+        strict : bool, default True
+            Whether to enforce strict type checking. If True, raise error when
+            the `dtype` does not match, else use default value.
+    */
+    #[staticmethod]
+    #[pyo3(signature = (addr, *, dtype = RangeDTypes::One(DType::Any), strict = true))]
+    pub fn from_addr(addr: &str, dtype: RangeDTypes, strict: bool) -> PyResult<Self> {
+        let range = CellRange::parse(addr)?;
+        Self::from_bounds(range.first, range.last, dtype, strict)
+    }
+    /**
+        Convert the range to an address string, e.g. "B2:D10".
+
+        Returns
+        -------
+        str
+    */
+    pub fn to_addr(&self) -> PyResult<String> {
+        let (first, last) = self.bounds()?;
+        Ok(idx_to_range(first, last))
+    }
+    /**
+        Move the range by `(n_rows, n_cols)`, the shape is kept. A position counted from the
+        end of the sheet stays counted from the end, and moving the range out of the worksheet
+        raises `ValueError`.
+
+        Parameters
+        ----------
+        n_rows : int
+            The number of rows to move down, negative to move up.
+        n_cols : int
+            The number of columns to move right, negative to move left.
+
+        Returns
+        -------
+        RangeInfo
+    */
+    pub fn offset(&self, n_rows: i32, n_cols: i32) -> PyResult<Self> {
+        self.check_not_named()?;
+        // The size of open-ended ranges is only known when they are read
+        let (height, width) = self.shape().unwrap_or((1, 1));
+        // Moving past the edge of the sheet would silently switch between positions counted
+        // from the start and from the end
+        let moved = |pos: i32, n: i32, size: usize, max: usize| {
+            let (moved, size, max) = (i64::from(pos) + i64::from(n), size as i64, max as i64);
+            let in_sheet = match pos < 0 {
+                true => -max <= moved && moved < 0,
+                false => moved >= 0 && moved + size <= max,
+            };
+            match in_sheet {
+                true => Ok(moved as i32),
+                false => Err(PyValueError::new_err(format!(
+                    "Moving the range by ({n_rows}, {n_cols}) puts it out of the worksheet, \
+                     which has {MAX_ROWS} rows and {MAX_COLS} columns"
+                ))),
+            }
+        };
+        Ok(Self {
+            pos: (
+                moved(self.pos.0, n_rows, height, MAX_ROWS)?,
+                moved(self.pos.1, n_cols, width, MAX_COLS)?,
+            ),
+            ..self.clone()
+        })
+    }
+    /**
+        Change the size of the range, the starting position is kept and the shape is inferred
+        from the new size.
+
+        Parameters
+        ----------
+        n_rows : int
+            The new number of rows.
+        n_cols : int
+            The new number of columns.
+
+        Returns
+        -------
+        RangeInfo
+    */
+    pub fn resize(&self, n_rows: usize, n_cols: usize) -> PyResult<Self> {
+        self.check_not_named()?;
+        Ok(Self {
+            data_shape: DShape::from_size(n_rows, n_cols)?,
+            ..self.clone()
+        })
+    }
+    /**
+        Get the overlapping part of two ranges, `dtype`, `strict` and `formulas` are taken
+        from this range.
+
+        Parameters
+        ----------
+        other : RangeInfo
+
+        Returns
+        -------
+        Optional[RangeInfo]
+            The intersection, None if the ranges do not overlap.
+    */
+    pub fn intersect(&self, other: &RangeInfo) -> PyResult<Option<Self>> {
+        let ((a_first, a_last), (b_first, b_last)) = (self.bounds()?, other.bounds()?);
+        let first = (a_first.0.max(b_first.0), a_first.1.max(b_first.1));
+        let last = (a_last.0.min(b_last.0), a_last.1.min(b_last.1));
+        if first.0 > last.0 || first.1 > last.1 {
+            return Ok(None);
+        }
+        self.with_bounds(first, last).map(Some)
+    }
+    /**
+        Get the smallest range containing both ranges, `dtype`, `strict` and `formulas` are
+        taken from this range.
+
+        Parameters
+        ----------
+        other : RangeInfo
+
+        Returns
+        -------
+        RangeInfo
+    */
+    pub fn union_bounds(&self, other: &RangeInfo) -> PyResult<Self> {
+        let ((a_first, a_last), (b_first, b_last)) = (self.bounds()?, other.bounds()?);
+        let first = (a_first.0.min(b_first.0), a_first.1.min(b_first.1));
+        let last = (a_last.0.max(b_last.0), a_last.1.max(b_last.1));
+        self.with_bounds(first, last)
+    }
+    /**
+        Whether a cell is inside the range.
+
+        Parameters
+        ----------
+        cell_addr : Union[Tuple[int, int], str]
+            The cell address, either as a tuple of (row, col) or a string (e.g., "A1").
+
+        Returns
+        -------
+        bool
+    */
+    pub fn contains(&self, cell_addr: CellAddr) -> PyResult<bool> {
+        let (row, col) = cell_addr.as_idx()?;
+        let (first, last) = self.bounds()?;
+        Ok((first.0..=last.0).contains(&row) && (first.1..=last.1).contains(&col))
+    }
+    fn __eq__(&self, other: &RangeInfo) -> bool {
+        self == other
+    }
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+    fn __repr__(&self) -> String {
+        let strict = if self.strict { "True" } else { "False" };
+        let formulas = if self.formulas { ", formulas=True" } else { "" };
+        match &self.name {
+            Some(name) => format!(
+                "RangeInfo.from_name(\"{}\", dtype={}, strict={}{})",
+                name,
+                self.dtype.repr(),
+                strict,
+                formulas
+            ),
+            None => format!(
+                "RangeInfo({:?}, {}, dtype={}, strict={}{})",
+                self.pos,
+                self.data_shape.repr(),
+                self.dtype.repr(),
+                strict,
+                formulas
+            ),
+        }
+    }
+}
+impl RangeInfo {
+    pub fn new(pos: (i32, i32), data_shape: DShape, dtype: DType, strict: bool) -> Self {
+        Self {
+            pos,
+            data_shape,
+            dtype: RangeDTypes::One(dtype),
+            strict,
+            name: None,
+            formulas: false,
+        }
+    }
+    pub fn from_bounds(
+        first: (usize, usize),
+        last: (usize, usize),
+        dtype: RangeDTypes,
+        strict: bool,
+    ) -> PyResult<Self> {
+        let data_shape = DShape::from_size(last.0 - first.0 + 1, last.1 - first.1 + 1)?;
+        Ok(Self {
+            dtype,
+            ..Self::new(
+                (first.0 as i32, first.1 as i32),
+                data_shape,
+                DType::Any,
+                strict,
+            )
+        })
+    }
+    /// A range over the bounds with the options of this range
+    pub fn with_bounds(&self, first: (usize, usize), last: (usize, usize)) -> PyResult<Self> {
+        Ok(Self {
+            formulas: self.formulas,
+            ..Self::from_bounds(first, last, self.dtype.clone(), self.strict)?
+        })
+    }
+    fn check_not_named(&self) -> PyResult<()> {
+        match &self.name {
+            Some(name) => Err(PyValueError::new_err(format!(
+                "The range of defined name \"{name}\" is only known when it is read"
+            ))),
+            None => Ok(()),
+        }
+    }
+    /// The 0-based (row, col) of the first and last cells, only known for ranges with a
+    /// non-negative position and a definite shape
+    pub fn bounds(&self) -> PyResult<((usize, usize), (usize, usize))> {
+        if self.pos.0 < 0 || self.pos.1 < 0 {
+            return Err(PyValueError::new_err(
+                "The bounds of a range counted from the end of the sheet are only known when it is read",
+            ));
+        }
+        let end = self.end()?;
+        Ok((
+            (self.pos.0 as usize, self.pos.1 as usize),
+            (end.0 as usize, end.1 as usize),
+        ))
+    }
+}
+
+/// A range, or the address of a range, e.g. "A1:D10".
+#[derive(Clone, FromPyObject)]
+pub enum RangeOrAddr {
+    Range(RangeInfo),
+    Addr(String),
+}
+impl RangeOrAddr {
+    pub fn range_info(&self) -> PyResult<RangeInfo> {
+        match self {
+            RangeOrAddr::Range(range_info) => Ok(range_info.clone()),
+            RangeOrAddr::Addr(addr) => {
+                RangeInfo::from_addr(addr, RangeDTypes::One(DType::Any), true)
+            }
+        }
     }
 }
 
@@ -106,8 +493,130 @@ pub enum IdxOrName {
     Name(String),
 }
 
+/// The data types of columns: one for all, a list by position or a dict by header.
+#[derive(Clone, FromPyObject)]
+pub enum ColumnDTypes {
+    One(DType),
+    List(Vec<DType>),
+    Dict(IndexMap<String, DType>),
+}
+impl ColumnDTypes {
+    /// Resolve the data type of each column, missing headers in a dict are read as `DType.Any`
+    pub fn resolve(&self, headers: &[String]) -> PyResult<Vec<DType>> {
+        self.resolve_with(headers, |_| DType::Any)
+    }
+    /// Resolve the data type of each column, with `missing` giving the data type of the j-th
+    /// column if its header is missing in a dict
+    pub fn resolve_with(
+        &self,
+        headers: &[String],
+        missing: impl Fn(usize) -> DType,
+    ) -> PyResult<Vec<DType>> {
+        match self {
+            ColumnDTypes::One(dtype) => Ok(vec![*dtype; headers.len()]),
+            ColumnDTypes::List(dtypes) => {
+                if dtypes.len() != headers.len() {
+                    return Err(PyValueError::new_err(format!(
+                        "Expected {} dtypes, got {}",
+                        headers.len(),
+                        dtypes.len()
+                    )));
+                }
+                Ok(dtypes.clone())
+            }
+            ColumnDTypes::Dict(dtypes) => {
+                if let Some(unknown) = dtypes.keys().find(|k| !headers.contains(k)) {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown column: \"{unknown}\""
+                    )));
+                }
+                Ok(headers
+                    .iter()
+                    .enumerate()
+                    .map(|(j, h)| dtypes.get(h).copied().unwrap_or_else(|| missing(j)))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// The formats of columns: one for all or a dict by header.
+#[derive(Clone, FromPyObject)]
+pub enum ColumnFormats {
+    One(Format),
+    Dict(IndexMap<String, Format>),
+}
+impl ColumnFormats {
+    /// Resolve the format of each column, columns missing in a dict are not formatted
+    pub fn resolve(self, headers: &[String]) -> PyResult<Vec<Option<Format>>> {
+        match self {
+            ColumnFormats::One(format) => Ok(vec![Some(format); headers.len()]),
+            ColumnFormats::Dict(mut formats) => {
+                if let Some(unknown) = formats.keys().find(|k| !headers.contains(k)) {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown column: \"{unknown}\""
+                    )));
+                }
+                Ok(headers.iter().map(|h| formats.swap_remove(h)).collect())
+            }
+        }
+    }
+}
+
+/// A file path, or a Python file-like object with a `write` method.
+#[derive(FromPyObject)]
+pub enum PathOrWriter {
+    Path(PathBuf),
+    Writer(PyObject),
+}
+
 #[derive(Clone, FromPyObject, IntoPyObject)]
 pub enum ListOrDict<K: PartialEq + Eq + Hash, T> {
     List(Vec<T>),
     Dict(IndexMap<K, T>),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(addr: &str) -> RangeInfo {
+        RangeInfo::from_addr(addr, RangeDTypes::One(DType::Any), true).unwrap()
+    }
+
+    fn moved(addr: &str, n_rows: i32, n_cols: i32) -> String {
+        let range = range(addr).offset(n_rows, n_cols).unwrap();
+        range.to_addr().unwrap()
+    }
+
+    #[test]
+    fn offset() {
+        assert_eq!(moved("B2:C3", 2, -1), "A4:B5");
+        // The last row and column of the worksheet
+        let last = moved("A1", MAX_ROWS as i32 - 1, MAX_COLS as i32 - 1);
+        assert_eq!(last, "XFD1048576");
+        // Positions counted from the end stay so
+        let from_end = RangeInfo::new((-3, -2), DShape::MatrixToEnd {}, DType::Any, true);
+        assert_eq!(from_end.offset(1, -1).unwrap().pos, (-2, -3));
+    }
+
+    #[test]
+    fn offset_out_of_sheet() {
+        // Before the first row or column, rather than counted from the end
+        assert!(range("B2").offset(-2, 0).is_err());
+        assert!(range("B2").offset(0, -2).is_err());
+        // Past the last row or column, including the end of the range
+        assert!(range("A1").offset(MAX_ROWS as i32, 0).is_err());
+        assert!(range("A1:A2").offset(MAX_ROWS as i32 - 1, 0).is_err());
+        assert!(range("A1:B1").offset(0, MAX_COLS as i32 - 1).is_err());
+        // Overflowing i32
+        let far = RangeInfo::new((i32::MAX - 1, 0), DShape::Scalar {}, DType::Any, true);
+        assert!(far.offset(i32::MAX, 0).is_err());
+        let far = RangeInfo::new((i32::MIN + 1, 0), DShape::Scalar {}, DType::Any, true);
+        assert!(far.offset(i32::MIN, 0).is_err());
+        // Counted from the end, past the start of the sheet or back to counted from the start
+        let from_end = RangeInfo::new((-1, -1), DShape::Scalar {}, DType::Any, true);
+        assert!(from_end.offset(1, 0).is_err());
+        assert!(from_end.offset(-(MAX_ROWS as i32), 0).is_err());
+    }
+}
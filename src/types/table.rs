@@ -0,0 +1,156 @@
+use indexmap::IndexMap;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rust_xlsxwriter::{Table, TableColumn, TableFunction, TableStyle};
+
+const TABLE_STYLES: [TableStyle; 61] = [
+    TableStyle::None,
+    TableStyle::Light1,
+    TableStyle::Light2,
+    TableStyle::Light3,
+    TableStyle::Light4,
+    TableStyle::Light5,
+    TableStyle::Light6,
+    TableStyle::Light7,
+    TableStyle::Light8,
+    TableStyle::Light9,
+    TableStyle::Light10,
+    TableStyle::Light11,
+    TableStyle::Light12,
+    TableStyle::Light13,
+    TableStyle::Light14,
+    TableStyle::Light15,
+    TableStyle::Light16,
+    TableStyle::Light17,
+    TableStyle::Light18,
+    TableStyle::Light19,
+    TableStyle::Light20,
+    TableStyle::Light21,
+    TableStyle::Medium1,
+    TableStyle::Medium2,
+    TableStyle::Medium3,
+    TableStyle::Medium4,
+    TableStyle::Medium5,
+    TableStyle::Medium6,
+    TableStyle::Medium7,
+    TableStyle::Medium8,
+    TableStyle::Medium9,
+    TableStyle::Medium10,
+    TableStyle::Medium11,
+    TableStyle::Medium12,
+    TableStyle::Medium13,
+    TableStyle::Medium14,
+    TableStyle::Medium15,
+    TableStyle::Medium16,
+    TableStyle::Medium17,
+    TableStyle::Medium18,
+    TableStyle::Medium19,
+    TableStyle::Medium20,
+    TableStyle::Medium21,
+    TableStyle::Medium22,
+    TableStyle::Medium23,
+    TableStyle::Medium24,
+    TableStyle::Medium25,
+    TableStyle::Medium26,
+    TableStyle::Medium27,
+    TableStyle::Medium28,
+    TableStyle::Dark1,
+    TableStyle::Dark2,
+    TableStyle::Dark3,
+    TableStyle::Dark4,
+    TableStyle::Dark5,
+    TableStyle::Dark6,
+    TableStyle::Dark7,
+    TableStyle::Dark8,
+    TableStyle::Dark9,
+    TableStyle::Dark10,
+    TableStyle::Dark11,
+];
+
+/// Parse a table style like "TableStyleMedium9", the "TableStyle" prefix could be omitted.
+fn parse_table_style(style: &str) -> PyResult<TableStyle> {
+    let lower = style.to_lowercase();
+    let name = lower.strip_prefix("tablestyle").unwrap_or(&lower);
+    TABLE_STYLES
+        .into_iter()
+        .find(|s| s.to_string().to_lowercase() == format!("tablestyle{name}"))
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid table style: {style}")))
+}
+
+/// Parse a total function name, None if it is not a function, i.e. a label.
+fn parse_total_function(function: &str) -> Option<TableFunction> {
+    match function {
+        "average" => Some(TableFunction::Average),
+        "count" => Some(TableFunction::Count),
+        "count_numbers" => Some(TableFunction::CountNumbers),
+        "max" => Some(TableFunction::Max),
+        "min" => Some(TableFunction::Min),
+        "sum" => Some(TableFunction::Sum),
+        "std_dev" => Some(TableFunction::StdDev),
+        "var" => Some(TableFunction::Var),
+        _ => None,
+    }
+}
+
+/// Whether to add a total row, or the function or label of each column in the total row.
+#[derive(Clone, FromPyObject)]
+pub enum TotalRow {
+    Enabled(bool),
+    Columns(IndexMap<String, String>),
+}
+
+/// A table to add to a worksheet over `(first_row, first_col, last_row, last_col)`.
+#[derive(Clone)]
+pub struct TableToWrite {
+    pub range: (u32, u16, u32, u16),
+    pub table: Table,
+}
+impl TableToWrite {
+    pub fn new(
+        first: (u32, u16),
+        headers: &[String],
+        n_rows: u32,
+        name: Option<String>,
+        style: Option<String>,
+        total_row: TotalRow,
+    ) -> PyResult<Self> {
+        let (show_total_row, totals) = match total_row {
+            TotalRow::Enabled(enabled) => (enabled, IndexMap::new()),
+            TotalRow::Columns(totals) => (true, totals),
+        };
+        if let Some(unknown) = totals.keys().find(|k| !headers.contains(k)) {
+            return Err(PyValueError::new_err(format!(
+                "Unknown column: \"{unknown}\""
+            )));
+        }
+        let columns = headers
+            .iter()
+            .map(|header| {
+                let column = TableColumn::new().set_header(header);
+                match totals.get(header) {
+                    Some(total) => match parse_total_function(total) {
+                        Some(function) => column.set_total_function(function),
+                        None => column.set_total_label(total),
+                    },
+                    None => column,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut table = Table::new()
+            .set_columns(&columns)
+            .set_total_row(show_total_row);
+        if let Some(name) = name {
+            table = table.set_name(name);
+        }
+        if let Some(style) = style {
+            table = table.set_style(parse_table_style(&style)?);
+        }
+        // Like Excel, keep an empty data row if there is no data
+        let last_row = first.0 + n_rows.max(1) + u32::from(show_total_row);
+        let last_col = first.1 + headers.len() as u16 - 1;
+        Ok(Self {
+            range: (first.0, first.1, last_row, last_col),
+            table,
+        })
+    }
+}
@@ -0,0 +1,123 @@
+use rust_xlsxwriter::{Workbook, XlsxError};
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use super::ExcelError;
+
+/// Starts the cached results of formulas written for cells of a type rust_xlsxwriter could not
+/// write, i.e. error cells and boolean or error results. rust_xlsxwriter stores any result which is not a number as a
+/// string, so these results are marked and the cells are given their types once the workbook
+/// is saved. The character is in the private use area, which Excel never writes.
+const MARK: char = '\u{F8FF}';
+/// The kinds of marked results, written after the mark
+const ERROR_VALUE: char = 'E';
+const ERROR_RESULT: char = 'e';
+const BOOL_RESULT: char = 'b';
+
+/// The result of the formula written for an error cell, which is written without the formula
+pub fn error_value(error: &ExcelError) -> String {
+    format!("{MARK}{ERROR_VALUE}{}", error.as_str())
+}
+/// The result of a formula evaluated to an error
+pub fn error_result(error: &ExcelError) -> String {
+    format!("{MARK}{ERROR_RESULT}{}", error.as_str())
+}
+/// The result of a formula evaluated to a boolean, stored as 1 or 0
+pub fn bool_result(value: bool) -> String {
+    format!("{MARK}{BOOL_RESULT}{}", u8::from(value))
+}
+/// Whether a formula result is marked to be given its type on save
+pub fn is_marked(result: &str) -> bool {
+    result.starts_with(MARK)
+}
+
+/// Give the marked cells of a worksheet their types, e.g.
+/// `<c r="A1" t="str"><f>#N/A</f><v>{MARK}E#N/A</v></c>` becomes
+/// `<c r="A1" t="e"><v>#N/A</v></c>`.
+fn retype_sheet(xml: &str) -> String {
+    let value_start = format!("<v>{MARK}");
+    let mut retyped = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(i) = rest.find(&value_start) {
+        let cell = rest[..i].rfind("<c ");
+        let tag_end = cell.and_then(|c| rest[c..i].find('>').map(|end| c + end + 1));
+        let value_end = rest[i..].find("</v>").map(|end| i + end);
+        let (Some(cell), Some(tag_end), Some(value_end)) = (cell, tag_end, value_end) else {
+            break;
+        };
+        let mut value = rest[i + value_start.len()..value_end].chars();
+        let kind = value.next();
+        let value = value.as_str();
+        let cell_type = match kind {
+            Some(ERROR_VALUE | ERROR_RESULT) => "e",
+            Some(BOOL_RESULT) => "b",
+            _ => {
+                // Not marked by fastxlsx, keep it as it is
+                retyped.push_str(&rest[..value_end]);
+                rest = &rest[value_end..];
+                continue;
+            }
+        };
+        let tag = rest[cell..tag_end].replace(" t=\"str\"", &format!(" t=\"{cell_type}\""));
+        retyped.push_str(&rest[..cell]);
+        retyped.push_str(&tag);
+        if kind != Some(ERROR_VALUE) {
+            // The formula
+            retyped.push_str(&rest[tag_end..i]);
+        }
+        retyped.push_str("<v>");
+        retyped.push_str(value);
+        rest = &rest[value_end..];
+    }
+    retyped.push_str(rest);
+    retyped
+}
+
+/// Give the marked cells of a saved workbook their types, the files other than worksheets are
+/// copied without being decompressed.
+fn retype_cells(buffer: Vec<u8>) -> Result<Vec<u8>, XlsxError> {
+    let mut archive = ZipArchive::new(Cursor::new(buffer))?;
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        if !file.name().starts_with("xl/worksheets/sheet") {
+            writer.raw_copy_file(file)?;
+            continue;
+        }
+        drop(file);
+        let mut file = archive.by_index(i)?;
+        let mut xml = String::new();
+        file.read_to_string(&mut xml)?;
+        let xml = retype_sheet(&xml);
+        let options = SimpleFileOptions::default()
+            .compression_method(file.compression())
+            .last_modified_time(file.last_modified().unwrap_or_default())
+            .large_file(xml.len() >= u32::MAX as usize);
+        writer.start_file(file.name(), options)?;
+        writer.write_all(xml.as_bytes())?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+/// Save a workbook into memory, giving its marked cells their types if it has any
+pub fn save_to_buffer(workbook: &mut Workbook, marked: bool) -> Result<Vec<u8>, XlsxError> {
+    let buffer = workbook.save_to_buffer()?;
+    match marked {
+        true => retype_cells(buffer),
+        false => Ok(buffer),
+    }
+}
+
+/// Save a workbook to a file, through memory if it has marked cells to give their types
+pub fn save_to_path(
+    workbook: &mut Workbook,
+    marked: bool,
+    path: impl AsRef<Path>,
+) -> Result<(), XlsxError> {
+    match marked {
+        true => Ok(std::fs::write(path, save_to_buffer(workbook, true)?)?),
+        false => workbook.save(path),
+    }
+}
@@ -1,8 +1,28 @@
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+
 pub fn adjust_idx(i: i32, n: usize) -> usize {
     if i < 0 {
-        return (n as i32 + i) as usize;
+        (n as i32 + i) as usize
     } else {
-        return i as usize;
+        i as usize
+    }
+}
+
+/// Copy the content of a bytes-like object, or read all from a file-like object, whose `read`
+/// must return a bytes-like object.
+pub fn extract_bytes(data: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    let (data, source) = match data.hasattr("read")? {
+        true => (data.call_method0("read")?, "read() of a file-like object"),
+        false => (data.clone(), "the workbook content"),
+    };
+    match PyBuffer::<u8>::get(&data) {
+        Ok(buffer) => buffer.to_vec(data.py()),
+        Err(_) => Err(PyTypeError::new_err(format!(
+            "Expected bytes, bytearray or a buffer of bytes from {source}, got {}",
+            data.get_type().name()?
+        ))),
     }
 }
 
@@ -73,3 +93,41 @@ define_extract_macro!(
     Array2,
     extract_array2
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::ffi::c_str;
+    use pyo3::types::PyModule;
+
+    #[test]
+    fn extract_bytes_of_objects() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::from_code(
+                py,
+                c_str!(
+                    "import io
+class SelfReader:
+    def read(self):
+        return self
+bytes_io = io.BytesIO(b'abc')
+string_io = io.StringIO('abc')
+self_reader = SelfReader()
+"
+                ),
+                c_str!("objects.py"),
+                c_str!("objects"),
+            )
+            .unwrap();
+            let extract = |name: &str| extract_bytes(&module.getattr(name).unwrap());
+            let data = pyo3::types::PyBytes::new(py, b"abc");
+            assert_eq!(extract_bytes(data.as_any()).unwrap(), b"abc");
+            assert_eq!(extract("bytes_io").unwrap(), b"abc");
+            // `read` is called once, its result must hold bytes
+            for name in ["string_io", "self_reader"] {
+                assert!(extract(name).unwrap_err().is_instance_of::<PyTypeError>(py));
+            }
+        });
+    }
+}
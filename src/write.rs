@@ -1,16 +1,96 @@
 use std::collections::HashSet;
+use std::sync::{Arc, Mutex, MutexGuard};
 
+use crate::dataframe::frame_to_write;
 use crate::types::{
-    Array1Container, Array2Container, CalamineData, DType, ValueContainer, WrappedValue,
-    WriteToSheet,
+    Array1Container, Array2Container, CalamineData, DType, Format, ValueContainer, WrappedValue,
+    WriteFormat, WriteToSheet, DEFAULT_DATETIME_NUM_FORMAT, DEFAULT_DATE_NUM_FORMAT,
+};
+use crate::types::{
+    save_to_buffer, save_to_path, CellAddr, ColumnDTypes, ColumnFormats, DShape, FormulaData,
+    FormulaKind, IdxOrName, PathOrWriter, RangeAddr, TableToWrite, TotalRow,
 };
-use crate::types::{CellAddr, DShape, IdxOrName};
 use chrono::{NaiveDate, NaiveDateTime};
 use indexmap::IndexMap;
+use ndarray::Array1;
 use pyo3::exceptions::{PyFileExistsError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::PyBytes;
 use rayon::prelude::*;
-use rust_xlsxwriter::{Workbook, Worksheet};
+use rust_xlsxwriter::{Workbook, Worksheet, XlsxError};
+
+/// A pending write: (start position, value, whether to write 1d-array as a column, format)
+pub type DataToWrite = ((u32, u16), WrappedValue, bool, Option<Format>);
+
+/// A `rust_xlsxwriter::Workbook` shared by a `WriteOnlyWorkbook` and its constant memory
+/// worksheets, which write into it directly instead of buffering their data.
+pub struct SharedWorkbook {
+    pub workbook: Workbook,
+    pub saved: bool,
+    /// Whether the buffered worksheets and defined names have been written into `workbook`,
+    /// which is kept when saving fails so that it could be saved again
+    pub written: bool,
+    /// Whether cells with marked formula results have been written into `workbook`
+    pub marked: bool,
+}
+fn lock_shared(shared: &Mutex<SharedWorkbook>) -> PyResult<MutexGuard<'_, SharedWorkbook>> {
+    shared
+        .lock()
+        .map_err(|_| PyValueError::new_err("Workbook is poisoned by a previous error"))
+}
+
+/// A constant memory worksheet, rows are flushed to a temporary file as soon as a later row
+/// is written, so they could not be written again.
+#[derive(Clone)]
+pub struct SheetStream {
+    pub shared: Arc<Mutex<SharedWorkbook>>,
+    pub idx: usize,
+    /// The last row written, rows before it have been flushed to disk
+    pub current_row: u32,
+    pub date_format: String,
+    pub datetime_format: String,
+}
+impl SheetStream {
+    pub fn write(
+        &mut self,
+        pos: (u32, u16),
+        value: &WrappedValue,
+        is_column: bool,
+        format: Option<&Format>,
+    ) -> PyResult<()> {
+        if pos.0 < self.current_row {
+            return Err(PyValueError::new_err(format!(
+                "Out-of-order write to row {} of a constant memory worksheet: rows before {} \
+                 have already been flushed to disk",
+                pos.0 + 1,
+                self.current_row + 1
+            )));
+        }
+        let n_rows = match value.get_shape(is_column) {
+            DShape::Column { n_rows } | DShape::Matrix { n_rows, .. } => n_rows,
+            _ => 1,
+        };
+        let mut shared = lock_shared(&self.shared)?;
+        if shared.saved {
+            return Err(PyValueError::new_err(
+                "Could not write to a constant memory worksheet after its workbook is saved",
+            ));
+        }
+        let sheet = shared
+            .workbook
+            .worksheet_from_index(self.idx)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let format = WriteFormat::new(format, &self.date_format, &self.datetime_format);
+        value
+            .write_to_sheet(sheet, pos, is_column, &format)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        shared.marked |= value.has_marked_cells();
+        self.current_row = self
+            .current_row
+            .max(pos.0 + (n_rows.max(1) - 1) as u32);
+        Ok(())
+    }
+}
 
 /// Write-only worksheet class
 #[pyclass]
@@ -18,7 +98,9 @@ use rust_xlsxwriter::{Workbook, Worksheet};
 pub struct WriteOnlyWorksheet {
     #[pyo3(get, set)]
     pub title: String,
-    pub data_to_write: Vec<((u32, u16), WrappedValue, bool)>,
+    pub data_to_write: Vec<DataToWrite>,
+    pub tables: Vec<TableToWrite>,
+    pub stream: Option<SheetStream>,
 }
 impl WriteOnlyWorksheet {
     pub fn write_to_self(
@@ -27,25 +109,74 @@ impl WriteOnlyWorksheet {
         col: usize,
         value: WrappedValue,
         is_column: bool,
+        format: Option<Format>,
     ) -> PyResult<()> {
         let row_u32 =
             u32::try_from(row).map_err(|_| PyValueError::new_err("Row index out of range"))?;
         let col_u16 =
             u16::try_from(col).map_err(|_| PyValueError::new_err("Column index out of range"))?;
+        match self.stream {
+            Some(ref mut stream) => {
+                stream.write((row_u32, col_u16), &value, is_column, format.as_ref())
+            }
+            None => {
+                self.data_to_write
+                    .push(((row_u32, col_u16), value, is_column, format));
+                Ok(())
+            }
+        }
+    }
+    pub fn write_formula_to_self(
+        &mut self,
+        range: RangeAddr,
+        formula: String,
+        value: Option<CalamineData>,
+        format: Option<Format>,
+        is_dynamic: bool,
+    ) -> PyResult<()> {
+        let (first, last) = range.as_idx()?;
+        let n_rows = u32::try_from(last.0 - first.0 + 1)
+            .map_err(|_| PyValueError::new_err("Row index out of range"))?;
+        let n_cols = u16::try_from(last.1 - first.1 + 1)
+            .map_err(|_| PyValueError::new_err("Column index out of range"))?;
+        let kind = if is_dynamic {
+            FormulaKind::DynamicArray((n_rows, n_cols))
+        } else {
+            FormulaKind::Array((n_rows, n_cols))
+        };
+        let value = WrappedValue::Formula(FormulaData::new(formula, value, kind));
+        self.write_to_self(first.0, first.1, value, false, format)
+    }
+    /// Whether any buffered cell is written with a marked formula result
+    pub fn has_marked_cells(&self) -> bool {
         self.data_to_write
-            .push(((row_u32, col_u16), value, is_column));
-        Ok(())
+            .iter()
+            .any(|(_, value, _, _)| value.has_marked_cells())
     }
-    pub fn to_sheet(&self, sheet: &mut Worksheet) -> PyResult<()> {
+    pub fn to_sheet(
+        &self,
+        sheet: &mut Worksheet,
+        date_format: &str,
+        datetime_format: &str,
+    ) -> PyResult<()> {
         let _ = sheet
             .set_name(&self.title)
             .map_err(|e| PyFileExistsError::new_err(e.to_string()))?;
         self.data_to_write
             .iter()
-            .try_for_each(|(pos, data, is_column)| {
-                data.write_to_sheet(sheet, *pos, *is_column)
+            .try_for_each(|(pos, data, is_column, format)| {
+                let format = WriteFormat::new(format.as_ref(), date_format, datetime_format);
+                data.write_to_sheet(sheet, *pos, *is_column, &format)
                     .map_err(|e| PyValueError::new_err(e.to_string()))
-            })
+            })?;
+        // Tables write their headers, so they are added after the data
+        self.tables.iter().try_for_each(|table| {
+            let (first_row, first_col, last_row, last_col) = table.range;
+            sheet
+                .add_table(first_row, first_col, last_row, last_col, &table.table)
+                .map(|_| ())
+                .map_err(|e| PyValueError::new_err(e.to_string()))
+        })
     }
 }
 #[pymethods]
@@ -63,8 +194,15 @@ impl WriteOnlyWorksheet {
         WriteOnlyWorksheet {
             title,
             data_to_write: Vec::new(),
+            tables: Vec::new(),
+            stream: None,
         }
     }
+    /// Whether the worksheet is in constant memory mode.
+    #[getter]
+    pub fn constant_memory(&self) -> bool {
+        self.stream.is_some()
+    }
 
     /**
         Write a value to a specific cell in the worksheet.
@@ -77,18 +215,25 @@ impl WriteOnlyWorksheet {
             The value to write to the cell.
         dtype : DType, default DType.Any
             The data type to enforce for the value. If None, try for each type automatically.
+        format : Optional[Format], default None
+            The format to apply to the cell.
+        num_format : Optional[str], default None
+            The number format to apply to the cell, e.g. "0.00%" or "dd.mm.yyyy". Override the
+            number format of `format` and the default date/datetime format of the workbook.
     */
-    #[pyo3(signature = (cell_addr, value, *, dtype = Some(DType::Any)))]
+    #[pyo3(signature = (cell_addr, value, *, dtype = Some(DType::Any), format = None, num_format = None))]
     pub fn write_cell<'py>(
         &mut self,
         cell_addr: CellAddr,
         value: &Bound<'py, PyAny>,
         dtype: Option<DType>,
-        // style: Option<f64>,
+        format: Option<Format>,
+        num_format: Option<String>,
     ) -> PyResult<()> {
+        let format = Format::with_num_format(format, num_format);
         let value = extract_scalar!(value, dtype)?;
         let (row, col) = cell_addr.as_idx()?;
-        self.write_to_self(row, col, value, false)
+        self.write_to_self(row, col, value, false, format)
     }
     /**
         Write a row of values starting from a specific cell.
@@ -103,15 +248,23 @@ impl WriteOnlyWorksheet {
         dtype : Optional[DType], default None
             The data type to enforce for the values. If None, will try for every possible types.\n
             DType.Any allow each item have different type, but will also increase the time cost.
+        format : Optional[Format], default None
+            The format to apply to every written cell.
+        num_format : Optional[str], default None
+            The number format to apply to every written cell, e.g. "0.00%" or "dd.mm.yyyy".
+            Override the number format of `format` and the default date/datetime format of the
+            workbook.
     */
-    #[pyo3(signature = (cell_addr, value, *, dtype = None))]
+    #[pyo3(signature = (cell_addr, value, *, dtype = None, format = None, num_format = None))]
     pub fn write_row<'py>(
         &mut self,
         cell_addr: CellAddr,
         value: &Bound<'py, PyAny>,
         dtype: Option<DType>,
-        // style: Option<f64>,
+        format: Option<Format>,
+        num_format: Option<String>,
     ) -> PyResult<()> {
+        let format = Format::with_num_format(format, num_format);
         let value = extract_array1!(value, dtype)?;
         let (row, col) = cell_addr.as_idx()?;
         let data_shape = value.get_shape(false);
@@ -120,7 +273,7 @@ impl WriteOnlyWorksheet {
                 "write_row only accepty 1d-array, please use write_cell for scalar or write_matrix for 2d-array",
             ));
         }
-        self.write_to_self(row, col, value, false)
+        self.write_to_self(row, col, value, false, format)
     }
     /**
         Write a column of values starting from a specific cell.
@@ -135,15 +288,23 @@ impl WriteOnlyWorksheet {
         dtype : Optional[DType], default None
             The data type to enforce for the values. If None, will try for every possible types.\n
             DType.Any allow each item have different type, but will also increase the time cost.
+        format : Optional[Format], default None
+            The format to apply to every written cell.
+        num_format : Optional[str], default None
+            The number format to apply to every written cell, e.g. "0.00%" or "dd.mm.yyyy".
+            Override the number format of `format` and the default date/datetime format of the
+            workbook.
     */
-    #[pyo3(signature = (cell_addr, value, *, dtype = None))]
+    #[pyo3(signature = (cell_addr, value, *, dtype = None, format = None, num_format = None))]
     pub fn write_column<'py>(
         &mut self,
         cell_addr: CellAddr,
         value: &Bound<'py, PyAny>,
         dtype: Option<DType>,
-        // style: Option<f64>,
+        format: Option<Format>,
+        num_format: Option<String>,
     ) -> PyResult<()> {
+        let format = Format::with_num_format(format, num_format);
         let (row, col) = cell_addr.as_idx()?;
         let value = extract_array1!(value, dtype)?;
         let data_shape = value.get_shape(true);
@@ -152,7 +313,7 @@ impl WriteOnlyWorksheet {
                 "write_column only accepty 1d-array, please use write_cell for scalar or write_matrix for matrix",
             ));
         }
-        self.write_to_self(row, col, value, true)
+        self.write_to_self(row, col, value, true, format)
     }
     /**
         Write a matrix of values starting from a specific cell.
@@ -167,15 +328,23 @@ impl WriteOnlyWorksheet {
         dtype : Optional[DType], default None
             The data type to enforce for the values. If None, will try for every possible types.\n
             DType.Any allow each item have different type, but will also increase the time cost.
+        format : Optional[Format], default None
+            The format to apply to every written cell.
+        num_format : Optional[str], default None
+            The number format to apply to every written cell, e.g. "0.00%" or "dd.mm.yyyy".
+            Override the number format of `format` and the default date/datetime format of the
+            workbook.
     */
-    #[pyo3(signature = (cell_addr, value, *, dtype = None))]
+    #[pyo3(signature = (cell_addr, value, *, dtype = None, format = None, num_format = None))]
     pub fn write_matrix<'py>(
         &mut self,
         cell_addr: CellAddr,
         value: &Bound<'py, PyAny>,
         dtype: Option<DType>,
-        // style: Option<f64>,
+        format: Option<Format>,
+        num_format: Option<String>,
     ) -> PyResult<()> {
+        let format = Format::with_num_format(format, num_format);
         let (row, col) = cell_addr.as_idx()?;
         let value = extract_array2!(value, dtype)?;
         let data_shape = value.get_shape(false);
@@ -190,7 +359,241 @@ impl WriteOnlyWorksheet {
                 "write_matrix only accepty 2d-array, please use write_cell for scalar or write_row/write_column for 1d-array",
             ));
         }
-        self.write_to_self(row, col, value, false)
+        self.write_to_self(row, col, value, false, format)
+    }
+    /**
+        Write columns of data as an Excel table (ListObject), with a header row of the column
+        names and optional total row.
+
+        Parameters
+        ----------
+        cell_addr : Union[Tuple[int, int], str]
+            The top-left cell of the table, either as a tuple of (row, col) or a string
+            (e.g., "A1").
+        columns : Dict[str, Union[np.ndarray, List[Any]]]
+            A dictionary mapping headers to columns of values with the same length.\n
+            MUST be `np.ndarray` with correct dtype if the dtype of a column is one of
+            [`DType.Bool`, `DType.Int`, `DType.Float`]
+        name : Optional[str], default None
+            The name of the table, e.g. "Sales". Excel names it "Table1", "Table2", ... if None.
+        style : Optional[str], default None
+            The table style, e.g. "TableStyleMedium9", "Light1" or "None". Excel uses
+            "TableStyleMedium9" if None.
+        total_row : bool | Dict[str, str], default False
+            Whether to add a total row, or a dictionary mapping headers to their total, which
+            is one of the functions "sum", "average", "count", "count_numbers", "max", "min",
+            "std_dev", "var", or a label like "Total".
+        dtypes : Optional[DType | List[DType] | Dict[str, DType]], default None
+            The data types of the columns, either one for all columns, a list in the order of
+            columns, or a dict by header. If None, will try for every possible types.
+    */
+    #[pyo3(signature = (
+        cell_addr,
+        columns,
+        *,
+        name = None,
+        style = None,
+        total_row = TotalRow::Enabled(false),
+        dtypes = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_table<'py>(
+        &mut self,
+        cell_addr: CellAddr,
+        columns: IndexMap<String, Bound<'py, PyAny>>,
+        name: Option<String>,
+        style: Option<String>,
+        total_row: TotalRow,
+        dtypes: Option<ColumnDTypes>,
+    ) -> PyResult<()> {
+        if self.stream.is_some() {
+            return Err(PyValueError::new_err(
+                "write_table is not supported by constant memory worksheets",
+            ));
+        }
+        if columns.is_empty() {
+            return Err(PyValueError::new_err("A table must have at least one column"));
+        }
+        let (row, col) = cell_addr.as_idx()?;
+        let headers = columns.keys().cloned().collect::<Vec<_>>();
+        let dtypes = match dtypes {
+            Some(dtypes) => dtypes.resolve(&headers)?.into_iter().map(Some).collect(),
+            None => vec![None; headers.len()],
+        };
+        let values = columns
+            .values()
+            .zip(dtypes)
+            .map(|(value, dtype)| extract_array1!(value, dtype))
+            .collect::<PyResult<Vec<_>>>()?;
+        let lengths = values
+            .iter()
+            .map(|value| match value.get_shape(true) {
+                DShape::Column { n_rows } => Ok(n_rows),
+                _ => Err(PyValueError::new_err("Columns of a table must be 1d-arrays")),
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        if lengths.iter().any(|&n| n != lengths[0]) {
+            return Err(PyValueError::new_err(
+                "Columns of a table must have the same length",
+            ));
+        }
+        let first = (
+            u32::try_from(row).map_err(|_| PyValueError::new_err("Row index out of range"))?,
+            u16::try_from(col).map_err(|_| PyValueError::new_err("Column index out of range"))?,
+        );
+        let n_rows =
+            u32::try_from(lengths[0]).map_err(|_| PyValueError::new_err("Table is too long"))?;
+        if headers.len() > (u16::MAX - first.1) as usize {
+            return Err(PyValueError::new_err("Table is too wide"));
+        }
+        let table = TableToWrite::new(first, &headers, n_rows, name, style, total_row)?;
+        // Validate the table in advance, instead of failing on `save`
+        let (first_row, first_col, last_row, last_col) = table.range;
+        Worksheet::new()
+            .add_table(first_row, first_col, last_row, last_col, &table.table)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        values
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(j, value)| self.write_to_self(row + 1, col + j, value, true, None))?;
+        self.tables.push(table);
+        Ok(())
+    }
+    /**
+        Write a pandas or polars DataFrame, with a header row of the column names. Each column
+        is written by the type of its values, and missing values (None, NaN, NaT) are written as
+        blank cells.
+
+        Parameters
+        ----------
+        cell_addr : Union[Tuple[int, int], str]
+            The top-left cell of the DataFrame, either as a tuple of (row, col) or a string
+            (e.g., "A1").
+        df : Union[pandas.DataFrame, polars.DataFrame]
+            The DataFrame to write. Boolean, integer, float and datetime columns are written from
+            their numpy arrays, datetime columns of polars `Date` type are written as dates.
+            Datetimes with a time zone are written as their local times, and unsigned integers
+            past the range of int64 as floats.
+        header : bool, default True
+            Whether to write the column names in the first row.
+        index : bool, default False
+            Whether to write the index of a pandas DataFrame as its first columns.
+        formats : Optional[Format | Dict[str, Format]], default None
+            The formats of the values, either one for all columns or a dict by column name.
+    */
+    #[pyo3(signature = (cell_addr, df, *, header = true, index = false, formats = None))]
+    pub fn write_dataframe<'py>(
+        &mut self,
+        cell_addr: CellAddr,
+        df: &Bound<'py, PyAny>,
+        header: bool,
+        index: bool,
+        formats: Option<ColumnFormats>,
+    ) -> PyResult<()> {
+        if self.stream.is_some() {
+            return Err(PyValueError::new_err(
+                "write_dataframe is not supported by constant memory worksheets",
+            ));
+        }
+        let (row, col) = cell_addr.as_idx()?;
+        let (headers, columns) = frame_to_write(df, index)?;
+        let formats = match formats {
+            Some(formats) => formats.resolve(&headers)?,
+            None => vec![None; headers.len()],
+        };
+        let first_row = if header && !headers.is_empty() {
+            let headers = Array1Container::new(Array1::from_vec(headers));
+            let value = WrappedValue::Str(ValueContainer::Array1(headers));
+            self.write_to_self(row, col, value, false, None)?;
+            row + 1
+        } else {
+            row
+        };
+        columns
+            .into_iter()
+            .zip(formats)
+            .enumerate()
+            .try_for_each(|(j, (value, format))| {
+                self.write_to_self(first_row, col + j, value, true, format)
+            })
+    }
+    /**
+        Write a formula to a specific cell in the worksheet.
+
+        Parameters
+        ----------
+        cell_addr : Union[Tuple[int, int], str]
+            The cell address, either as a 0-based tuple of (row, col) or a string (e.g., "A1").
+        formula : str
+            The formula to write, e.g. "=SUM(A1:A10)". The leading "=" is optional.
+        value : Optional[Any], default None
+            The cached result of the formula, shown by readers that do not recalculate.
+            If None, Excel will recalculate it when the file is opened.
+        format : Optional[Format], default None
+            The format to apply to the cell.
+    */
+    #[pyo3(signature = (cell_addr, formula, *, value = None, format = None))]
+    pub fn write_formula(
+        &mut self,
+        cell_addr: CellAddr,
+        formula: String,
+        value: Option<CalamineData>,
+        format: Option<Format>,
+    ) -> PyResult<()> {
+        let (row, col) = cell_addr.as_idx()?;
+        let value = WrappedValue::Formula(FormulaData::new(formula, value, FormulaKind::Single));
+        self.write_to_self(row, col, value, false, format)
+    }
+    /**
+        Write an array formula (CSE formula) to a range of the worksheet.
+
+        Parameters
+        ----------
+        range : Union[Tuple[Tuple[int, int], Tuple[int, int]], str]
+            The range the formula applies to, either as a 0-based tuple of (first, last) cells or
+            a string (e.g., "A1:C3").
+        formula : str
+            The array formula to write, e.g. "{=LINEST(A1:A10, B1:B10)}". The braces and leading
+            "=" are optional.
+        value : Optional[Any], default None
+            The cached result of the first cell of the range.
+        format : Optional[Format], default None
+            The format to apply to every cell of the range.
+    */
+    #[pyo3(signature = (range, formula, *, value = None, format = None))]
+    pub fn write_array_formula(
+        &mut self,
+        range: RangeAddr,
+        formula: String,
+        value: Option<CalamineData>,
+        format: Option<Format>,
+    ) -> PyResult<()> {
+        self.write_formula_to_self(range, formula, value, format, false)
+    }
+    /**
+        Write a dynamic array formula to a range of the worksheet.
+
+        Parameters
+        ----------
+        range : Union[Tuple[Tuple[int, int], Tuple[int, int]], str]
+            The range the formula result spills into, either as a 0-based tuple of
+            (first, last) cells or a string (e.g., "A1:A10").
+        formula : str
+            The dynamic array formula to write, e.g. "=SORT(B1:B10)". The leading "=" is optional.
+        value : Optional[Any], default None
+            The cached result of the first cell of the range.
+        format : Optional[Format], default None
+            The format to apply to every cell of the range.
+    */
+    #[pyo3(signature = (range, formula, *, value = None, format = None))]
+    pub fn write_dynamic_array_formula(
+        &mut self,
+        range: RangeAddr,
+        formula: String,
+        value: Option<CalamineData>,
+        format: Option<Format>,
+    ) -> PyResult<()> {
+        self.write_formula_to_self(range, formula, value, format, true)
     }
 
     fn __repr__(&self) -> String {
@@ -203,6 +606,14 @@ impl WriteOnlyWorksheet {
 pub struct WriteOnlyWorkbook {
     worksheets: Vec<Py<WriteOnlyWorksheet>>,
     title_map: IndexMap<String, usize>,
+    #[pyo3(get, set)]
+    date_format: String,
+    #[pyo3(get, set)]
+    datetime_format: String,
+    /// Only created once a constant memory worksheet is requested
+    shared: Option<Arc<Mutex<SharedWorkbook>>>,
+    /// The defined names as (name, reference), added to the workbook on `save`
+    defined_names: Vec<(String, String)>,
 }
 
 impl WriteOnlyWorkbook {
@@ -214,15 +625,139 @@ impl WriteOnlyWorkbook {
             .collect::<Vec<String>>();
         format!("[{}]", sheeynames.join(", "))
     }
+    /// Add a worksheet to the shared workbook if needed, return the stream to write into it
+    /// directly if `constant_memory` is true.
+    fn add_to_shared(
+        &mut self,
+        title: &str,
+        constant_memory: bool,
+    ) -> PyResult<Option<SheetStream>> {
+        if constant_memory && self.shared.is_none() {
+            // Keep the order of worksheets created before, they will be filled on `save`
+            let mut workbook = Workbook::new();
+            self.worksheets.iter().for_each(|_| {
+                workbook.add_worksheet();
+            });
+            self.shared = Some(Arc::new(Mutex::new(SharedWorkbook {
+                workbook,
+                saved: false,
+                written: false,
+                marked: false,
+            })));
+        }
+        let Some(shared) = &self.shared else {
+            return Ok(None);
+        };
+        let mut guard = lock_shared(shared)?;
+        if guard.saved || guard.written {
+            return Err(PyValueError::new_err(
+                "Could not add worksheet to a workbook with constant memory worksheets after it is saved",
+            ));
+        }
+        let sheet = if constant_memory {
+            guard.workbook.add_worksheet_with_constant_memory()
+        } else {
+            guard.workbook.add_worksheet()
+        };
+        sheet
+            .set_name(title)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(constant_memory.then(|| SheetStream {
+            shared: Arc::clone(shared),
+            idx: self.worksheets.len(),
+            current_row: 0,
+            date_format: self.date_format.clone(),
+            datetime_format: self.datetime_format.clone(),
+        }))
+    }
+    /// Fill a `rust_xlsxwriter::Workbook` with all worksheets and hand it to `save_fn`, with
+    /// whether it has cells with marked formula results. The GIL is released meanwhile.
+    fn save_rs<T: Send>(
+        &self,
+        py: Python<'_>,
+        save_fn: impl FnOnce(&mut Workbook, bool) -> Result<T, XlsxError> + Send,
+    ) -> PyResult<T> {
+        // Borrow the buffered data of the Python objects rather than copying it, the borrows
+        // are held while the heavy work is done without the GIL
+        let borrowed = self
+            .worksheets
+            .iter()
+            .map(|py_ws| py_ws.try_borrow(py))
+            .collect::<Result<Vec<_>, _>>()?;
+        let worksheets = borrowed.iter().map(|ws| &**ws).collect::<Vec<_>>();
+        let (date_format, datetime_format) = (&self.date_format, &self.datetime_format);
+        let marked = worksheets.iter().any(|ws| ws.has_marked_cells());
+        let define_names = |workbook: &mut Workbook| {
+            self.defined_names
+                .iter()
+                .try_for_each(|(name, reference)| {
+                    workbook.define_name(name, reference)?;
+                    Ok(())
+                })
+                .map_err(|e: XlsxError| PyValueError::new_err(e.to_string()))
+        };
+        py.allow_threads(|| {
+            if let Some(shared) = &self.shared {
+                let mut guard = lock_shared(shared)?;
+                if guard.saved {
+                    return Err(PyValueError::new_err(
+                        "A workbook with constant memory worksheets could only be saved once",
+                    ));
+                }
+                if !guard.written {
+                    worksheets.iter().enumerate().try_for_each(|(idx, ws)| {
+                        let sheet = guard
+                            .workbook
+                            .worksheet_from_index(idx)
+                            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                        ws.to_sheet(sheet, date_format, datetime_format)
+                    })?;
+                    define_names(&mut guard.workbook)?;
+                    guard.written = true;
+                }
+                let marked = marked || guard.marked;
+                let result = save_fn(&mut guard.workbook, marked)
+                    .map_err(|e| PyFileExistsError::new_err(e.to_string()))?;
+                guard.saved = true;
+                return Ok(result);
+            }
+            let mut workbook = Workbook::new();
+            worksheets.iter().try_for_each(|ws| {
+                let sheet = workbook.add_worksheet();
+                ws.to_sheet(sheet, date_format, datetime_format)
+            })?;
+            define_names(&mut workbook)?;
+            save_fn(&mut workbook, marked).map_err(|e| PyFileExistsError::new_err(e.to_string()))
+        })
+    }
 }
 
 #[pymethods]
 impl WriteOnlyWorkbook {
+    /**
+        Generate a `WriteOnlyWorkbook` object.
+
+        Parameters
+        ----------
+        date_format : str, default "yyyy/mm/dd"
+            The default number format of date cells.
+        datetime_format : str, default "yyyy/mm/dd hh:mm:ss"
+            The default number format of datetime cells.
+    */
     #[new]
-    fn new() -> Self {
+    #[pyo3(signature = (
+        *,
+        date_format = DEFAULT_DATE_NUM_FORMAT.to_string(),
+        datetime_format = DEFAULT_DATETIME_NUM_FORMAT.to_string()
+    ))]
+    fn new(date_format: String, datetime_format: String) -> Self {
         Self {
             worksheets: Vec::new(),
             title_map: IndexMap::new(),
+            date_format,
+            datetime_format,
+            shared: None,
+            defined_names: Vec::new(),
         }
     }
 
@@ -233,25 +768,42 @@ impl WriteOnlyWorkbook {
         ----------
         name : str
             The name of the new worksheet.
+        constant_memory : bool, default False
+            Whether to create the worksheet in constant memory mode. Instead of being buffered
+            until `save`, data are written into the workbook immediately and each row is flushed
+            to a temporary file as soon as a later row is written. Thus:\n
+            - Writes must be in row order: writing to a row before the last written row raises
+            `ValueError`. Writing several times to the last written row is allowed.\n
+            - `write_column` and `write_matrix` move the last written row to their last row.\n
+            - The date formats of the workbook are fixed when the worksheet is created.\n
+            - The workbook could only be saved once, and the worksheet could not be used
+            with `write_many`.
 
         Returns
         -------
         WriteOnlyWorksheet
             The newly created worksheet.
     */
-    fn create_sheet(&mut self, py: Python<'_>, title: String) -> PyResult<&Py<WriteOnlyWorksheet>> {
+    #[pyo3(signature = (title, *, constant_memory = false))]
+    fn create_sheet(
+        &mut self,
+        py: Python<'_>,
+        title: String,
+        constant_memory: bool,
+    ) -> PyResult<&Py<WriteOnlyWorksheet>> {
         if self.title_map.contains_key(&title) {
             Err(PyValueError::new_err(format!(
                 "Duplicate worksheet title: {}",
                 title
             )))
         } else {
+            let stream = self.add_to_shared(&title, constant_memory)?;
             self.title_map.insert(title.clone(), self.title_map.len());
             let py_worksheet = Py::new(
                 py,
                 WriteOnlyWorksheet {
-                    title,
-                    data_to_write: Vec::new(),
+                    stream,
+                    ..WriteOnlyWorksheet::new(title)
                 },
             )?;
             self.worksheets.push(py_worksheet);
@@ -275,7 +827,7 @@ impl WriteOnlyWorkbook {
     */
     fn get_by_idx(&self, idx: usize) -> PyResult<&Py<WriteOnlyWorksheet>> {
         self.worksheets
-            .get(idx as usize)
+            .get(idx)
             .ok_or(PyValueError::new_err(format!(
                 "Worksheet at index {} not found. Total worksheets available: {}",
                 idx,
@@ -325,23 +877,70 @@ impl WriteOnlyWorkbook {
         }
     }
     /**
-        Save the workbook to the specified file path.
+        Define a name (named range) of the workbook.
 
         Parameters
         ----------
-        path : str
-            The file path where the workbook will be saved.
+        name : str
+            The name, e.g. "Rate". Prefix it with a sheet name to define a name local to that
+            sheet, e.g. "Sheet1!Rate".
+        reference : str
+            What the name refers to, e.g. "=Sheet1!$B$2", "=Sheet1!$A$1:$C$10" or a constant
+            like "=0.05".
     */
-    fn save(&self, py: Python<'_>, path: String) -> PyResult<()> {
-        let mut workbook = Workbook::new();
-        let _ = self.worksheets.iter().try_for_each(|py_ws| {
-            let ws: WriteOnlyWorksheet = py_ws.extract(py)?;
-            let sheet = workbook.add_worksheet();
-            ws.to_sheet(sheet)
-        })?;
-        workbook
-            .save(path)
-            .map_err(|e| PyFileExistsError::new_err(e.to_string()))
+    fn define_name(&mut self, name: String, reference: String) -> PyResult<()> {
+        let local_name = name.split_once('!').map_or(name.as_str(), |(_, local)| local);
+        if local_name.is_empty() {
+            return Err(PyValueError::new_err("Defined name could not be empty"));
+        }
+        // Validate the name in advance, instead of failing on `save`
+        Workbook::new()
+            .define_name(&name, &reference)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        if self
+            .defined_names
+            .iter()
+            .any(|(defined, _)| defined.to_lowercase() == name.to_lowercase())
+        {
+            return Err(PyValueError::new_err(format!(
+                "Duplicate defined name: \"{name}\""
+            )));
+        }
+        self.defined_names.push((name, reference));
+        Ok(())
+    }
+    /**
+        Save the workbook to the specified file path or file-like object.
+
+        Parameters
+        ----------
+        path : str | os.PathLike | BinaryIO
+            The file path where the workbook will be saved, or a writable binary file-like
+            object, e.g. an `io.BytesIO` or a file opened with "wb".
+    */
+    fn save(&self, py: Python<'_>, path: PathOrWriter) -> PyResult<()> {
+        match path {
+            PathOrWriter::Path(path) => self.save_rs(py, |workbook, marked| {
+                save_to_path(workbook, marked, path)
+            }),
+            PathOrWriter::Writer(writer) => {
+                let buffer = self.save_rs(py, save_to_buffer)?;
+                writer.call_method1(py, "write", (PyBytes::new(py, &buffer),))?;
+                Ok(())
+            }
+        }
+    }
+    /**
+        Save the workbook into memory.
+
+        Returns
+        -------
+        bytes
+            The content of the xlsx file.
+    */
+    fn save_to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let buffer = self.save_rs(py, save_to_buffer)?;
+        Ok(PyBytes::new(py, &buffer))
     }
     /**
         Get the names of all worksheets in the workbook.
@@ -374,6 +973,10 @@ impl WriteOnlyWorkbook {
     workbooks_to_write : Dict[str, List[WriteOnlyWorksheet]]
         A dictionary mapping workbook file paths to lists of `WriteOnlyWorksheet` objects.
         Each `WriteOnlyWorksheet` object represents a worksheet containing data to be written.
+    date_format : str, default "yyyy/mm/dd"
+        The default number format of date cells.
+    datetime_format : str, default "yyyy/mm/dd hh:mm:ss"
+        The default number format of datetime cells.
 
     Examples
     --------
@@ -390,26 +993,1035 @@ impl WriteOnlyWorkbook {
     >>> write_many(workbooks_to_write)
 */
 #[pyfunction]
-pub fn write_many(workbooks_to_write: IndexMap<String, Vec<WriteOnlyWorksheet>>) -> PyResult<()> {
+#[pyo3(signature = (
+    workbooks_to_write,
+    *,
+    date_format = DEFAULT_DATE_NUM_FORMAT.to_string(),
+    datetime_format = DEFAULT_DATETIME_NUM_FORMAT.to_string()
+))]
+pub fn write_many(
+    py: Python<'_>,
+    workbooks_to_write: IndexMap<String, Vec<WriteOnlyWorksheet>>,
+    date_format: String,
+    datetime_format: String,
+) -> PyResult<()> {
+    py.allow_threads(|| write_many_rs(workbooks_to_write, &date_format, &datetime_format))
+}
+/**
+    Write multiple workbooks into memory.
+
+    The same as `write_many`, but the workbooks are returned instead of being written to disk.
+
+    Parameters
+    ----------
+    workbooks_to_write : Dict[str, List[WriteOnlyWorksheet]]
+        A dictionary mapping workbook keys (e.g. file names) to lists of `WriteOnlyWorksheet`
+        objects.
+    date_format : str, default "yyyy/mm/dd"
+        The default number format of date cells.
+    datetime_format : str, default "yyyy/mm/dd hh:mm:ss"
+        The default number format of datetime cells.
+
+    Returns
+    -------
+    Dict[str, bytes]
+        A dictionary mapping the keys to the content of the xlsx files.
+*/
+#[pyfunction]
+#[pyo3(signature = (
+    workbooks_to_write,
+    *,
+    date_format = DEFAULT_DATE_NUM_FORMAT.to_string(),
+    datetime_format = DEFAULT_DATETIME_NUM_FORMAT.to_string()
+))]
+pub fn write_many_to_bytes(
+    py: Python<'_>,
+    workbooks_to_write: IndexMap<String, Vec<WriteOnlyWorksheet>>,
+    date_format: String,
+    datetime_format: String,
+) -> PyResult<IndexMap<String, Py<PyBytes>>> {
+    let buffers = py.allow_threads(|| {
+        workbooks_to_write
+            .into_par_iter()
+            .map(|(key, worksheets)| {
+                let (mut workbook, marked) =
+                    build_workbook(worksheets, &date_format, &datetime_format)?;
+                let buffer = save_to_buffer(&mut workbook, marked)
+                    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                Ok((key, buffer))
+            })
+            .collect::<PyResult<Vec<_>>>()
+    })?;
+    Ok(buffers
+        .into_iter()
+        .map(|(key, buffer)| (key, PyBytes::new(py, &buffer).unbind()))
+        .collect())
+}
+
+fn write_many_rs(
+    workbooks_to_write: IndexMap<String, Vec<WriteOnlyWorksheet>>,
+    date_format: &str,
+    datetime_format: &str,
+) -> PyResult<()> {
     workbooks_to_write
         .into_par_iter()
         .try_for_each(|(filename, worksheets)| {
-            let mut workbook = Workbook::new();
-            let mut title_set: HashSet<String> = HashSet::new();
-            let _ = worksheets.into_iter().try_for_each(|ws| {
-                if title_set.contains(&ws.title) {
-                    Err(PyValueError::new_err(format!(
-                        "Duplicate worksheet title: \"{}\"",
-                        ws.title
-                    )))
-                } else {
-                    title_set.insert(ws.title.clone());
-                    let sheet = workbook.add_worksheet();
-                    ws.to_sheet(sheet)
-                }
-            })?;
-            workbook
-                .save(filename)
+            let (mut workbook, marked) = build_workbook(worksheets, date_format, datetime_format)?;
+            save_to_path(&mut workbook, marked, filename)
                 .map_err(|e| PyFileExistsError::new_err(e.to_string()))
         })
 }
+
+/// Build a workbook from worksheets, with whether it has cells with marked formula results
+fn build_workbook(
+    worksheets: Vec<WriteOnlyWorksheet>,
+    date_format: &str,
+    datetime_format: &str,
+) -> PyResult<(Workbook, bool)> {
+    let marked = worksheets.iter().any(|ws| ws.has_marked_cells());
+    let mut workbook = Workbook::new();
+    let mut title_set: HashSet<String> = HashSet::new();
+    worksheets.into_iter().try_for_each(|ws| {
+        if title_set.contains(&ws.title) {
+            Err(PyValueError::new_err(format!(
+                "Duplicate worksheet title: \"{}\"",
+                ws.title
+            )))
+        } else if ws.stream.is_some() {
+            Err(PyValueError::new_err(format!(
+                "Constant memory worksheet \"{}\" could only be saved by its workbook",
+                ws.title
+            )))
+        } else {
+            title_set.insert(ws.title.clone());
+            let sheet = workbook.add_worksheet();
+            ws.to_sheet(sheet, date_format, datetime_format)
+        }
+    })?;
+    Ok((workbook, marked))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::{ReadOnlyWorkbook, ReadOnlyWorksheet};
+    use crate::types::{ExcelError, ListOrDict, RangeDTypes, RangeInfo};
+    use calamine::{CellErrorType, Data, Reader, Sheets, Xlsx};
+    use pyo3::ffi::c_str;
+    use pyo3::types::{PyDate, PyDateTime, PyList, PyModule};
+    use std::io::Cursor;
+
+    type Ws = Py<WriteOnlyWorksheet>;
+
+    fn workbook() -> WriteOnlyWorkbook {
+        WriteOnlyWorkbook::new(
+            DEFAULT_DATE_NUM_FORMAT.to_string(),
+            DEFAULT_DATETIME_NUM_FORMAT.to_string(),
+        )
+    }
+
+    /// Save the workbook after it is built by `build` into memory
+    fn save(
+        mut wb: WriteOnlyWorkbook,
+        build: impl FnOnce(Python<'_>, &mut WriteOnlyWorkbook) -> PyResult<()>,
+    ) -> Vec<u8> {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            build(py, &mut wb).unwrap();
+            wb.save_to_bytes(py).unwrap().as_bytes().to_vec()
+        })
+    }
+
+    /// Save the workbook built by `build` into memory and open it again
+    fn round_trip(
+        build: impl FnOnce(Python<'_>, &mut WriteOnlyWorkbook) -> PyResult<()>,
+    ) -> Xlsx<Cursor<Vec<u8>>> {
+        Xlsx::new(Cursor::new(save(workbook(), build))).unwrap()
+    }
+
+    /// Open a saved workbook with `ReadOnlyWorkbook`
+    fn reopen(buffer: Vec<u8>) -> ReadOnlyWorkbook {
+        ReadOnlyWorkbook::from_bytes_rs(buffer).unwrap()
+    }
+
+    /// The XML of a file in a saved workbook, e.g. "xl/styles.xml"
+    fn xml(buffer: &[u8], name: &str) -> String {
+        let mut archive = zip::ZipArchive::new(Cursor::new(buffer)).unwrap();
+        std::io::read_to_string(archive.by_name(name).unwrap()).unwrap()
+    }
+
+    /// The values read from a worksheet as text in row order, "" for empty cells
+    fn texts(value: WrappedValue) -> Vec<String> {
+        fn flat<T: Clone>(value: ValueContainer<T>) -> Vec<T> {
+            match value {
+                ValueContainer::Scalar(v) => vec![v],
+                ValueContainer::Array1(c) => c.value.to_vec(),
+                ValueContainer::Array2(c) => c.value.iter().cloned().collect(),
+            }
+        }
+        fn text(v: impl ToString) -> String {
+            v.to_string()
+        }
+        match value {
+            WrappedValue::Bool(v) => flat(v).into_iter().map(text).collect(),
+            WrappedValue::Int(v) => flat(v).into_iter().map(text).collect(),
+            WrappedValue::Float(v) => flat(v).into_iter().map(text).collect(),
+            WrappedValue::Str(v) => flat(v),
+            WrappedValue::Date(v) => flat(v).into_iter().map(text).collect(),
+            WrappedValue::DateTime(v) => flat(v).into_iter().map(text).collect(),
+            WrappedValue::Any(v) => flat(v)
+                .into_iter()
+                .map(|v| match v {
+                    CalamineData::Int(v) => text(v),
+                    CalamineData::Float(v) => text(v),
+                    CalamineData::Str(v) => v,
+                    CalamineData::Bool(v) => text(v),
+                    CalamineData::Date(v) => text(v),
+                    CalamineData::DateTime(v) => text(v),
+                    CalamineData::Error(v) => text(v.as_str()),
+                    CalamineData::Empty => String::new(),
+                })
+                .collect(),
+            WrappedValue::Columns(columns) => columns.into_values().flat_map(texts).collect(),
+            WrappedValue::Formula(_) => panic!("formulas are not read"),
+        }
+    }
+
+    /// Read the range at `addr` of a sheet with `dtype`
+    fn read(ws: &ReadOnlyWorksheet, addr: &str, dtype: DType) -> Vec<String> {
+        let range_info = RangeInfo::from_addr(addr, RangeDTypes::One(dtype), true).unwrap();
+        texts(ws.read_value_rs(&range_info).unwrap())
+    }
+
+    fn sheet(wb: &mut WriteOnlyWorkbook, py: Python<'_>, title: &str, constant_memory: bool) -> Ws {
+        let ws = wb.create_sheet(py, title.to_string(), constant_memory);
+        ws.unwrap().clone_ref(py)
+    }
+
+    fn cell(addr: &str) -> CellAddr {
+        CellAddr::Name(addr.to_string())
+    }
+
+    #[test]
+    fn error_cells() {
+        let mut xlsx = round_trip(|py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            let na = Bound::new(py, ExcelError::NA)?;
+            ws.write_cell(cell("A1"), na.as_any(), Some(DType::Any), None, None)?;
+            let row = PyList::new(
+                py,
+                [
+                    1i64.into_pyobject(py)?.into_any(),
+                    Bound::new(py, ExcelError::Div0)?.into_any(),
+                ],
+            )?;
+            ws.write_row(cell("B1"), row.as_any(), Some(DType::Any), None, None)?;
+            let value = Some(CalamineData::Error(ExcelError::Div0));
+            ws.write_formula(cell("A2"), "=1/0".to_string(), value, None)?;
+            // Text looking like an error is kept as text
+            let text = "#N/A".into_pyobject(py)?;
+            ws.write_cell(cell("A3"), text.as_any(), Some(DType::Any), None, None)?;
+            let value = Some(CalamineData::Str("#N/A".to_string()));
+            ws.write_formula(cell("A4"), "=\"#N/A\"".to_string(), value, None)?;
+            // Constant memory worksheets are written directly into the workbook
+            let ws = sheet(wb, py, "Sheet2", true);
+            let mut ws = ws.borrow_mut(py);
+            let value = Bound::new(py, ExcelError::Ref)?;
+            ws.write_cell(cell("A1"), value.as_any(), None, None, None)
+        });
+        let range = xlsx.worksheet_range("Sheet1").unwrap();
+        let error = |e| Some(Data::Error(e));
+        assert_eq!(range.get_value((0, 0)).cloned(), error(CellErrorType::NA));
+        assert_eq!(range.get_value((0, 1)).cloned(), Some(Data::Float(1.0)));
+        assert_eq!(range.get_value((0, 2)).cloned(), error(CellErrorType::Div0));
+        assert_eq!(range.get_value((1, 0)).cloned(), error(CellErrorType::Div0));
+        assert_eq!(
+            range.get_value((2, 0)).cloned(),
+            Some(Data::String("#N/A".into()))
+        );
+        assert_eq!(
+            range.get_value((3, 0)).cloned(),
+            Some(Data::String("#N/A".into()))
+        );
+        // Error cells are written without formulas, formulas keep their errors as results
+        let formulas = xlsx.worksheet_formula("Sheet1").unwrap();
+        assert!(formulas.get_value((0, 0)).is_none_or(String::is_empty));
+        assert_eq!(formulas.get_value((1, 0)), Some(&"1/0".to_string()));
+        let range = xlsx.worksheet_range("Sheet2").unwrap();
+        assert_eq!(range.get_value((0, 0)).cloned(), error(CellErrorType::Ref));
+    }
+
+    #[test]
+    fn formula_results() {
+        let mut xlsx = round_trip(|py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            let results = [
+                ("=1=1", CalamineData::Bool(true)),
+                ("=1=2", CalamineData::Bool(false)),
+                ("=1+1", CalamineData::Int(2)),
+                ("=\"a\"", CalamineData::Str("a".to_string())),
+            ];
+            for (i, (formula, value)) in results.into_iter().enumerate() {
+                ws.write_formula(
+                    CellAddr::Idx((i, 0)),
+                    formula.to_string(),
+                    Some(value),
+                    None,
+                )?;
+            }
+            let range = RangeAddr::Name("B1:B2".to_string());
+            let value = Some(CalamineData::Bool(true));
+            ws.write_array_formula(range, "=A1:A2".to_string(), value, None)
+        });
+        let range = xlsx.worksheet_range("Sheet1").unwrap();
+        assert_eq!(range.get_value((0, 0)), Some(&Data::Bool(true)));
+        assert_eq!(range.get_value((1, 0)), Some(&Data::Bool(false)));
+        assert_eq!(range.get_value((2, 0)), Some(&Data::Float(2.0)));
+        assert_eq!(range.get_value((3, 0)), Some(&Data::String("a".into())));
+        assert_eq!(range.get_value((0, 1)), Some(&Data::Bool(true)));
+        let formulas = xlsx.worksheet_formula("Sheet1").unwrap();
+        assert_eq!(formulas.get_value((0, 0)), Some(&"1=1".to_string()));
+        assert_eq!(formulas.get_value((0, 1)), Some(&"A1:A2".to_string()));
+    }
+
+    #[test]
+    fn saved_twice() {
+        // Saving borrows the buffered data, which is still there for the next save
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let mut wb = workbook();
+            let ws = sheet(&mut wb, py, "Sheet1", false);
+            let value = "text".into_pyobject(py).unwrap();
+            let write = |addr| {
+                let mut ws = ws.borrow_mut(py);
+                ws.write_cell(cell(addr), value.as_any(), Some(DType::Str), None, None)
+            };
+            write("A1").unwrap();
+            let first = wb.save_to_bytes(py).unwrap().as_bytes().to_vec();
+            write("A2").unwrap();
+            let second = wb.save_to_bytes(py).unwrap().as_bytes().to_vec();
+            let range = |buffer| {
+                let mut xlsx: Xlsx<_> = Xlsx::new(Cursor::new(buffer)).unwrap();
+                xlsx.worksheet_range("Sheet1").unwrap().get_size()
+            };
+            assert_eq!(range(first), (1, 1));
+            assert_eq!(range(second), (2, 1));
+            // A worksheet being written to could not be saved
+            let _ws = ws.borrow_mut(py);
+            assert!(wb.save_to_bytes(py).is_err());
+        });
+    }
+
+    /// The style index of a cell in the XML of a worksheet, None for the default style
+    fn style(sheet: &str, addr: &str) -> Option<String> {
+        let start = sheet.find(&format!("<c r=\"{addr}\""))?;
+        let tag = &sheet[start..start + sheet[start..].find('>')?];
+        let (_, style) = tag.split_once(" s=\"")?;
+        Some(style[..style.find('"')?].to_string())
+    }
+
+    /// A format with a bold font, a fill color or a number format
+    fn format(bold: bool, bg_color: Option<&str>, num_format: Option<&str>) -> Format {
+        let (bg_color, num_format) = (bg_color.map(Into::into), num_format.map(Into::into));
+        Format::new(
+            bold, false, false, None, None, None, bg_color, None, None, None, None, num_format,
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        let buffer = save(workbook(), |py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            let text = "header".into_pyobject(py)?;
+            let header = format(true, Some("#FF0000"), None);
+            ws.write_cell(cell("A1"), text.as_any(), None, Some(header.clone()), None)?;
+            let row = PyList::new(py, [0.5, 0.25])?;
+            let percent = format(false, None, Some("0.00%"));
+            ws.write_row(
+                cell("B1"),
+                row.as_any(),
+                Some(DType::Any),
+                Some(percent),
+                None,
+            )?;
+            // `num_format` overrides the number format of `format`
+            let bold = Some(format(true, None, Some("0.00%")));
+            let num_format = Some("0.0".to_string());
+            ws.write_cell(
+                cell("A2"),
+                row.get_item(0)?.as_any(),
+                None,
+                bold,
+                num_format,
+            )?;
+            ws.write_formula(cell("B2"), "=B1*2".to_string(), None, Some(header))?;
+            ws.write_cell(cell("C2"), text.as_any(), None, None, None)
+        });
+        let styles = xml(&buffer, "xl/styles.xml");
+        assert!(styles.contains("<b/>"));
+        assert!(styles.contains(r#"<fgColor rgb="FFFF0000"/>"#));
+        assert!(styles.contains(r#"formatCode="0.00%""#));
+        assert!(styles.contains(r#"formatCode="0.0""#));
+        let sheet = xml(&buffer, "xl/worksheets/sheet1.xml");
+        let [a1, b1, c1, a2, b2, c2] =
+            ["A1", "B1", "C1", "A2", "B2", "C2"].map(|a| style(&sheet, a));
+        assert!(a1.is_some() && b1.is_some() && a2.is_some());
+        assert_eq!(b1, c1);
+        assert_eq!(b2, a1);
+        assert_ne!(a1, b1);
+        assert_ne!(a2, b1);
+        assert_eq!(c2, None);
+        // The formats do not change the values
+        let ws = reopen(buffer).get_by_idx_rs(0, false).unwrap();
+        assert_eq!(
+            read(&ws, "A1:C2", DType::Any),
+            ["header", "0.5", "0.25", "0.5", "0", "header"]
+        );
+    }
+
+    #[test]
+    fn date_formats() {
+        let wb = WriteOnlyWorkbook::new("dd.mm.yyyy".to_string(), "dd.mm.yyyy hh:mm".to_string());
+        let buffer = save(wb, |py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            let date = PyDate::new(py, 2024, 1, 31)?;
+            let datetime = PyDateTime::new(py, 2024, 1, 31, 12, 30, 0, 0, None)?;
+            ws.write_cell(cell("A1"), date.as_any(), None, None, None)?;
+            ws.write_cell(cell("A2"), datetime.as_any(), None, None, None)?;
+            // Per call number formats override the defaults of the workbook
+            let iso = Some("yyyy-mm-dd".to_string());
+            ws.write_cell(cell("A3"), date.as_any(), None, None, iso.clone())?;
+            let us = format(false, None, Some("mm/dd/yyyy"));
+            ws.write_cell(cell("A4"), date.as_any(), None, Some(us), None)?;
+            let dates = PyList::new(py, [&date, &date])?;
+            ws.write_row(cell("B3"), dates.as_any(), Some(DType::Date), None, iso)
+        });
+        let styles = xml(&buffer, "xl/styles.xml");
+        for num_format in ["dd.mm.yyyy", "dd.mm.yyyy hh:mm", "yyyy-mm-dd", "mm/dd/yyyy"] {
+            assert!(styles.contains(&format!("formatCode=\"{num_format}\"")));
+        }
+        let sheet = xml(&buffer, "xl/worksheets/sheet1.xml");
+        let [a1, a2, a3, a4, b3] = ["A1", "A2", "A3", "A4", "B3"].map(|a| style(&sheet, a));
+        assert!([&a1, &a2, &a3, &a4].iter().all(|s| s.is_some()));
+        assert_eq!(a3, b3);
+        assert_ne!(a1, a2);
+        assert_ne!(a1, a3);
+        assert_ne!(a3, a4);
+        // The cells are read back as dates, as their number formats are date formats
+        let ws = reopen(buffer).get_by_idx_rs(0, false).unwrap();
+        let date = "2024-01-31 00:00:00";
+        let expected = [
+            date,
+            "",
+            "",
+            "2024-01-31 12:30:00",
+            "",
+            "",
+            date,
+            date,
+            date,
+        ];
+        assert_eq!(read(&ws, "A1:C3", DType::Any), expected);
+        assert_eq!(read(&ws, "A4", DType::Date), ["2024-01-31"]);
+    }
+
+    #[test]
+    fn constant_memory_order() {
+        let buffer = save(workbook(), |py, wb| {
+            let first = sheet(wb, py, "First", true);
+            let regular = sheet(wb, py, "Regular", false);
+            let second = sheet(wb, py, "Second", true);
+            let (mut first, mut second) = (first.borrow_mut(py), second.borrow_mut(py));
+            let [d, e, h] = ["d", "e", "h"].map(|v| v.into_pyobject(py).unwrap());
+            let dtype = Some(DType::Str);
+            let column = PyList::new(py, ["a", "b", "c"])?;
+            first.write_column(cell("A1"), column.as_any(), dtype, None, None)?;
+            // Writing in the last row written is allowed, and the sheets stream their rows
+            // independently
+            first.write_cell(cell("B3"), d.as_any(), dtype, None, None)?;
+            second.write_cell(cell("A1"), e.as_any(), dtype, None, None)?;
+            let row = PyList::new(py, ["f", "g"])?;
+            first.write_row(cell("A5"), row.as_any(), dtype, None, None)?;
+            // Writing above the last row written fails
+            let err = first.write_cell(cell("C2"), h.as_any(), dtype, None, None);
+            let err = err.unwrap_err().to_string();
+            assert!(err.contains("Out-of-order write to row 2"));
+            second.write_cell(cell("A2"), h.as_any(), dtype, None, None)?;
+            // Regular worksheets are written in any order
+            let mut regular = regular.borrow_mut(py);
+            regular.write_cell(cell("B2"), h.as_any(), dtype, None, None)?;
+            regular.write_cell(cell("A1"), h.as_any(), dtype, None, None)
+        });
+        let mut wb = reopen(buffer);
+        assert_eq!(wb.sheetnames, ["First", "Regular", "Second"]);
+        let ws = wb.get_by_name_rs("First".to_string(), false).unwrap();
+        let expected = ["a", "", "b", "", "c", "d", "", "", "f", "g"];
+        assert_eq!(read(&ws, "A1:B5", DType::Any), expected);
+        let ws = wb.get_by_name_rs("Regular".to_string(), false).unwrap();
+        assert_eq!(read(&ws, "A1:B2", DType::Any), ["h", "", "", "h"]);
+        let ws = wb.get_by_name_rs("Second".to_string(), false).unwrap();
+        assert_eq!(read(&ws, "A1:A2", DType::Any), ["e", "h"]);
+    }
+
+    #[test]
+    fn saved_to_bytes_and_files() {
+        pyo3::prepare_freethreaded_python();
+        let dir = std::env::temp_dir().join(format!("fastxlsx-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (path, many_path) = (dir.join("saved.xlsx"), dir.join("many.xlsx"));
+        let (written, many) = Python::with_gil(|py| {
+            let mut wb = workbook();
+            let ws = sheet(&mut wb, py, "Sheet1", false);
+            let value = "text".into_pyobject(py).unwrap();
+            let mut ws = ws.borrow_mut(py);
+            ws.write_cell(cell("B2"), value.as_any(), None, None, None)
+                .unwrap();
+            // `write_many` takes copies of the worksheets
+            let sheets = vec![ws.clone()];
+            drop(ws);
+            wb.save(py, PathOrWriter::Path(path.clone())).unwrap();
+            let writer = py.import("io").unwrap().call_method0("BytesIO").unwrap();
+            wb.save(py, PathOrWriter::Writer(writer.clone().unbind()))
+                .unwrap();
+            let written = writer.call_method0("getvalue").unwrap();
+            let (date_format, datetime_format) =
+                (wb.date_format.clone(), wb.datetime_format.clone());
+            let to_write = IndexMap::from([(many_path.to_string_lossy().to_string(), sheets)]);
+            write_many(
+                py,
+                to_write.clone(),
+                date_format.clone(),
+                datetime_format.clone(),
+            )
+            .unwrap();
+            let many = write_many_to_bytes(py, to_write, date_format, datetime_format).unwrap();
+            let many = many
+                .values()
+                .map(|b| b.as_bytes(py).to_vec())
+                .collect::<Vec<_>>();
+            (written.extract::<Vec<u8>>().unwrap(), many)
+        });
+        let saved = [
+            ReadOnlyWorkbook::from_path(path).unwrap(),
+            ReadOnlyWorkbook::from_path(many_path).unwrap(),
+            reopen(written),
+            reopen(many.into_iter().next().unwrap()),
+        ];
+        std::fs::remove_dir_all(dir).unwrap();
+        for mut wb in saved {
+            let ws = wb.get_by_name_rs("Sheet1".to_string(), false).unwrap();
+            assert_eq!(read(&ws, "A1:B2", DType::Any), ["", "", "", "text"]);
+        }
+    }
+
+    /// A `RangeInfo` of `data_shape` at `addr`
+    fn shaped(addr: &str, data_shape: DShape) -> RangeInfo {
+        let (row, col) = cell(addr).as_idx().unwrap();
+        RangeInfo::new((row as i32, col as i32), data_shape, DType::Any, true)
+    }
+
+    #[test]
+    fn used_range() {
+        let buffer = save(workbook(), |py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            for (addr, row) in [
+                ("C3", vec!["a", "b", "c"]),
+                ("C4", vec!["d"]),
+                ("C5", vec!["e", "f"]),
+            ] {
+                let row = PyList::new(py, row)?;
+                ws.write_row(cell(addr), row.as_any(), Some(DType::Str), None, None)?;
+            }
+            Ok(())
+        });
+        let mut wb = reopen(buffer);
+        let ws = wb.get_by_idx_rs(0, false).unwrap();
+        let read_shaped =
+            |addr, data_shape| texts(ws.read_value_rs(&shaped(addr, data_shape)).unwrap());
+        let to_end = read_shaped("C3", DShape::MatrixToEnd {});
+        assert_eq!(to_end, ["a", "b", "c", "d", "", "", "e", "f", ""]);
+        let from_a1 = read_shaped("A1", DShape::MatrixToEnd {});
+        assert_eq!(from_a1.len(), 25);
+        // Rows 3 to 5 of columns C to E
+        let block = [12, 13, 14, 17, 18, 19, 22, 23, 24].map(|i| from_a1[i].clone());
+        assert_eq!(block.as_slice(), to_end);
+        assert!(from_a1[..10].iter().all(String::is_empty));
+        assert_eq!(read_shaped("C3", DShape::RowToEmpty {}), ["a", "b", "c"]);
+        assert_eq!(read_shaped("C4", DShape::RowToEmpty {}), ["d"]);
+        assert_eq!(read_shaped("C3", DShape::ColumnToEmpty {}), ["a", "d", "e"]);
+        assert_eq!(read_shaped("D3", DShape::ColumnToEmpty {}), ["b"]);
+        // The used range starts at the first used cell, not at A1
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let ws = Bound::new(py, ws).unwrap();
+            let used_range = ws
+                .getattr("used_range")
+                .unwrap()
+                .extract::<RangeInfo>()
+                .unwrap();
+            assert_eq!(used_range.to_addr().unwrap(), "C3:E5");
+            let frame = ws.borrow().read_frame_rs(None, true, None, true).unwrap();
+            assert_eq!(frame.headers.unwrap(), ["a", "b", "c"]);
+            assert_eq!(frame.columns.len(), 3);
+        });
+    }
+
+    #[test]
+    fn absolute_addressing() {
+        let buffer = save(workbook(), |py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            let rows = PyList::new(py, [["a", "b"], ["c", "d"]])?;
+            ws.write_matrix(cell("C5"), rows.as_any(), Some(DType::Str), None, None)
+        });
+        let mut wb = reopen(buffer);
+        let ws = wb.get_by_idx_rs(0, false).unwrap();
+        // Cells are read by their position in the sheet, not in the used range
+        assert_eq!(read(&ws, "A1", DType::Any), [""]);
+        assert_eq!(read(&ws, "C5:D6", DType::Any), ["a", "b", "c", "d"]);
+        assert_eq!(read(&ws, "B4:C5", DType::Any), ["", "", "", "a"]);
+        assert_eq!((ws.n_rows, ws.n_cols), (6, 4));
+        // Negative positions are counted from the end of the sheet
+        let last = RangeInfo::new((-1, -1), DShape::Scalar {}, DType::Any, true);
+        assert_eq!(texts(ws.read_value_rs(&last).unwrap()), ["d"]);
+        let column = RangeInfo::new((-2, -1), DShape::Column { n_rows: 2 }, DType::Any, true);
+        assert_eq!(texts(ws.read_value_rs(&column).unwrap()), ["b", "d"]);
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let ws = Bound::new(py, ws).unwrap();
+            let position = |name| ws.getattr(name).unwrap().extract::<(u32, u32)>().unwrap();
+            assert_eq!(position("start"), (4, 2));
+            assert_eq!(position("end"), (5, 3));
+        });
+    }
+
+    #[test]
+    fn defined_names() {
+        let buffer = save(workbook(), |py, wb| {
+            for title in ["Inputs", "Outputs"] {
+                let ws = sheet(wb, py, title, false);
+                let column = PyList::new(py, [format!("{title} 1"), format!("{title} 2")])?;
+                let mut ws = ws.borrow_mut(py);
+                ws.write_column(cell("B2"), column.as_any(), Some(DType::Str), None, None)?;
+            }
+            wb.define_name("Sales".to_string(), "=Inputs!$B$2:$B$3".to_string())?;
+            wb.define_name("Total".to_string(), "=Outputs!$B$3".to_string())?;
+            wb.define_name("Rate".to_string(), "=0.05".to_string())
+        });
+        let mut wb = reopen(buffer);
+        let names = wb
+            .defined_names
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Rate", "Sales", "Total"]);
+        // Names are read instead of positions, and are not case sensitive
+        let named =
+            |name: &str| RangeInfo::from_name(name.to_string(), RangeDTypes::One(DType::Str), true);
+        let ws = wb.get_by_name_rs("Inputs".to_string(), false).unwrap();
+        assert_eq!(
+            texts(ws.read_value_rs(&named("Sales")).unwrap()),
+            ["Inputs 1", "Inputs 2"]
+        );
+        // A name of another sheet or of a constant is not read from the sheet
+        assert!(ws.read_value_rs(&named("Total")).is_err());
+        assert!(ws.read_value_rs(&named("Rate")).is_err());
+        let to_read = IndexMap::from([
+            (
+                IdxOrName::Name("Inputs".to_string()),
+                ListOrDict::List(vec![named("sales")]),
+            ),
+            (IdxOrName::Idx(1), ListOrDict::List(vec![named("TOTAL")])),
+        ]);
+        let values = wb.read_worksheets_rs(to_read).unwrap();
+        let values = values
+            .into_values()
+            .flat_map(|values| match values {
+                ListOrDict::List(values) => values.into_iter().flat_map(texts).collect::<Vec<_>>(),
+                ListOrDict::Dict(_) => panic!("values read from a list"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(values, ["Inputs 1", "Inputs 2", "Outputs 2"]);
+    }
+
+    #[test]
+    fn tables() {
+        let buffer = save(workbook(), |py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            let dates = [PyDate::new(py, 2024, 1, 31)?, PyDate::new(py, 2024, 2, 29)?];
+            let columns = IndexMap::from([
+                ("Name".to_string(), PyList::new(py, ["a", "b"])?.into_any()),
+                ("When".to_string(), PyList::new(py, dates)?.into_any()),
+                (
+                    "Amount".to_string(),
+                    PyList::new(py, [1.5, 2.0])?.into_any(),
+                ),
+            ]);
+            let total_row = IndexMap::from([
+                ("Name".to_string(), "Total".to_string()),
+                ("Amount".to_string(), "sum".to_string()),
+            ]);
+            let dtypes = ColumnDTypes::List(vec![DType::Str, DType::Date, DType::Any]);
+            ws.write_table(
+                cell("B2"),
+                columns,
+                Some("Sales".to_string()),
+                Some("Light1".to_string()),
+                TotalRow::Columns(total_row),
+                Some(dtypes),
+            )
+        });
+        let tables = xml(&buffer, "xl/tables/table1.xml");
+        assert!(tables.contains(r#"ref="B2:D5""#));
+        assert!(tables.contains(r#"totalsRowFunction="sum""#));
+        assert!(tables.contains(r#"name="TableStyleLight1""#));
+        let mut wb = reopen(buffer);
+        assert_eq!(wb.table_names_rs(Some("Sheet1")).unwrap(), ["Sales"]);
+        let columns = wb
+            .read_table_rs("Sales", &ColumnDTypes::One(DType::Any), true)
+            .unwrap();
+        let headers = columns.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(headers, ["Name", "When", "Amount"]);
+        let values = columns.into_values().flat_map(texts).collect::<Vec<_>>();
+        let date = |d| format!("2024-{d} 00:00:00");
+        assert_eq!(
+            values,
+            ["a", "b", &date("01-31"), &date("02-29"), "1.5", "2"]
+        );
+        // The total row is written below the data
+        let ws = wb.get_by_name_rs("Sheet1".to_string(), true).unwrap();
+        assert_eq!(read(&ws, "B5", DType::Any), ["Total"]);
+        let formulas = ws.formulas.unwrap();
+        let total = formulas.get_value((4, 3)).unwrap().to_string();
+        assert_eq!(total, "=SUBTOTAL(109,[Amount])");
+    }
+
+    #[test]
+    fn formulas() {
+        let buffer = save(workbook(), |py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            let column = PyList::new(py, [1, 2])?;
+            ws.write_column(cell("A1"), column.as_any(), Some(DType::Any), None, None)?;
+            let value = Some(CalamineData::Int(3));
+            ws.write_formula(cell("A3"), "=SUM(A1:A2)".to_string(), value, None)?;
+            let range = RangeAddr::Name("B1:B2".to_string());
+            let value = Some(CalamineData::Int(2));
+            ws.write_array_formula(range, "{=A1:A2*2}".to_string(), value, None)
+        });
+        let mut wb = reopen(buffer);
+        let ws = wb.get_by_name_rs("Sheet1".to_string(), true).unwrap();
+        assert_eq!(
+            read(&ws, "A1:B3", DType::Any),
+            ["1", "2", "2", "0", "3", ""]
+        );
+        // Cells without a formula are read as None, array formulas are stored in their first
+        // cell
+        let mut formulas =
+            RangeInfo::from_addr("A1:B3", RangeDTypes::One(DType::Any), true).unwrap();
+        formulas.formulas = true;
+        let expected = ["", "=A1:A2*2", "", "", "=SUM(A1:A2)", ""];
+        assert_eq!(texts(ws.read_value_rs(&formulas).unwrap()), expected);
+        // Formulas are not read from sheets got without them
+        let ws = wb.get_by_name_rs("Sheet1".to_string(), false).unwrap();
+        assert!(ws.read_value_rs(&formulas).is_err());
+        // `read_worksheets` gets the formulas of the sheets they are read from
+        let values = wb
+            .read_worksheets_rs(IndexMap::from([(
+                IdxOrName::Idx(0),
+                ListOrDict::List(vec![formulas]),
+            )]))
+            .unwrap();
+        let Some(ListOrDict::List(values)) = values.into_values().next() else {
+            panic!("values read from a list");
+        };
+        assert_eq!(
+            values.into_iter().flat_map(texts).collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn detected_file_format() {
+        let buffer = save(workbook(), |py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let value = "text".into_pyobject(py)?;
+            let mut ws = ws.borrow_mut(py);
+            ws.write_cell(cell("A1"), value.as_any(), None, None, None)
+        });
+        // The format is detected from the content, whatever the extension
+        let dir = std::env::temp_dir().join(format!("fastxlsx-format-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = ["book.xlsx", "book.xlsb", "book.ods", "book"].map(|name| dir.join(name));
+        let mut workbooks = paths
+            .iter()
+            .map(|path| {
+                std::fs::write(path, &buffer).unwrap();
+                ReadOnlyWorkbook::from_path(path.clone()).unwrap()
+            })
+            .collect::<Vec<_>>();
+        std::fs::remove_dir_all(dir).unwrap();
+        workbooks.push(reopen(buffer));
+        for mut wb in workbooks {
+            assert!(matches!(wb.reader, Sheets::Xlsx(_)));
+            let ws = wb.get_by_idx_rs(0, false).unwrap();
+            assert_eq!(read(&ws, "A1", DType::Any), ["text"]);
+        }
+        let err = ReadOnlyWorkbook::from_bytes_rs(b"not a workbook".to_vec())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("could not be read as xlsx"));
+    }
+
+    #[test]
+    fn cached_sheets() {
+        let buffer = save(workbook(), |py, wb| {
+            for title in ["Sheet1", "Sheet2"] {
+                let ws = sheet(wb, py, title, false);
+                let value = title.into_pyobject(py)?;
+                let mut ws = ws.borrow_mut(py);
+                ws.write_cell(cell("A1"), value.as_any(), None, None, None)?;
+            }
+            Ok(())
+        });
+        let get =
+            |wb: &mut ReadOnlyWorkbook, idx_or_name| wb.get_rs(idx_or_name, false).unwrap().sheet;
+        let (first, second) = (IdxOrName::Idx(0), IdxOrName::Name("Sheet1".to_string()));
+        // Without the cache, sheets are parsed every time they are got
+        let mut wb = reopen(buffer.clone());
+        assert!(!Arc::ptr_eq(
+            &get(&mut wb, first.clone()),
+            &get(&mut wb, second.clone())
+        ));
+        assert!(wb.sheet_cache.is_empty());
+        let mut wb = reopen(buffer);
+        wb.cache = true;
+        let sheet = get(&mut wb, first.clone());
+        assert!(Arc::ptr_eq(&sheet, &get(&mut wb, second.clone())));
+        let range = RangeInfo::from_addr("A1", RangeDTypes::One(DType::Str), true).unwrap();
+        let to_read = IndexMap::from([
+            (first.clone(), ListOrDict::List(vec![range.clone()])),
+            (IdxOrName::Idx(1), ListOrDict::List(vec![range])),
+        ]);
+        let values = wb.read_worksheets_rs(to_read).unwrap();
+        let values = values.into_values().flat_map(|values| match values {
+            ListOrDict::List(values) => values.into_iter().flat_map(texts).collect::<Vec<_>>(),
+            ListOrDict::Dict(_) => panic!("values read from a list"),
+        });
+        assert_eq!(values.collect::<Vec<_>>(), ["Sheet1", "Sheet2"]);
+        // The cached sheet is reused, the other one is only parsed for the cells read, which
+        // is not cached
+        assert_eq!(wb.sheet_cache.len(), 1);
+        assert!(Arc::ptr_eq(&sheet, &get(&mut wb, second.clone())));
+        // An evicted sheet is parsed again
+        wb.evict_rs(first.clone());
+        assert!(wb.sheet_cache.is_empty());
+        let parsed_again = get(&mut wb, first);
+        assert!(!Arc::ptr_eq(&sheet, &parsed_again));
+        assert_eq!(
+            parsed_again.get_value((0, 0)),
+            Some(&Data::String("Sheet1".to_string()))
+        );
+    }
+
+    #[test]
+    fn column_dtypes() {
+        let buffer = save(workbook(), |py, wb| {
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            let rows = [(1, 10i64, "a", 1.5f64), (2, 20, "b", 2.5)];
+            for (row, (day, id, name, value)) in rows.into_iter().enumerate() {
+                let values = [
+                    PyDate::new(py, 2024, 1, day)?.into_any(),
+                    id.into_pyobject(py)?.into_any(),
+                    name.into_pyobject(py)?.into_any(),
+                    value.into_pyobject(py)?.into_any(),
+                ];
+                let values = PyList::new(py, values)?;
+                let addr = CellAddr::Idx((row, 0));
+                ws.write_row(addr, values.as_any(), Some(DType::Any), None, None)?;
+            }
+            Ok(())
+        });
+        let mut wb = reopen(buffer);
+        let ws = wb.get_by_idx_rs(0, false).unwrap();
+        let read_as = |dtypes| {
+            let range_info = RangeInfo::from_addr("A1:D2", RangeDTypes::Columns(dtypes), true);
+            ws.read_value_rs(&range_info.unwrap())
+        };
+        let dtypes = vec![DType::Date, DType::Int, DType::Str, DType::Float];
+        let WrappedValue::Columns(columns) = read_as(dtypes.clone()).unwrap() else {
+            panic!("columns read with their own data types");
+        };
+        let read_dtypes = columns.values().map(WrappedValue::get_dtype);
+        assert_eq!(read_dtypes.collect::<Vec<_>>(), dtypes);
+        let values = columns.into_values().flat_map(texts).collect::<Vec<_>>();
+        let expected = [
+            "2024-01-01",
+            "2024-01-02",
+            "10",
+            "20",
+            "a",
+            "b",
+            "1.5",
+            "2.5",
+        ];
+        assert_eq!(values, expected);
+        // The data types must match the columns
+        assert!(read_as(vec![DType::Date, DType::Int]).is_err());
+        assert!(read_as(vec![DType::Date, DType::Int, DType::Int, DType::Float]).is_err());
+    }
+
+    /// Frames with the API of pandas and polars used by `write_dataframe`, with text columns, as
+    /// numpy is not required by the tests
+    const MOCK_FRAMES: &std::ffi::CStr = c_str!(
+        r#"
+from types import SimpleNamespace
+
+class Array(list):
+    dtype = SimpleNamespace(kind="U", str="<U5")
+
+    def tolist(self):
+        return list(self)
+
+class Series:
+    def __init__(self, name, values, tz=None, polars=False):
+        self.name, self.values, self.polars = name, values, polars
+        self.dtype = SimpleNamespace(time_zone=tz) if polars else SimpleNamespace(tz=tz)
+        self.dt = self
+
+    def to_numpy(self):
+        return Array(self.values)
+
+    def local(self, tz):
+        # The times with a time zone are "HH:MM+HH", their local times "HH:MM"
+        assert tz is None
+        return Series(self.name, [v[:5] for v in self.values], polars=self.polars)
+
+    def __getattr__(self, name):
+        if name == ("replace_time_zone" if self.polars else "tz_localize"):
+            return self.local
+        raise AttributeError(name)
+
+class PandasFrame:
+    def __init__(self, *series):
+        self.series = series
+
+    def items(self):
+        return [(s.name, s) for s in self.series]
+
+class PolarsFrame:
+    def __init__(self, *series):
+        self.series = series
+
+    def get_columns(self):
+        return list(self.series)
+
+def frames():
+    columns = lambda polars: [
+        Series("name", ["a", "b"], polars=polars),
+        Series("time", ["12:00+01", "13:30+01"], tz="+01:00", polars=polars),
+    ]
+    return PandasFrame(*columns(False)), PolarsFrame(*columns(True))
+"#
+    );
+
+    #[test]
+    fn mock_dataframes() {
+        let buffer = save(workbook(), |py, wb| {
+            let module =
+                PyModule::from_code(py, MOCK_FRAMES, c_str!("frames.py"), c_str!("frames"))?;
+            let (pandas, polars) = module
+                .call_method0("frames")?
+                .extract::<(Bound<PyAny>, Bound<PyAny>)>()?;
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            let bold = ColumnFormats::Dict(IndexMap::from([(
+                "name".to_string(),
+                format(true, None, None),
+            )]));
+            ws.write_dataframe(cell("A1"), &pandas, true, false, Some(bold))?;
+            ws.write_dataframe(cell("D2"), &polars, false, false, None)?;
+            // Only pandas DataFrames have an index
+            assert!(ws
+                .write_dataframe(cell("A10"), &polars, true, true, None)
+                .is_err());
+            Ok(())
+        });
+        let sheet = xml(&buffer, "xl/worksheets/sheet1.xml");
+        assert!(style(&sheet, "A2").is_some());
+        assert_eq!(style(&sheet, "B2"), None);
+        assert_eq!(style(&sheet, "A1"), None);
+        let ws = reopen(buffer).get_by_idx_rs(0, false).unwrap();
+        // Times with a time zone are written as their local times
+        let expected = [
+            "name", "time", "", "", "", "a", "12:00", "", "a", "12:00", "b", "13:30", "", "b",
+            "13:30",
+        ];
+        assert_eq!(read(&ws, "A1:E3", DType::Any), expected);
+    }
+
+    #[test]
+    fn pandas_dataframe() {
+        pyo3::prepare_freethreaded_python();
+        if Python::with_gil(|py| py.import("pandas").is_err()) {
+            eprintln!("pandas is not installed, skipping");
+            return;
+        }
+        let buffer = save(workbook(), |py, wb| {
+            let frame = PyModule::from_code(
+                py,
+                c_str!(
+                    r#"
+import datetime
+import pandas as pd
+
+tz = datetime.timezone(datetime.timedelta(hours=1))
+df = pd.DataFrame(
+    {
+        "int": [1, 2],
+        "big": pd.Series([2**64 - 1, 1], dtype="uint64"),
+        "float": [1.5, float("nan")],
+        "when": pd.Series([pd.Timestamp(2024, 1, 31, 12, tz=tz), pd.NaT]),
+        "name": ["a", None],
+        "flag": [True, False],
+    },
+    index=pd.Index([10, 20], name="id"),
+)
+"#
+                ),
+                c_str!("frame.py"),
+                c_str!("frame"),
+            )?
+            .getattr("df")?;
+            let ws = sheet(wb, py, "Sheet1", false);
+            let mut ws = ws.borrow_mut(py);
+            ws.write_dataframe(cell("A1"), &frame, true, true, None)
+        });
+        let ws = reopen(buffer).get_by_idx_rs(0, false).unwrap();
+        // Unsigned integers past the range of int64 are written as floats, datetimes with a time
+        // zone as their local times, and missing values as blank cells
+        let expected = [
+            ["id", "int", "big", "float", "when", "name", "flag"],
+            [
+                "10",
+                "1",
+                "18446744073709552000",
+                "1.5",
+                "2024-01-31 12:00:00",
+                "a",
+                "true",
+            ],
+            ["20", "2", "1", "", "", "", "false"],
+        ];
+        assert_eq!(read(&ws, "A1:G3", DType::Any), expected.concat());
+    }
+}